@@ -0,0 +1,36 @@
+#include "bluerobotics_navigator.h"
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+int main(void) {
+  const char *ci_env = getenv("CI");
+  if (ci_env && strcmp(ci_env, "true") == 0) {
+    printf("Running from CI\n");
+    printf("Not possible to test navigator sensors yet.\n");
+
+    return 0;
+  }
+
+  printf("Initiating navigator module.\n");
+  init();
+
+  printf("Setting led on!\n");
+  set_led(UserLed_Led1, true);
+
+  printf("Temperature: %f\n", read_temp());
+  printf("Pressure: %f\n", read_pressure());
+
+  float adc[4];
+  read_adc_all(adc, 4);
+  printf("Reading ADC Channels: 1 = %f, 2 = %f, 3 = %f, 4 = %f\n", adc[0],
+         adc[1], adc[2], adc[3]);
+
+  AxisData accel = read_accel();
+  printf("Acceleration: X = %f, Y = %f, Z = %f\n", accel.x, accel.y, accel.z);
+
+  printf("Setting led off!\n");
+  set_led(UserLed_Led1, false);
+
+  return 0;
+}
@@ -0,0 +1,34 @@
+#include "navigator.hpp"
+#include <stdio.h>
+
+int main() {
+  const char *ci_env = std::getenv("CI");
+  if (ci_env && std::string(ci_env) == "true") {
+    printf("Running from CI\n");
+    printf("Not possible to test navigator sensors yet.\n");
+
+    return 0;
+  }
+
+  // The instance is released when `nav` goes out of scope
+  navigator::Navigator nav = navigator::Builder()
+                                 .rgb_led_strip_size(1)
+                                 .board_orientation(Rotation::NoRotation)
+                                 .build();
+
+  nav.set_led(UserLed::Led1, true);
+  printf("Temperature: %f\n", nav.read_temp());
+
+  SensorData data = nav.read_all();
+  printf("Acceleration: X = %f, Y = %f, Z = %f\n", data.accelerometer.x,
+         data.accelerometer.y, data.accelerometer.z);
+  printf("Acceleration norm: %f\n", navigator::norm(data.accelerometer));
+
+  std::array<float, 4> adc = nav.read_adc_all();
+  printf("ADC Channels: 1 = %f, 2 = %f, 3 = %f, 4 = %f\n", adc[0], adc[1],
+         adc[2], adc[3]);
+
+  nav.set_led(UserLed::Led1, false);
+
+  return 0;
+}
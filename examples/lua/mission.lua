@@ -0,0 +1,27 @@
+-- Blinks the LED and ramps up the first PWM channel until the target depth is reached.
+-- cargo run --release --features lua --bin navigator-lua -- --timeout 120 mission.lua
+
+local TARGET_PRESSURE = 110 -- [kPa]
+
+-- Needed when another process could drive the PWM through navigatord
+navigator.claim_pwm()
+navigator.set_pwm_freq_hz(50)
+navigator.set_pwm_enable(true)
+
+local duty_cycle = 0.075
+while navigator.read_pressure() < TARGET_PRESSURE do
+    if navigator.read_leak() then
+        error("Leak detected")
+    end
+
+    navigator.set_led_toggle(navigator.UserLed.Led1)
+    duty_cycle = math.min(duty_cycle + 0.001, 0.085)
+    navigator.set_pwm_channel_duty_cycle(navigator.PwmChannel.Ch1, duty_cycle)
+
+    local accel = navigator.read_accel()
+    print(string.format("%.1fs: %.2f kPa, accel z %.2f", navigator.clock(), navigator.read_pressure(), accel.z))
+    navigator.sleep(0.2)
+end
+
+navigator.set_pwm_channel_duty_cycle(navigator.PwmChannel.Ch1, 0.075)
+navigator.set_led(navigator.UserLed.Led1, false)
@@ -0,0 +1,31 @@
+#!/usr/bin/env node
+'use strict';
+
+const navigator = require('../../node');
+const { UserLed } = navigator;
+
+async function main() {
+  navigator.init();
+
+  console.log(`Temperature: ${navigator.readTemp()}`);
+  console.log(`Pressure: ${navigator.readPressure()}`);
+  console.log(`ADC: ${navigator.readAdcAll()}`);
+
+  navigator.setLedToggle(UserLed.Led1);
+  navigator.setNeopixel([[0, 0, 255]]);
+
+  let samples = 0;
+  for await (const { accelerometer, gyro, magnetometer } of navigator.streamImu(50)) {
+    console.log('Accelerometer:', accelerometer);
+    console.log('Gyroscope:', gyro);
+    console.log('Magnetometer:', magnetometer);
+    if (++samples === 10) {
+      break;
+    }
+  }
+}
+
+main().catch((error) => {
+  console.error(error);
+  process.exit(1);
+});
@@ -0,0 +1,25 @@
+#!/usr/bin/env python
+
+import asyncio
+from bluerobotics_navigator import aio
+
+
+async def print_temperature():
+    while True:
+        print(f"Temperature: {await aio.read_temp()}")
+        await asyncio.sleep(1)
+
+
+async def print_imu():
+    async for accel, gyro, mag in aio.stream_imu(rate=200):
+        print(f"Acceleration: X = {accel.x}, Y = {accel.y}, Z = {accel.z}")
+        print(f"Gyroscope: X = {gyro.x}, Y = {gyro.y}, Z = {gyro.z}")
+        print(f"Magnetic field: X = {mag.x}, Y = {mag.y}, Z = {mag.z}")
+
+
+async def main():
+    await asyncio.gather(print_temperature(), print_imu())
+
+
+if __name__ == "__main__":
+    asyncio.run(main())
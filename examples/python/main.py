@@ -1,7 +1,6 @@
 #!/usr/bin/env python
 
 import os
-import platform
 import bluerobotics_navigator as navigator
 from bluerobotics_navigator import AdcChannel, UserLed
 
@@ -16,13 +15,10 @@ def navigator_check():
 
     print("Initializing navigator module.")
 
-    if platform.machine() == "aarch64":
-        # It's possible to set the configuration before initializing the navigator, check this example
-        from bluerobotics_navigator import NavigatorVersion, Raspberry
-        print("Setting up for Navigator V2 on Raspberry Pi 5")
-        navigator.set_rgb_led_strip_size(1)
-        navigator.set_navigator_version(NavigatorVersion.Version2)
-        navigator.set_raspberry_pi_version(Raspberry.Pi5)
+    # The board and Raspberry Pi versions are detected, set_navigator_version and
+    # set_raspberry_pi_version can be used before the first call if the detection fails
+    hardware = navigator.get_detected_hardware()
+    print(f"Detected {hardware.navigator_version} on {hardware.raspberry_pi_version}")
 
     navigator.init()
 
@@ -0,0 +1,58 @@
+#!/usr/bin/env python
+
+# Stand-in for `navigatord` without a Navigator board: answers the daemon protocol with fixed
+# readings, after a delay simulating a slow bus. Used to run the examples in CI.
+
+import argparse
+import os
+import socket
+import threading
+import time
+
+AXIS = "0.1 0.2 9.8"
+READINGS = {
+    "read_adc_all": "1 2 3 4",
+    "read_adc": "1",
+    "read_pressure": "101.3",
+    "read_temperature": "21.5",
+    "read_mag": AXIS,
+    "read_accel": AXIS,
+    "read_gyro": AXIS,
+    "read_leak": "0",
+    "get_led": "0",
+    "get_pwm_state": "0 50 121 " + " ".join(["0"] * 16),
+    # ADC, temperature, pressure, leak, then the accelerometer, magnetometer and gyroscope axes
+    "read_all": f"1 2 3 4 21.5 101.3 0 {AXIS} {AXIS} {AXIS}",
+}
+
+
+def serve_client(connection, delay):
+    with connection, connection.makefile("rw") as stream:
+        for line in stream:
+            command = line.split()[0] if line.strip() else ""
+            reading = READINGS.get(command)
+            if reading is not None:
+                time.sleep(delay)
+            stream.write(f"ok {reading or ''}".rstrip() + "\n")
+            stream.flush()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Serves fixed readings on a Unix socket.")
+    parser.add_argument("socket_path")
+    parser.add_argument("--delay", type=float, default=0.0, help="Delay of each reading [s]")
+    args = parser.parse_args()
+
+    if os.path.exists(args.socket_path):
+        os.remove(args.socket_path)
+    listener = socket.socket(socket.AF_UNIX)
+    listener.bind(args.socket_path)
+    listener.listen()
+    print(f"Serving on {args.socket_path}", flush=True)
+    while True:
+        connection, _ = listener.accept()
+        threading.Thread(target=serve_client, args=(connection, args.delay), daemon=True).start()
+
+
+if __name__ == "__main__":
+    main()
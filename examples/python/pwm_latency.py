@@ -0,0 +1,62 @@
+#!/usr/bin/env python
+
+# Benchmark: time to update all PWM channels one call at a time, and in a single batch.
+# Use `--backend daemon` to measure the requests to `navigatord` instead.
+
+import argparse
+import os
+import statistics
+import time
+import bluerobotics_navigator as navigator
+from bluerobotics_navigator import Backend
+
+CHANNELS = list(range(16))
+ITERATIONS = 200
+
+
+def measure(update):
+    durations = []
+    for iteration in range(ITERATIONS):
+        duty_cycle = 0.05 + 0.05 * (iteration % 2)
+        start = time.perf_counter()
+        update(duty_cycle)
+        durations.append(time.perf_counter() - start)
+    return statistics.median(durations) * 1e3, max(durations) * 1e3
+
+
+def per_channel(duty_cycle):
+    for channel in CHANNELS:
+        navigator.set_pwm_channel_duty_cycle(channel, duty_cycle)
+
+
+def batch(duty_cycle):
+    navigator.set_pwm_channels_duty_cycle_values(
+        CHANNELS, [duty_cycle + channel * 0.01 for channel in CHANNELS]
+    )
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Times the PWM channel updates.")
+    parser.add_argument("--backend", choices=["local", "daemon"], default="local")
+    args = parser.parse_args()
+
+    if os.environ.get("CI") == "true" and args.backend == "local":
+        print("Running in CI")
+        print("Not possible to test navigator PWM yet.")
+        return
+
+    if args.backend == "daemon":
+        navigator.set_backend(Backend.Daemon)
+    navigator.init()
+    navigator.claim_pwm()
+    navigator.set_pwm_freq_hz(50)
+
+    for name, update in [("16 single-channel calls", per_channel), ("1 batch call", batch)]:
+        median, worst = measure(update)
+        print(f"{name}: median {median:.3f} ms, max {worst:.3f} ms")
+
+    navigator.set_pwm_channels_duty_cycle_values(CHANNELS, [0.0] * len(CHANNELS))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,100 @@
+#!/usr/bin/env python
+
+# Stress test: sensor reads release the GIL, so Python threads keep running while a read is
+# blocked on the hardware. Use `--mock` (the default in CI) to read from mock_navigatord.py, which
+# delays each reading, instead of the board.
+
+import argparse
+import os
+import subprocess
+import sys
+import tempfile
+import threading
+import time
+import bluerobotics_navigator as navigator
+from bluerobotics_navigator import Backend
+
+MOCK_READ_DELAY = 0.1
+READ_CYCLES = 5
+# Share of its free-running speed the counter must keep during the reads, a held GIL gives ~0
+MIN_PROGRESS = 0.25
+
+READS = [
+    navigator.read_accel,
+    navigator.read_gyro,
+    navigator.read_mag,
+    navigator.read_pressure,
+    navigator.read_adc_all,
+    navigator.read_all,
+]
+
+
+def count(stop, counter):
+    while not stop.is_set():
+        counter[0] += 1
+
+
+def counter_rate(counter, action):
+    """Counter increments per second while `action` runs in this thread."""
+    before, start = counter[0], time.perf_counter()
+    action()
+    return (counter[0] - before) / (time.perf_counter() - start)
+
+
+def start_mock(socket_path):
+    mock = os.path.join(os.path.dirname(os.path.abspath(__file__)), "mock_navigatord.py")
+    process = subprocess.Popen(
+        [sys.executable, mock, socket_path, "--delay", str(MOCK_READ_DELAY)]
+    )
+    while not os.path.exists(socket_path):
+        if process.poll() is not None:
+            raise RuntimeError("mock_navigatord.py exited")
+        time.sleep(0.01)
+    return process
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Checks that Python threads run during reads.")
+    parser.add_argument("--mock", action="store_true", default=os.environ.get("CI") == "true")
+    args = parser.parse_args()
+
+    mock = None
+    if args.mock:
+        print("Reading from mock_navigatord.py")
+        socket_path = os.path.join(tempfile.mkdtemp(), "navigator.sock")
+        mock = start_mock(socket_path)
+        navigator.set_backend(Backend.Daemon, socket_path)
+
+    try:
+        navigator.init()
+
+        stop = threading.Event()
+        counter = [0]
+        counter_thread = threading.Thread(target=count, args=(stop, counter))
+        counter_thread.start()
+
+        # time.sleep releases the GIL, giving the speed of the counter running alone
+        idle = counter_rate(counter, lambda: time.sleep(MOCK_READ_DELAY))
+        print(f"Counter alone: {idle:.0f} increments/s")
+
+        failed = []
+        for read in READS:
+            during = counter_rate(counter, lambda: [read() for _ in range(READ_CYCLES)])
+            progress = during / idle
+            print(f"Counter during {read.__name__}: {during:.0f} increments/s ({progress:.0%})")
+            if progress < MIN_PROGRESS:
+                failed.append(read.__name__)
+
+        stop.set()
+        counter_thread.join()
+    finally:
+        if mock is not None:
+            mock.terminate()
+            mock.wait()
+
+    assert not failed, f"Python threads stalled during {', '.join(failed)}"
+    print("Python threads kept running while the reads were blocked.")
+
+
+if __name__ == "__main__":
+    main()
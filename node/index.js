@@ -0,0 +1,21 @@
+'use strict';
+
+// Loads the addon built with `cargo build --release --lib --features node`, copied next to this file
+// as `bluerobotics_navigator.node` (see the README).
+const path = require('path');
+
+const navigator = require(
+  process.env.NAVIGATOR_ADDON || path.join(__dirname, 'bluerobotics_navigator.node'),
+);
+
+// Streams implement `next()` in Rust, expose them to `for await`. Leaving the loop early calls
+// `return()`, which stops the sampling thread.
+navigator.SensorStream.prototype[Symbol.asyncIterator] = function () {
+  return this;
+};
+navigator.SensorStream.prototype.return = function (value) {
+  this.close();
+  return Promise.resolve({ done: true, value });
+};
+
+module.exports = navigator;
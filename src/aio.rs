@@ -0,0 +1,405 @@
+use std::collections::VecDeque;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use pyo3::exceptions::{PyRuntimeError, PyStopAsyncIteration};
+use pyo3::prelude::*;
+
+use crate::handle::Instance;
+use crate::{panic_message, spawn_worker, with_default_instance, AdcChannel, AxisData};
+
+// Samples kept by a stream while nobody is awaiting, older ones are dropped
+const STREAM_BUFFER_SIZE: usize = 64;
+
+// Threads running the asynchronous reads, more wouldn't help since reads wait for each other on
+// the navigator lock
+const READ_WORKERS: usize = 4;
+
+type Read = Box<dyn FnOnce() + Send>;
+
+lazy_static! {
+    static ref READS: Mutex<mpsc::Sender<Read>> = {
+        let (sender, receiver) = mpsc::channel::<Read>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..READ_WORKERS {
+            let receiver = receiver.clone();
+            spawn_worker(move || loop {
+                // Unlocked before running the read, so other workers can take the next one
+                let read = receiver.lock().unwrap().recv();
+                let Ok(read) = read else { return };
+                read();
+            });
+        }
+        Mutex::new(sender)
+    };
+}
+
+#[pyfunction]
+fn set_future_result(future: &PyAny, value: PyObject) -> PyResult<()> {
+    // The future may have been cancelled while the hardware was being accessed
+    if !future.call_method0("done")?.is_true()? {
+        future.call_method1("set_result", (value,))?;
+    }
+    Ok(())
+}
+
+#[pyfunction]
+fn set_future_exception(future: &PyAny, exception: PyObject) -> PyResult<()> {
+    if !future.call_method0("done")?.is_true()? {
+        future.call_method1("set_exception", (exception,))?;
+    }
+    Ok(())
+}
+
+/// Resolves `future` from any thread, through its event loop.
+fn resolve(py: Python, event_loop: &PyObject, future: &PyObject, result: PyResult<PyObject>) {
+    // Fails only if the event loop is already closed, then nobody is waiting for it
+    let _ = match result {
+        Ok(value) => wrap_pyfunction!(set_future_result, py).and_then(|callback| {
+            event_loop.call_method1(py, "call_soon_threadsafe", (callback, future, value))
+        }),
+        Err(error) => wrap_pyfunction!(set_future_exception, py).and_then(|callback| {
+            let exception = error.into_value(py);
+            event_loop.call_method1(py, "call_soon_threadsafe", (callback, future, exception))
+        }),
+    };
+}
+
+/// Creates a future on the running event loop, resolved with `read` output from a worker thread.
+fn spawn_read<T, F>(py: Python, read: F) -> PyResult<PyObject>
+where
+    T: IntoPy<PyObject>,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let event_loop: PyObject = py
+        .import("asyncio")?
+        .call_method0("get_running_loop")?
+        .into();
+    let future = event_loop.call_method0(py, "create_future")?;
+
+    let (thread_loop, thread_future) = (event_loop.clone_ref(py), future.clone_ref(py));
+    let read: Read = Box::new(move || {
+        let result = catch_unwind(AssertUnwindSafe(read)).map_err(panic_message);
+        Python::with_gil(|py| {
+            let result = result
+                .map(|value| value.into_py(py))
+                .map_err(PyRuntimeError::new_err);
+            resolve(py, &thread_loop, &thread_future, result);
+        });
+    });
+    READS
+        .lock()
+        .unwrap()
+        .send(read)
+        .map_err(|_| PyRuntimeError::new_err("Asynchronous reads are stopped"))?;
+
+    Ok(future)
+}
+
+struct StreamState {
+    samples: VecDeque<Result<PyObject, String>>,
+    waiting: VecDeque<(PyObject, PyObject)>,
+    closed: bool,
+}
+
+/// Asynchronous iterator over sensor samples, taken at a fixed rate by a Rust thread.
+#[pyclass]
+pub struct SensorStream {
+    state: Arc<Mutex<StreamState>>,
+}
+
+impl SensorStream {
+    fn spawn<T, F>(rate: f32, read: F) -> PyResult<Self>
+    where
+        T: IntoPy<PyObject>,
+        F: Fn() -> T + Send + 'static,
+    {
+        if rate.is_nan() || rate <= 0.0 {
+            return Err(PyRuntimeError::new_err(format!("Invalid rate: {rate}")));
+        }
+        let period = Duration::from_secs_f32(1.0 / rate);
+        let state = Arc::new(Mutex::new(StreamState {
+            samples: VecDeque::new(),
+            waiting: VecDeque::new(),
+            closed: false,
+        }));
+
+        let thread_state = state.clone();
+        spawn_worker(move || {
+            let mut deadline = Instant::now();
+            while !thread_state.lock().unwrap().closed {
+                let result = catch_unwind(AssertUnwindSafe(&read)).map_err(panic_message);
+                Python::with_gil(|py| {
+                    let result = result.map(|value| value.into_py(py));
+                    // Resolved once the state is unlocked, the event loop may release the GIL
+                    // meanwhile and `__anext__` lock the state while holding it
+                    let mut state = thread_state.lock().unwrap();
+                    match state.waiting.pop_front() {
+                        Some((event_loop, future)) => {
+                            drop(state);
+                            let result = result.map_err(PyRuntimeError::new_err);
+                            resolve(py, &event_loop, &future, result);
+                        }
+                        None => {
+                            if state.samples.len() == STREAM_BUFFER_SIZE {
+                                state.samples.pop_front();
+                            }
+                            state.samples.push_back(result);
+                        }
+                    }
+                });
+
+                deadline += period;
+                let now = Instant::now();
+                if deadline > now {
+                    std::thread::sleep(deadline - now);
+                } else {
+                    // Too slow to keep up with the rate, don't try to catch up
+                    deadline = now;
+                }
+            }
+        });
+
+        Ok(Self { state })
+    }
+}
+
+#[pymethods]
+impl SensorStream {
+    fn __aiter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __anext__(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let event_loop: PyObject = py
+            .import("asyncio")?
+            .call_method0("get_running_loop")?
+            .into();
+        let future = event_loop.call_method0(py, "create_future")?;
+
+        let sample = {
+            let mut state = self.state.lock().unwrap();
+            if state.closed {
+                return Ok(None);
+            }
+            let sample = state.samples.pop_front();
+            if sample.is_none() {
+                state.waiting.push_back((event_loop, future.clone_ref(py)));
+            }
+            sample
+        };
+        match sample {
+            Some(Ok(value)) => {
+                future.call_method1(py, "set_result", (value,))?;
+            }
+            Some(Err(message)) => {
+                future.call_method1(py, "set_exception", (PyRuntimeError::new_err(message),))?;
+            }
+            None => {}
+        }
+        Ok(Some(future))
+    }
+
+    /// Stops sampling, ending the iteration, also of the pending ``__anext__`` calls.
+    fn close(&self, py: Python) {
+        let waiting = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            std::mem::take(&mut state.waiting)
+        };
+        for (event_loop, future) in waiting {
+            let result = Err(PyStopAsyncIteration::new_err(()));
+            resolve(py, &event_loop, &future, result);
+        }
+    }
+}
+
+impl Drop for SensorStream {
+    fn drop(&mut self) {
+        Python::with_gil(|py| self.close(py));
+    }
+}
+
+/// Adds the ``aio`` submodule to `parent`, also importable as ``bluerobotics_navigator.aio``.
+pub(crate) fn add_submodule(py: Python, parent: &PyModule) -> PyResult<()> {
+    let module = PyModule::new(py, "aio")?;
+    module.setattr("__doc__", AIO_DOC)?;
+    module.add_class::<SensorStream>()?;
+    module.add_function(wrap_pyfunction!(read_adc_all, module)?)?;
+    module.add_function(wrap_pyfunction!(read_adc, module)?)?;
+    module.add_function(wrap_pyfunction!(read_pressure, module)?)?;
+    module.add_function(wrap_pyfunction!(read_temp, module)?)?;
+    module.add_function(wrap_pyfunction!(read_leak, module)?)?;
+    module.add_function(wrap_pyfunction!(read_mag, module)?)?;
+    module.add_function(wrap_pyfunction!(read_accel, module)?)?;
+    module.add_function(wrap_pyfunction!(read_gyro, module)?)?;
+    module.add_function(wrap_pyfunction!(read_all, module)?)?;
+    module.add_function(wrap_pyfunction!(read_imu, module)?)?;
+    module.add_function(wrap_pyfunction!(stream_adc_all, module)?)?;
+    module.add_function(wrap_pyfunction!(stream_pressure, module)?)?;
+    module.add_function(wrap_pyfunction!(stream_mag, module)?)?;
+    module.add_function(wrap_pyfunction!(stream_accel, module)?)?;
+    module.add_function(wrap_pyfunction!(stream_gyro, module)?)?;
+    module.add_function(wrap_pyfunction!(stream_imu, module)?)?;
+    parent.add_submodule(module)?;
+
+    // Extension modules aren't packages, `import bluerobotics_navigator.aio` finds it only here
+    py.import("sys")?
+        .getattr("modules")?
+        .set_item("bluerobotics_navigator.aio", module)
+}
+
+const AIO_DOC: &str = "Asyncio versions of the sensor reads.
+
+Hardware access runs on Rust threads, without holding the GIL, so the event loop keeps running
+while waiting for the sensors.
+
+Examples:
+    >>> import asyncio
+    >>> from bluerobotics_navigator import aio
+    >>> async def main():
+    ...     acceleration = await aio.read_accel()
+    ...     async for accel, gyro, mag in aio.stream_imu(rate=200):
+    ...         print(gyro.z)
+    >>> asyncio.run(main())
+";
+
+/// Asynchronous version of :py:func:`read_adc_all`.
+#[pyfunction]
+fn read_adc_all(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || with_default_instance(Instance::read_adc_all))
+}
+
+/// Asynchronous version of :py:func:`read_adc`.
+#[pyfunction]
+fn read_adc(py: Python, channel: AdcChannel) -> PyResult<PyObject> {
+    spawn_read(py, move || {
+        with_default_instance(|instance| instance.read_adc(channel))
+    })
+}
+
+/// Asynchronous version of :py:func:`read_pressure`.
+#[pyfunction]
+fn read_pressure(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || with_default_instance(Instance::read_pressure))
+}
+
+/// Asynchronous version of :py:func:`read_temp`.
+#[pyfunction]
+fn read_temp(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || with_default_instance(Instance::read_temp))
+}
+
+/// Asynchronous version of :py:func:`read_leak`.
+#[pyfunction]
+fn read_leak(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || {
+        with_default_instance(|instance| instance.navigator.read_leak())
+    })
+}
+
+/// Asynchronous version of :py:func:`read_mag`.
+#[pyfunction]
+fn read_mag(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || with_default_instance(Instance::read_mag))
+}
+
+/// Asynchronous version of :py:func:`read_accel`.
+#[pyfunction]
+fn read_accel(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || with_default_instance(Instance::read_accel))
+}
+
+/// Asynchronous version of :py:func:`read_gyro`.
+#[pyfunction]
+fn read_gyro(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || with_default_instance(Instance::read_gyro))
+}
+
+/// Asynchronous version of :py:func:`read_all`.
+#[pyfunction]
+fn read_all(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, || with_default_instance(Instance::read_all))
+}
+
+/// Reads the accelerometer, gyroscope and magnetometer.
+///
+/// Returns:
+///     (:py:class:`AxisData`, :py:class:`AxisData`, :py:class:`AxisData`): Acceleration [m/s²],
+///     angular velocity [rad/s] and magnetic field [µT].
+#[pyfunction]
+fn read_imu(py: Python) -> PyResult<PyObject> {
+    spawn_read(py, read_imu_axes)
+}
+
+/// Streams :py:func:`read_adc_all` measurements.
+///
+/// Args:
+///     rate (float32): Sampling rate [Hz].
+#[pyfunction]
+#[pyo3(signature = (rate = 100.0))]
+fn stream_adc_all(rate: f32) -> PyResult<SensorStream> {
+    SensorStream::spawn(rate, || with_default_instance(Instance::read_adc_all))
+}
+
+/// Streams :py:func:`read_pressure` measurements.
+///
+/// Args:
+///     rate (float32): Sampling rate [Hz].
+#[pyfunction]
+#[pyo3(signature = (rate = 100.0))]
+fn stream_pressure(rate: f32) -> PyResult<SensorStream> {
+    SensorStream::spawn(rate, || with_default_instance(Instance::read_pressure))
+}
+
+/// Streams :py:func:`read_mag` measurements.
+///
+/// Args:
+///     rate (float32): Sampling rate [Hz].
+#[pyfunction]
+#[pyo3(signature = (rate = 100.0))]
+fn stream_mag(rate: f32) -> PyResult<SensorStream> {
+    SensorStream::spawn(rate, || with_default_instance(Instance::read_mag))
+}
+
+/// Streams :py:func:`read_accel` measurements.
+///
+/// Args:
+///     rate (float32): Sampling rate [Hz].
+#[pyfunction]
+#[pyo3(signature = (rate = 100.0))]
+fn stream_accel(rate: f32) -> PyResult<SensorStream> {
+    SensorStream::spawn(rate, || with_default_instance(Instance::read_accel))
+}
+
+/// Streams :py:func:`read_gyro` measurements.
+///
+/// Args:
+///     rate (float32): Sampling rate [Hz].
+#[pyfunction]
+#[pyo3(signature = (rate = 100.0))]
+fn stream_gyro(rate: f32) -> PyResult<SensorStream> {
+    SensorStream::spawn(rate, || with_default_instance(Instance::read_gyro))
+}
+
+/// Streams :py:func:`read_imu` measurements.
+///
+/// Args:
+///     rate (float32): Sampling rate [Hz].
+#[pyfunction]
+#[pyo3(signature = (rate = 100.0))]
+fn stream_imu(rate: f32) -> PyResult<SensorStream> {
+    SensorStream::spawn(rate, read_imu_axes)
+}
+
+fn read_imu_axes() -> (AxisData, AxisData, AxisData) {
+    with_default_instance(|instance| {
+        (
+            instance.read_accel(),
+            instance.read_gyro(),
+            instance.read_mag(),
+        )
+    })
+}
@@ -0,0 +1,500 @@
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use navigator_rs::{AdcChannel, AxisData, PiVersion, SensorData, UserLed};
+
+use crate::icm20689::{self, Icm20689};
+use crate::pca9685::{Counters, Pca9685};
+use crate::{AccelRange, DetectedHardware, GyroRange, ImuDlpf, PwmOutputMode};
+
+pub const PWM_CHANNELS: usize = 16;
+
+/// External clock of the PCA9685 on the Navigator board [Hz], also assumed by navigator-rs.
+pub const DEFAULT_PWM_CLOCK_HZ: f32 = 24_576_000.0;
+/// Prescale value of the PCA9685 after a power-on reset.
+const PWM_DEFAULT_PRESCALE: u8 = 30;
+/// Smallest prescale value accepted by the PCA9685.
+pub(crate) const PWM_MIN_PRESCALE: u8 = 3;
+/// ON counter offset between consecutive channels when their phases are staggered.
+const PWM_PHASE_STEP: u16 = 4096 / PWM_CHANNELS as u16;
+
+/// PWM outputs as written to the PCA9685, after the rounding done by the chip's counters.
+#[derive(Clone, Debug)]
+pub struct PwmState {
+    pub enabled: bool,
+    pub frequency: f32,
+    pub prescale: u8,
+    pub duty_cycles: [f32; PWM_CHANNELS],
+}
+
+impl Default for PwmState {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frequency: prescale_to_frequency(PWM_DEFAULT_PRESCALE, DEFAULT_PWM_CLOCK_HZ),
+            prescale: PWM_DEFAULT_PRESCALE,
+            duty_cycles: [0.0; PWM_CHANNELS],
+        }
+    }
+}
+
+/// PWM frequency [Hz] produced by `prescale` with a `clock_hz` clock.
+pub fn prescale_to_frequency(prescale: u8, clock_hz: f32) -> f32 {
+    clock_hz / (4096.0 * (prescale as f32 + 1.0))
+}
+
+/// PWM frequencies [Hz] produced with a `clock_hz` clock, by the prescale values from 255 down
+/// to `PWM_MIN_PRESCALE`.
+pub fn pwm_frequency_range(clock_hz: f32) -> RangeInclusive<f32> {
+    prescale_to_frequency(u8::MAX, clock_hz)..=prescale_to_frequency(PWM_MIN_PRESCALE, clock_hz)
+}
+
+/// Closest prescale to `freq_hz` with a `clock_hz` clock, rounded like navigator-rs. Fails if the
+/// frequency is out of the range of the prescale register.
+pub fn frequency_to_prescale(freq_hz: f32, clock_hz: f32) -> Result<u8, String> {
+    let range = pwm_frequency_range(clock_hz);
+    if !(freq_hz > 0.0 && range.contains(&freq_hz)) {
+        return Err(format!(
+            "PWM frequency must be between {:.1} and {:.0} Hz, found {freq_hz}.",
+            range.start(),
+            range.end()
+        ));
+    }
+    Ok(((clock_hz / (4096.0 * freq_hz)).round() - 1.0) as u8)
+}
+
+/// Checks that the PCA9685 accepts `prescale`.
+pub fn check_prescale(prescale: u8) -> Result<(), String> {
+    match prescale < PWM_MIN_PRESCALE {
+        true => Err(format!(
+            "PWM prescale must be between {PWM_MIN_PRESCALE} and 255, found {prescale}."
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Operations used by the bindings, implemented by anything able to drive a Navigator board.
+///
+/// The signatures follow [`navigator_rs::Navigator`], so the exported functions work the same
+/// way whether the board is accessed directly or through `navigatord`.
+pub trait NavigatorBackend: Send {
+    fn set_led(&mut self, select: UserLed, state: bool);
+    fn get_led(&mut self, select: UserLed) -> bool;
+    fn set_led_toggle(&mut self, select: UserLed);
+    fn set_neopixel(&mut self, colors: &[[u8; 3]]);
+    fn set_neopixel_rgbw(&mut self, colors: &[[u8; 4]]);
+    fn read_adc_all(&mut self) -> Vec<f32>;
+    fn read_adc(&mut self, channel: AdcChannel) -> f32;
+    fn read_pressure(&mut self) -> f32;
+    fn read_temperature(&mut self) -> f32;
+    fn read_mag(&mut self) -> AxisData;
+    fn read_accel(&mut self) -> AxisData;
+    fn read_gyro(&mut self) -> AxisData;
+    fn read_leak(&mut self) -> bool;
+    /// Sets the accelerometer range, the readings keep the same unit.
+    fn set_accel_range(&mut self, range: AccelRange);
+    /// Sets the gyroscope range, the readings keep the same unit.
+    fn set_gyro_range(&mut self, range: GyroRange);
+    fn set_imu_dlpf(&mut self, dlpf: ImuDlpf);
+    /// Fails if the rate can't be produced by the sample rate divider of the IMU.
+    fn set_imu_sample_rate(&mut self, rate_hz: f32) -> Result<(), String>;
+    /// Reads up to `max_samples` accelerometer and gyroscope samples from the IMU FIFO, oldest
+    /// first, in the unit of `read_accel` and `read_gyro`. Fails if the FIFO overflowed.
+    fn read_imu_fifo(&mut self, max_samples: usize) -> Result<Vec<(AxisData, AxisData)>, String>;
+    fn set_pwm_enable(&mut self, enable: bool);
+    /// Fails if the frequency can't be produced by the clock of the chip.
+    fn set_pwm_frequency(&mut self, freq_hz: f32) -> Result<(), String>;
+    /// Fails if the prescale is below `PWM_MIN_PRESCALE`.
+    fn set_pwm_prescale(&mut self, prescale: u8) -> Result<(), String>;
+    /// Selects the output driver of the PWM channels, totem-pole after power-on.
+    fn set_pwm_output_mode(&mut self, mode: PwmOutputMode);
+    /// Inverts the logic state of the PWM outputs, for drivers active low.
+    fn set_pwm_invert(&mut self, inverted: bool);
+    fn set_pwm_duty_cycle(&mut self, channel: usize, duty_cycle: f32);
+    /// Sets both counters of a channel, up to 4095: the output turns ON at `on` and OFF at `off`.
+    fn set_pwm_channel_on_off(&mut self, channel: usize, on: u16, off: u16);
+    /// Offsets the ON counter of the duty cycles written afterwards by 1/16 of a cycle per channel,
+    /// so the outputs don't all switch at the same time.
+    fn set_pwm_phase_stagger(&mut self, enabled: bool);
+    fn pwm_state(&mut self) -> PwmState;
+
+    /// Sets the duty cycle of multiple channels, in a single operation when possible.
+    fn set_pwm_duty_cycles(&mut self, channels: &[usize], duty_cycles: &[f32]) {
+        for (channel, duty_cycle) in channels.iter().zip(duty_cycles) {
+            self.set_pwm_duty_cycle(*channel, *duty_cycle);
+        }
+    }
+
+    /// Reads `n` accelerometer, gyroscope and magnetometer samples in a single operation.
+    fn read_imu_batch(&mut self, n: usize) -> Vec<[AxisData; 3]> {
+        (0..n)
+            .map(|_| [self.read_accel(), self.read_gyro(), self.read_mag()])
+            .collect()
+    }
+
+    /// Makes this client the only one driving the PWM outputs of a shared board, until released or
+    /// disconnected. Fails if another client claimed them, a local board is always owned.
+    fn claim_pwm(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Releases the PWM outputs claimed by `claim_pwm`.
+    fn release_pwm(&mut self) {}
+
+    /// Hardware found by the detection of the process owning the board, used for `Auto` versions.
+    fn detected_hardware(&mut self) -> DetectedHardware {
+        crate::DETECTED_HARDWARE.clone()
+    }
+
+    /// Reads all sensors in a single pass.
+    fn read_all(&mut self) -> SensorData {
+        SensorData {
+            adc: self.read_adc_all(),
+            temperature: self.read_temperature(),
+            pressure: self.read_pressure(),
+            accelerometer: self.read_accel(),
+            magnetometer: self.read_mag(),
+            gyro: self.read_gyro(),
+            leak: self.read_leak(),
+        }
+    }
+}
+
+// Set while a `HardwareLock` is held
+static HARDWARE_IN_USE: AtomicBool = AtomicBool::new(false);
+
+/// Exclusive access to the board within the process, released when dropped.
+pub struct HardwareLock(());
+
+impl HardwareLock {
+    /// Fails if another instance of this process already accesses the board, its drivers would
+    /// fight over the same buses.
+    pub fn acquire() -> Result<Self, String> {
+        HARDWARE_IN_USE
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| Self(()))
+            .map_err(|_| {
+                "The Navigator hardware is already used by another instance of this process, \
+                    close it first or use the daemon backend"
+                    .to_string()
+            })
+    }
+}
+
+impl Drop for HardwareLock {
+    fn drop(&mut self) {
+        HARDWARE_IN_USE.store(false, Ordering::Release);
+    }
+}
+
+/// Direct access to the board, keeping track of the PWM outputs since the chip can't be read back.
+pub struct LocalNavigator {
+    navigator: navigator_rs::Navigator,
+    pwm: Pca9685,
+    pwm_clock_hz: f32,
+    pwm_state: PwmState,
+    // Counters written to each channel, rewritten as they are by the bursts spanning them
+    pwm_counters: [Counters; PWM_CHANNELS],
+    pwm_phase_stagger: bool,
+    imu: Icm20689,
+    // Ratios of the IMU ranges to the ones navigator-rs scales its readings for
+    accel_scale: f32,
+    gyro_scale: f32,
+    _hardware: HardwareLock,
+}
+
+impl LocalNavigator {
+    /// Builds the navigator for `pi` while holding `hardware`, fails if the PWM chip or the IMU
+    /// can't be opened.
+    pub fn new(
+        hardware: HardwareLock,
+        pi: PiVersion,
+        builder: navigator_rs::NavigatorBuilder,
+    ) -> Result<Self, String> {
+        let navigator = builder.with_pi(pi).build();
+        let pwm = Pca9685::open(pi)
+            .map_err(|error| format!("Failed to open the PWM chip (PCA9685): {error}"))?;
+        let imu = Icm20689::open()
+            .map_err(|error| format!("Failed to open the IMU (ICM20689): {error}"))?;
+        Ok(Self {
+            navigator,
+            pwm,
+            _hardware: hardware,
+            pwm_clock_hz: DEFAULT_PWM_CLOCK_HZ,
+            pwm_state: PwmState::default(),
+            // The power-on state of the chip
+            pwm_counters: [Counters::FULL_OFF; PWM_CHANNELS],
+            pwm_phase_stagger: false,
+            imu,
+            accel_scale: 1.0,
+            gyro_scale: 1.0,
+        })
+    }
+
+    /// Sets the frequency of the clock connected to the PCA9685 EXTCLK pin.
+    pub fn with_pwm_clock_hz(mut self, clock_hz: f32) -> Self {
+        self.pwm_clock_hz = clock_hz;
+        self.pwm_state.frequency = prescale_to_frequency(self.pwm_state.prescale, clock_hz);
+        self
+    }
+
+    /// ON counter of the channel's duty cycles.
+    fn pwm_phase(&self, channel: usize) -> u16 {
+        match self.pwm_phase_stagger {
+            true => channel as u16 * PWM_PHASE_STEP,
+            false => 0,
+        }
+    }
+
+    /// Writes the counters of consecutive channels from `first`. Like navigator-rs, failures panic.
+    fn write_pwm_channels(&mut self, first: usize, counters: &[Counters]) {
+        self.pwm
+            .write_channels(first, counters)
+            .unwrap_or_else(|error| panic!("Failed to write the PWM channels: {error}"));
+        self.pwm_counters[first..first + counters.len()].copy_from_slice(counters);
+        for (state, counters) in self.pwm_state.duty_cycles[first..].iter_mut().zip(counters) {
+            *state = counters.duty_cycle();
+        }
+    }
+}
+
+fn scale(axis: AxisData, factor: f32) -> AxisData {
+    AxisData {
+        x: axis.x * factor,
+        y: axis.y * factor,
+        z: axis.z * factor,
+    }
+}
+
+/// Converts the raw `axes` of the FIFO to the unit of the navigator-rs readings, for a `full_scale`
+/// range.
+fn raw_to_axis(axes: &[i16], full_scale: f32) -> AxisData {
+    let factor = full_scale / (i16::MAX as f32 + 1.0);
+    AxisData {
+        x: axes[0] as f32 * factor,
+        y: axes[1] as f32 * factor,
+        z: axes[2] as f32 * factor,
+    }
+}
+
+impl NavigatorBackend for LocalNavigator {
+    fn set_led(&mut self, select: UserLed, state: bool) {
+        self.navigator.set_led(select, state)
+    }
+
+    fn get_led(&mut self, select: UserLed) -> bool {
+        self.navigator.get_led(select)
+    }
+
+    fn set_led_toggle(&mut self, select: UserLed) {
+        self.navigator.set_led_toggle(select)
+    }
+
+    fn set_neopixel(&mut self, colors: &[[u8; 3]]) {
+        self.navigator.set_neopixel(colors)
+    }
+
+    fn set_neopixel_rgbw(&mut self, colors: &[[u8; 4]]) {
+        self.navigator.set_neopixel_rgbw(colors)
+    }
+
+    fn read_adc_all(&mut self) -> Vec<f32> {
+        self.navigator.read_adc_all()
+    }
+
+    fn read_adc(&mut self, channel: AdcChannel) -> f32 {
+        self.navigator.read_adc(channel)
+    }
+
+    fn read_pressure(&mut self) -> f32 {
+        self.navigator.read_pressure()
+    }
+
+    fn read_temperature(&mut self) -> f32 {
+        self.navigator.read_temperature()
+    }
+
+    fn read_mag(&mut self) -> AxisData {
+        self.navigator.read_mag()
+    }
+
+    fn read_accel(&mut self) -> AxisData {
+        scale(self.navigator.read_accel(), self.accel_scale)
+    }
+
+    fn read_gyro(&mut self) -> AxisData {
+        scale(self.navigator.read_gyro(), self.gyro_scale)
+    }
+
+    fn read_leak(&mut self) -> bool {
+        self.navigator.read_leak()
+    }
+
+    fn set_pwm_enable(&mut self, enable: bool) {
+        self.navigator.set_pwm_enable(enable);
+        self.pwm_state.enabled = enable;
+    }
+
+    fn set_pwm_frequency(&mut self, freq_hz: f32) -> Result<(), String> {
+        self.set_pwm_prescale(frequency_to_prescale(freq_hz, self.pwm_clock_hz)?)
+    }
+
+    fn set_pwm_prescale(&mut self, prescale: u8) -> Result<(), String> {
+        check_prescale(prescale)?;
+        // navigator-rs only takes a frequency, which it converts back to the same prescale
+        self.navigator
+            .set_pwm_frequency(prescale_to_frequency(prescale, DEFAULT_PWM_CLOCK_HZ));
+        self.pwm.mode1_changed();
+        self.pwm_state.prescale = prescale;
+        self.pwm_state.frequency = prescale_to_frequency(prescale, self.pwm_clock_hz);
+        Ok(())
+    }
+
+    fn set_pwm_output_mode(&mut self, mode: PwmOutputMode) {
+        self.pwm
+            .set_totem_pole(matches!(mode, PwmOutputMode::TotemPole))
+            .unwrap_or_else(|error| panic!("Failed to set the PWM output mode: {error}"));
+    }
+
+    fn set_pwm_invert(&mut self, inverted: bool) {
+        self.pwm
+            .set_inverted(inverted)
+            .unwrap_or_else(|error| panic!("Failed to set the PWM output inversion: {error}"));
+    }
+
+    fn set_pwm_duty_cycle(&mut self, channel: usize, duty_cycle: f32) {
+        self.set_pwm_duty_cycles(&[channel], &[duty_cycle]);
+    }
+
+    /// Writes the channels from the lowest to the highest one in a single auto-increment burst,
+    /// so they all change at the same time. The channels in between are rewritten unchanged.
+    fn set_pwm_duty_cycles(&mut self, channels: &[usize], duty_cycles: &[f32]) {
+        let mut counters = self.pwm_counters;
+        let mut range: Option<(usize, usize)> = None;
+        for (&channel, &duty_cycle) in channels.iter().zip(duty_cycles) {
+            // Invalid channels are ignored, like navigator-rs
+            let Some(channel_counters) = counters.get_mut(channel) else {
+                continue;
+            };
+            *channel_counters = Counters::from_duty_cycle(duty_cycle, self.pwm_phase(channel));
+            range = Some(match range {
+                Some((first, last)) => (first.min(channel), last.max(channel)),
+                None => (channel, channel),
+            });
+        }
+        if let Some((first, last)) = range {
+            self.write_pwm_channels(first, &counters[first..=last]);
+        }
+    }
+
+    fn set_pwm_channel_on_off(&mut self, channel: usize, on: u16, off: u16) {
+        if channel < PWM_CHANNELS {
+            self.write_pwm_channels(channel, &[Counters::new(on, off)]);
+        }
+    }
+
+    fn set_pwm_phase_stagger(&mut self, enabled: bool) {
+        self.pwm_phase_stagger = enabled;
+    }
+
+    fn pwm_state(&mut self) -> PwmState {
+        self.pwm_state.clone()
+    }
+
+    fn set_accel_range(&mut self, range: AccelRange) {
+        self.accel_scale = range.full_scale() / icm20689::DEFAULT_ACCEL_RANGE.full_scale();
+        self.imu
+            .set_accel_range(range)
+            .unwrap_or_else(|error| panic!("Failed to set the accelerometer range: {error}"));
+    }
+
+    fn set_gyro_range(&mut self, range: GyroRange) {
+        self.gyro_scale = range.full_scale() / icm20689::DEFAULT_GYRO_RANGE.full_scale();
+        self.imu
+            .set_gyro_range(range)
+            .unwrap_or_else(|error| panic!("Failed to set the gyroscope range: {error}"));
+    }
+
+    fn set_imu_dlpf(&mut self, dlpf: ImuDlpf) {
+        self.imu
+            .set_dlpf(dlpf)
+            .unwrap_or_else(|error| panic!("Failed to set the IMU low-pass filter: {error}"));
+    }
+
+    fn set_imu_sample_rate(&mut self, rate_hz: f32) -> Result<(), String> {
+        let divider = icm20689::sample_rate_divider(rate_hz)?;
+        self.imu
+            .set_sample_rate_divider(divider)
+            .unwrap_or_else(|error| panic!("Failed to set the IMU sample rate: {error}"));
+        Ok(())
+    }
+
+    fn read_imu_fifo(&mut self, max_samples: usize) -> Result<Vec<(AxisData, AxisData)>, String> {
+        let samples = self
+            .imu
+            .read_fifo(max_samples)
+            .map_err(|error| format!("Failed to read the IMU FIFO: {error}"))?;
+        let accel_full_scale = self.accel_scale * icm20689::DEFAULT_ACCEL_RANGE.full_scale();
+        let gyro_full_scale = self.gyro_scale * icm20689::DEFAULT_GYRO_RANGE.full_scale();
+        Ok(samples
+            .iter()
+            .map(|sample| {
+                (
+                    raw_to_axis(&sample[..3], accel_full_scale),
+                    raw_to_axis(&sample[3..], gyro_full_scale),
+                )
+            })
+            .collect())
+    }
+
+    fn read_all(&mut self) -> SensorData {
+        let mut data = self.navigator.read_all();
+        data.accelerometer = scale(data.accelerometer, self.accel_scale);
+        data.gyro = scale(data.gyro, self.gyro_scale);
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequencies_are_checked_against_the_prescale_range() {
+        assert_eq!(frequency_to_prescale(50.0, DEFAULT_PWM_CLOCK_HZ), Ok(119));
+        let range = pwm_frequency_range(DEFAULT_PWM_CLOCK_HZ);
+        assert_eq!(
+            frequency_to_prescale(*range.start(), DEFAULT_PWM_CLOCK_HZ),
+            Ok(255)
+        );
+        assert_eq!(
+            frequency_to_prescale(*range.end(), DEFAULT_PWM_CLOCK_HZ),
+            Ok(3)
+        );
+        for freq in [0.0, -50.0, f32::NAN, 20.0, 1600.0] {
+            let error = frequency_to_prescale(freq, DEFAULT_PWM_CLOCK_HZ).unwrap_err();
+            assert!(error.contains("between 23.4 and 1500 Hz"), "{error}");
+        }
+        assert!(check_prescale(2).is_err());
+        assert!(check_prescale(3).is_ok());
+    }
+
+    #[test]
+    fn fifo_samples_have_the_scale_of_the_readings() {
+        // The icm20689 crate scales gyroscope readings by 2000 dps per 32768 counts
+        let reading = 1000.0 * (2000.0f32 / 32768.0).to_radians();
+        let gyro = raw_to_axis(&[1000, -1000, 0], icm20689::DEFAULT_GYRO_RANGE.full_scale());
+        assert!((gyro.x - reading).abs() < 1e-6);
+        assert!((gyro.y + reading).abs() < 1e-6);
+        assert_eq!(gyro.z, 0.0);
+    }
+
+    #[test]
+    fn hardware_is_used_by_one_instance_at_a_time() {
+        let hardware = HardwareLock::acquire().unwrap();
+        let error = HardwareLock::acquire().err().unwrap();
+        assert!(error.contains("already used"), "{error}");
+        drop(hardware);
+        assert!(HardwareLock::acquire().is_ok());
+    }
+}
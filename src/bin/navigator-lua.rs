@@ -0,0 +1,119 @@
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bluerobotics_navigator::lua::{self, Limits};
+use bluerobotics_navigator::{Backend, NavigatorVersion, Raspberry};
+
+const USAGE: &str = "Usage: navigator-lua [OPTIONS] <SCRIPT>
+
+Runs a Lua mission script with access to the Navigator sensors, LEDs and PWM outputs through the
+`navigator` table. Scripts are sandboxed and stopped once they exceed their budget.
+
+Options:
+    --config <PATH>                Board configuration file, overridden by the options after it
+    --socket <PATH>                Use the navigatord daemon listening on this Unix socket
+    --navigator-version <1|2|auto> Navigator board version [default: auto]
+    --pi-version <4|5|auto>        Raspberry Pi version [default: auto]
+    --rgb-led-strip-size <SIZE>    Size of the navigator led strip [default: 1]
+    --max-instructions <COUNT>     Lua VM instructions budget, or `none` [default: 100000000]
+    --timeout <SECONDS>            Wall-clock time budget, or `none` [default: 60]
+    --max-memory <BYTES>           Lua memory budget, or `none` [default: 16777216]
+    -h, --help                     Print this help";
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("{error}\n\n{USAGE}");
+    std::process::exit(1);
+}
+
+/// Parses a budget value, `none` disabling the limit.
+fn limit<T: std::str::FromStr>(arg: &str, value: &str) -> Option<T> {
+    match value {
+        "none" => None,
+        _ => Some(
+            value
+                .parse()
+                .unwrap_or_else(|_| exit_with_usage(&format!("Invalid value for {arg}: {value}"))),
+        ),
+    }
+}
+
+fn main() {
+    let mut script = None;
+    let mut limits = Limits::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{USAGE}");
+            return;
+        }
+        if !arg.starts_with("--") {
+            if script.replace(PathBuf::from(&arg)).is_some() {
+                exit_with_usage(&format!("Unexpected argument: {arg}"));
+            }
+            continue;
+        }
+        let value = args
+            .next()
+            .unwrap_or_else(|| exit_with_usage(&format!("Missing value for {arg}")));
+        match (arg.as_str(), value.as_str()) {
+            ("--config", path) => {
+                let path = CString::new(path)
+                    .unwrap_or_else(|_| exit_with_usage(&format!("Invalid config path: {path}")));
+                if !bluerobotics_navigator::load_config(path.as_ptr()) {
+                    let error =
+                        unsafe { CStr::from_ptr(bluerobotics_navigator::navigator_last_error()) };
+                    eprintln!("{}", error.to_string_lossy());
+                    std::process::exit(1);
+                }
+            }
+            ("--socket", path) => {
+                let path = CString::new(path)
+                    .unwrap_or_else(|_| exit_with_usage(&format!("Invalid socket path: {path}")));
+                bluerobotics_navigator::set_backend(Backend::Daemon, path.as_ptr());
+            }
+            ("--navigator-version", "1") => {
+                bluerobotics_navigator::set_navigator_version(NavigatorVersion::Version1)
+            }
+            ("--navigator-version", "2") => {
+                bluerobotics_navigator::set_navigator_version(NavigatorVersion::Version2)
+            }
+            ("--navigator-version", "auto") => {
+                bluerobotics_navigator::set_navigator_version(NavigatorVersion::Auto)
+            }
+            ("--pi-version", "4") => {
+                bluerobotics_navigator::set_raspberry_pi_version(Raspberry::Pi4)
+            }
+            ("--pi-version", "5") => {
+                bluerobotics_navigator::set_raspberry_pi_version(Raspberry::Pi5)
+            }
+            ("--pi-version", "auto") => {
+                bluerobotics_navigator::set_raspberry_pi_version(Raspberry::Auto)
+            }
+            ("--rgb-led-strip-size", size) => bluerobotics_navigator::set_rgb_led_strip_size(
+                size.parse()
+                    .unwrap_or_else(|_| exit_with_usage(&format!("Invalid size: {size}"))),
+            ),
+            ("--max-instructions", count) => limits.instructions = limit(&arg, count),
+            ("--timeout", seconds) => {
+                limits.time = limit(&arg, seconds).map(|seconds: f64| {
+                    Duration::try_from_secs_f64(seconds).unwrap_or_else(|_| {
+                        exit_with_usage(&format!("Invalid value for {arg}: {seconds}"))
+                    })
+                })
+            }
+            ("--max-memory", bytes) => limits.memory = limit(&arg, bytes),
+            _ => exit_with_usage(&format!("Invalid argument: {arg} {value}")),
+        }
+    }
+    let script = script.unwrap_or_else(|| exit_with_usage("Missing script path"));
+
+    let result = lua::run_file(&script, &limits);
+    // Stops the PWM ramps and releases the hardware, also when the script was interrupted
+    bluerobotics_navigator::deinit();
+    if let Err(error) = result {
+        eprintln!("Script failed: {error}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,90 @@
+use bluerobotics_navigator::backend::{self, HardwareLock, LocalNavigator};
+use bluerobotics_navigator::daemon;
+use bluerobotics_navigator::{NavigatorVersion, Raspberry};
+
+const USAGE: &str = "Usage: navigatord [OPTIONS]
+
+Owns the Navigator hardware and shares it with other processes through a Unix socket.
+
+Options:
+    --socket <PATH>                Unix socket to listen on [default: /run/navigator.sock]
+    --navigator-version <1|2|auto> Navigator board version [default: auto]
+    --pi-version <4|5|auto>        Raspberry Pi version [default: auto]
+    --rgb-led-strip-size <SIZE>    Size of the navigator led strip [default: 1]
+    --pwm-clock-hz <FREQ>          Frequency of the PWM chip external clock [default: 24576000]
+    -h, --help                     Print this help";
+
+fn exit_with_usage(error: &str) -> ! {
+    eprintln!("{error}\n\n{USAGE}");
+    std::process::exit(1);
+}
+
+fn main() {
+    let mut socket_path = daemon::DEFAULT_SOCKET_PATH.to_string();
+    let mut builder = navigator_rs::Navigator::create();
+    let mut navigator_version = NavigatorVersion::Auto;
+    let mut pi_version = Raspberry::Auto;
+    let mut pwm_clock_hz = backend::DEFAULT_PWM_CLOCK_HZ;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{USAGE}");
+            return;
+        }
+        let value = args
+            .next()
+            .unwrap_or_else(|| exit_with_usage(&format!("Missing value for {arg}")));
+        builder = match (arg.as_str(), value.as_str()) {
+            ("--socket", _) => {
+                socket_path = value;
+                builder
+            }
+            ("--navigator-version", version) => {
+                navigator_version = match version {
+                    "1" => NavigatorVersion::Version1,
+                    "2" => NavigatorVersion::Version2,
+                    "auto" => NavigatorVersion::Auto,
+                    _ => exit_with_usage(&format!("Invalid argument: {arg} {value}")),
+                };
+                builder
+            }
+            ("--pi-version", version) => {
+                pi_version = match version {
+                    "4" => Raspberry::Pi4,
+                    "5" => Raspberry::Pi5,
+                    "auto" => Raspberry::Auto,
+                    _ => exit_with_usage(&format!("Invalid argument: {arg} {value}")),
+                };
+                builder
+            }
+            ("--rgb-led-strip-size", size) => builder.with_rgb_led_strip_size(
+                size.parse()
+                    .unwrap_or_else(|_| exit_with_usage(&format!("Invalid size: {size}"))),
+            ),
+            ("--pwm-clock-hz", freq) => {
+                pwm_clock_hz = freq
+                    .parse()
+                    .unwrap_or_else(|_| exit_with_usage(&format!("Invalid frequency: {freq}")));
+                builder
+            }
+            _ => exit_with_usage(&format!("Invalid argument: {arg} {value}")),
+        };
+    }
+
+    // `Auto` versions are detected by the conversion
+    let builder = builder.with_navigator(navigator_version.into());
+
+    println!("Serving navigator on {socket_path}");
+    let hardware = HardwareLock::acquire().expect("The daemon is the only user of the hardware");
+    let navigator = LocalNavigator::new(hardware, pi_version.into(), builder)
+        .unwrap_or_else(|error| {
+            eprintln!("{error}");
+            std::process::exit(1);
+        })
+        .with_pwm_clock_hz(pwm_clock_hz);
+    if let Err(error) = daemon::serve(Box::new(navigator), &socket_path) {
+        eprintln!("Failed to serve navigator: {error}");
+        std::process::exit(1);
+    }
+}
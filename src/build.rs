@@ -1,18 +1,159 @@
 extern crate cbindgen;
 
+use std::path::Path;
+
+const LIBRARY_NAME: &str = "bluerobotics_navigator";
+
 fn main() {
     let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
     let target_dir = std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_string());
     let profile = std::env::var("PROFILE").unwrap_or_else(|_| "debug".to_string());
-    let bindings_file = std::path::Path::new(&target_dir)
-        .join(profile)
-        .join("bindings.h");
+    let output_dir = Path::new(&crate_dir).join(target_dir).join(profile);
+
+    // Also kept in OUT_DIR, where the ABI snapshot test reads it
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let cxx_bindings = bindings(&crate_dir, cbindgen::Language::Cxx);
+    cxx_bindings.write_to_file(Path::new(&out_dir).join("bindings.h"));
+    cxx_bindings.write_to_file(output_dir.join("bindings.h"));
+    bindings(&crate_dir, cbindgen::Language::C)
+        .write_to_file(output_dir.join(format!("{LIBRARY_NAME}.h")));
+
+    // RAII wrapper over the navigator handle functions, included next to the bindings
+    std::fs::copy(
+        Path::new(&crate_dir).join("src/cpp/navigator.hpp"),
+        output_dir.join("navigator.hpp"),
+    )
+    .expect("Unable to copy C++ header");
+
+    write_package_files(&output_dir);
+
+    let navigator_rs_version = [Path::new(&out_dir), Path::new(&crate_dir)]
+        .iter()
+        .find_map(|dir| locked_version(dir, "navigator-rs"))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=NAVIGATOR_RS_VERSION={navigator_rs_version}");
+
+    // The node process provides the N-API functions when it loads the addon, built with `--lib`.
+    // Linux allows undefined symbols in shared libraries, macOS needs to be told (like napi-build)
+    if std::env::var("CARGO_FEATURE_NODE").is_ok()
+        && std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("macos")
+    {
+        println!("cargo:rustc-cdylib-link-arg=-Wl,-undefined,dynamic_lookup");
+    }
+}
+
+fn bindings(crate_dir: &str, language: cbindgen::Language) -> cbindgen::Bindings {
+    let mut config = cbindgen::Config {
+        language,
+        ..Default::default()
+    };
+    // Public in the `backend` module used by navigatord, not part of the C API
+    config.export.exclude = vec!["PWM_CHANNELS".into(), "DEFAULT_PWM_CLOCK_HZ".into()];
+    if language == cbindgen::Language::C {
+        // C enums share a single namespace, `AdcChannel_Ch1` and `PwmChannel_Ch1` would collide
+        config.enumeration.prefix_with_name = true;
+        config.include_guard = Some("BLUEROBOTICS_NAVIGATOR_H".to_string());
+        config.cpp_compat = true;
+    }
+
     cbindgen::Builder::new()
+        .with_config(config)
         .with_crate(crate_dir)
         .with_parse_deps(false)
-        .with_language(cbindgen::Language::Cxx)
-        .with_parse_expand(&["bluerobotics_navigator"])
+        .with_parse_expand(&[LIBRARY_NAME])
+        // Functions take PWM channel indexes, export the enum to name them
+        .include_item("PwmChannel")
         .generate()
         .expect("Unable to generate bindings")
-        .write_to_file(bindings_file);
+}
+
+/// Finds the version of `package` in the Cargo.lock of the workspace containing `dir`, the
+/// resolved version isn't available otherwise.
+fn locked_version(dir: &Path, package: &str) -> Option<String> {
+    let lock = dir
+        .ancestors()
+        .find_map(|dir| std::fs::read_to_string(dir.join("Cargo.lock")).ok())?;
+    let name = format!("name = \"{package}\"");
+    let mut lines = lock.lines().skip_while(|line| *line != name).skip(1);
+    let version = lines
+        .next()?
+        .strip_prefix("version = \"")?
+        .strip_suffix('"')?;
+    Some(version.to_string())
+}
+
+/// Writes the pkg-config and CMake package files, relative to their own directory so the output
+/// directory can be installed anywhere.
+fn write_package_files(output_dir: &Path) {
+    let version = std::env::var("CARGO_PKG_VERSION").unwrap();
+    let (library, import_library) = match std::env::var("CARGO_CFG_TARGET_OS").as_deref() {
+        Ok("windows") => (
+            format!("{LIBRARY_NAME}.dll"),
+            format!("{LIBRARY_NAME}.dll.lib"),
+        ),
+        Ok("macos") => (format!("lib{LIBRARY_NAME}.dylib"), String::new()),
+        _ => (format!("lib{LIBRARY_NAME}.so"), String::new()),
+    };
+
+    let pkg_config = format!(
+        "prefix=${{pcfiledir}}
+libdir=${{prefix}}
+includedir=${{prefix}}
+
+Name: {LIBRARY_NAME}
+Description: Library to access the Blue Robotics Navigator board
+Version: {version}
+Libs: -L${{libdir}} -l{LIBRARY_NAME}
+Cflags: -I${{includedir}}
+"
+    );
+    std::fs::write(output_dir.join("navigator.pc"), pkg_config)
+        .expect("Unable to write pkg-config file");
+
+    let cmake_config = format!(
+        r#"# Defines the bluerobotics_navigator::bluerobotics_navigator imported target, providing
+# bluerobotics_navigator.h (C), bindings.h and navigator.hpp (C++).
+
+get_filename_component(_navigator_dir "${{CMAKE_CURRENT_LIST_FILE}}" DIRECTORY)
+
+if(NOT TARGET {LIBRARY_NAME}::{LIBRARY_NAME})
+    add_library({LIBRARY_NAME}::{LIBRARY_NAME} SHARED IMPORTED)
+    set_target_properties({LIBRARY_NAME}::{LIBRARY_NAME} PROPERTIES
+        IMPORTED_LOCATION "${{_navigator_dir}}/{library}"
+        INTERFACE_INCLUDE_DIRECTORIES "${{_navigator_dir}}"
+    )
+    if(NOT "{import_library}" STREQUAL "")
+        set_target_properties({LIBRARY_NAME}::{LIBRARY_NAME} PROPERTIES
+            IMPORTED_IMPLIB "${{_navigator_dir}}/{import_library}"
+        )
+    endif()
+endif()
+
+unset(_navigator_dir)
+"#
+    );
+    std::fs::write(
+        output_dir.join(format!("{LIBRARY_NAME}Config.cmake")),
+        cmake_config,
+    )
+    .expect("Unable to write CMake package file");
+
+    let cmake_version = format!(
+        r#"set(PACKAGE_VERSION "{version}")
+
+if(PACKAGE_FIND_VERSION VERSION_GREATER PACKAGE_VERSION)
+    set(PACKAGE_VERSION_COMPATIBLE FALSE)
+else()
+    set(PACKAGE_VERSION_COMPATIBLE TRUE)
+    if(PACKAGE_FIND_VERSION STREQUAL PACKAGE_VERSION)
+        set(PACKAGE_VERSION_EXACT TRUE)
+    endif()
+endif()
+"#
+    );
+    std::fs::write(
+        output_dir.join(format!("{LIBRARY_NAME}ConfigVersion.cmake")),
+        cmake_version,
+    )
+    .expect("Unable to write CMake package version file");
 }
@@ -0,0 +1,225 @@
+//! Names given to PWM and ADC channels, so applications use `"lights"` instead of `PwmChannel.Ch9`.
+//!
+//! Names come from the `[pwm.<name>]` and `[adc.<name>]` tables of the configuration file, or are
+//! set at runtime. PWM and ADC names are separate, and each channel has at most one name.
+
+use std::collections::BTreeMap;
+
+use crate::backend::PWM_CHANNELS;
+use crate::config::Config;
+use crate::AdcChannel;
+
+#[derive(Clone, Debug, Default)]
+pub struct ChannelNames {
+    pwm: BTreeMap<String, usize>,
+    adc: BTreeMap<String, AdcChannel>,
+}
+
+impl ChannelNames {
+    /// Names of a configuration file.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            pwm: config
+                .pwm
+                .iter()
+                .map(|(name, output)| (name.clone(), output.channel))
+                .collect(),
+            adc: config.adc.clone(),
+        }
+    }
+
+    /// Names the PWM channel at `channel` (0 for `Ch1`), replacing its previous name.
+    pub fn set_pwm(&mut self, name: &str, channel: usize) -> Result<(), String> {
+        validate_name(name)?;
+        if channel >= PWM_CHANNELS {
+            return Err(format!(
+                "Invalid PWM channel: {channel}, expected an index from 0 to 15"
+            ));
+        }
+        self.pwm.retain(|_, named| *named != channel);
+        self.pwm.insert(name.to_string(), channel);
+        Ok(())
+    }
+
+    /// Names an ADC channel, replacing its previous name.
+    pub fn set_adc(&mut self, name: &str, channel: AdcChannel) -> Result<(), String> {
+        validate_name(name)?;
+        self.adc
+            .retain(|_, named| !same_adc_channel(named, &channel));
+        self.adc.insert(name.to_string(), channel);
+        Ok(())
+    }
+
+    /// Removes a PWM or ADC name, returns whether it existed.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let pwm = self.pwm.remove(name).is_some();
+        let adc = self.adc.remove(name).is_some();
+        pwm || adc
+    }
+
+    pub fn clear(&mut self) {
+        self.pwm.clear();
+        self.adc.clear();
+    }
+
+    /// Index of the PWM channel named `name`.
+    pub fn pwm(&self, name: &str) -> Result<usize, String> {
+        self.pwm
+            .get(name)
+            .copied()
+            .ok_or_else(|| unknown_name("PWM", name, self.pwm.keys()))
+    }
+
+    /// ADC channel named `name`.
+    pub fn adc(&self, name: &str) -> Result<AdcChannel, String> {
+        self.adc
+            .get(name)
+            .cloned()
+            .ok_or_else(|| unknown_name("ADC", name, self.adc.keys()))
+    }
+
+    /// PWM channel indexes, by name.
+    #[cfg(any(feature = "python", feature = "node", feature = "lua", test))]
+    pub fn pwm_names(&self) -> &BTreeMap<String, usize> {
+        &self.pwm
+    }
+
+    /// ADC channels, by name.
+    #[cfg(any(feature = "python", feature = "node", feature = "lua", test))]
+    pub fn adc_names(&self) -> &BTreeMap<String, AdcChannel> {
+        &self.adc
+    }
+
+    /// Name of the PWM channel at `channel`, if any.
+    #[cfg(any(not(feature = "python"), test))]
+    pub fn pwm_name(&self, channel: usize) -> Option<&str> {
+        self.pwm
+            .iter()
+            .find(|(_, named)| **named == channel)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Name of an ADC channel, if any.
+    #[cfg(any(not(feature = "python"), test))]
+    pub fn adc_name(&self, channel: AdcChannel) -> Option<&str> {
+        self.adc
+            .iter()
+            .find(|(_, named)| same_adc_channel(named, &channel))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+// `AdcChannel` only derives `Clone`
+fn same_adc_channel(a: &AdcChannel, b: &AdcChannel) -> bool {
+    a.clone() as usize == b.clone() as usize
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    match name.trim().is_empty() {
+        true => Err("Channel names can't be empty".to_string()),
+        false => Ok(()),
+    }
+}
+
+fn unknown_name<'a>(kind: &str, name: &str, names: impl Iterator<Item = &'a String>) -> String {
+    let names: Vec<&str> = names.map(String::as_str).collect();
+    match names.is_empty() {
+        true => format!("Unknown {kind} channel name {name:?}, no {kind} channel is named"),
+        false => format!(
+            "Unknown {kind} channel name {name:?}, expected one of: {}",
+            names.join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_channels_from_config() {
+        let config = crate::config::parse(
+            r#"
+            [pwm.thruster_port]
+            channel = "Ch1"
+
+            [pwm.lights]
+            channel = "Ch9"
+
+            [adc.battery_voltage]
+            channel = "Ch2"
+            "#,
+        )
+        .unwrap();
+        let names = ChannelNames::from_config(&config);
+
+        assert_eq!(names.pwm("thruster_port"), Ok(0));
+        assert_eq!(names.pwm("lights"), Ok(8));
+        assert!(matches!(names.adc("battery_voltage"), Ok(AdcChannel::Ch2)));
+        assert_eq!(names.pwm_name(8), Some("lights"));
+        assert_eq!(names.pwm_name(1), None);
+        assert_eq!(names.adc_name(AdcChannel::Ch2), Some("battery_voltage"));
+        assert_eq!(
+            names.pwm_names().keys().collect::<Vec<_>>(),
+            ["lights", "thruster_port"]
+        );
+    }
+
+    #[test]
+    fn renames_channels() {
+        let mut names = ChannelNames::default();
+        names.set_pwm("lights", 8).unwrap();
+        names.set_pwm("camera_tilt", 8).unwrap();
+        assert!(names.pwm("lights").is_err());
+        assert_eq!(names.pwm("camera_tilt"), Ok(8));
+
+        // A name moves to the new channel
+        names.set_pwm("camera_tilt", 9).unwrap();
+        assert_eq!(names.pwm_name(8), None);
+        assert_eq!(names.pwm_names().len(), 1);
+
+        names.set_adc("battery_voltage", AdcChannel::Ch2).unwrap();
+        names.set_adc("battery_current", AdcChannel::Ch2).unwrap();
+        assert_eq!(names.adc_names().len(), 1);
+        assert_eq!(names.adc_name(AdcChannel::Ch2), Some("battery_current"));
+    }
+
+    #[test]
+    fn separates_pwm_and_adc_names() {
+        let mut names = ChannelNames::default();
+        names.set_pwm("lights", 8).unwrap();
+        names.set_adc("lights", AdcChannel::Ch0).unwrap();
+        assert_eq!(names.pwm("lights"), Ok(8));
+        assert!(matches!(names.adc("lights"), Ok(AdcChannel::Ch0)));
+
+        assert!(names.remove("lights"));
+        assert!(names.pwm("lights").is_err());
+        assert!(names.adc("lights").is_err());
+        assert!(!names.remove("lights"));
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let mut names = ChannelNames::default();
+        assert!(names.set_pwm("", 0).is_err());
+        assert!(names.set_pwm("all", 16).is_err());
+        assert!(names.set_adc(" ", AdcChannel::Ch0).is_err());
+        assert!(names.pwm_names().is_empty());
+        assert!(names.adc_names().is_empty());
+    }
+
+    #[test]
+    fn lists_known_names_on_lookup_failure() {
+        let mut names = ChannelNames::default();
+        let error = names.pwm("lights").unwrap_err();
+        assert!(error.contains("\"lights\""), "{error}");
+
+        names.set_pwm("thruster_port", 0).unwrap();
+        names.set_pwm("thruster_starboard", 1).unwrap();
+        let error = names.pwm("thruster").unwrap_err();
+        assert!(
+            error.ends_with("thruster_port, thruster_starboard"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,693 @@
+//! Board configuration loaded from a TOML file, replacing the `set_*` calls repeated by every
+//! program. `NAVIGATOR_CONFIG` names a file loaded by the default configuration, `load_config`
+//! loads one explicitly.
+//!
+//! ```toml
+//! navigator_version = "auto"  # 1, 2 or "auto"
+//! raspberry_pi_version = 4    # 4, 5 or "auto"
+//! rgb_led_strip_size = 1
+//! pwm_clock_hz = 24_576_000
+//! pwm_frequency_hz = 50
+//! pwm_phase_stagger = true    # spreads the channels' ON times over the cycle
+//!
+//! [orientation]               # degrees, or `matrix = [[...], [...], [...]]`
+//! roll = 180
+//! yaw = 90
+//!
+//! [pwm.lights]
+//! channel = "Ch9"
+//! max = 0.5                   # duty cycle limits, 0.0 and 1.0 by default
+//!
+//! [adc.battery_voltage]
+//! channel = "Ch2"
+//!
+//! [filter.gyro]               # accel, gyro, mag, pressure, temperature or adc
+//! type = "low_pass"           # and its `set_*_filter` parameters
+//! sample_rate = 100
+//! cutoff = 20
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::backend::{pwm_frequency_range, PWM_CHANNELS};
+use crate::filters::FilterSpec;
+use crate::{orientation, AdcChannel, FilterChannel, NavigatorVersion, Raspberry};
+
+/// Environment variable naming a configuration file loaded by the default configuration.
+pub const PATH_ENV: &str = "NAVIGATOR_CONFIG";
+
+// Largest deviation of a rotation matrix from an orthonormal one
+const ROTATION_TOLERANCE: f64 = 1e-3;
+
+/// Validated configuration, options absent from the file are `None`.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub navigator_version: Option<NavigatorVersion>,
+    pub raspberry_pi_version: Option<Raspberry>,
+    pub rgb_led_strip_size: Option<usize>,
+    pub orientation: Option<orientation::Matrix>,
+    pub pwm_clock_hz: Option<f32>,
+    pub pwm_frequency_hz: Option<f32>,
+    pub pwm_phase_stagger: Option<bool>,
+    /// Named PWM outputs, by name.
+    pub pwm: BTreeMap<String, PwmOutput>,
+    /// Named ADC inputs, by name.
+    pub adc: BTreeMap<String, AdcChannel>,
+    /// Filters, by `FilterChannel` index.
+    pub filters: BTreeMap<usize, FilterSpec>,
+}
+
+/// A named PWM output, with the duty cycle range it is clamped to.
+#[derive(Clone, Debug)]
+pub struct PwmOutput {
+    /// Index of the `PwmChannel`, 0 for `Ch1`.
+    pub channel: usize,
+    pub limits: PwmLimits,
+}
+
+/// Duty cycle range of a PWM channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PwmLimits {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for PwmLimits {
+    fn default() -> Self {
+        Self { min: 0.0, max: 1.0 }
+    }
+}
+
+impl PwmLimits {
+    pub fn clamp(&self, duty_cycle: f32) -> f32 {
+        duty_cycle.clamp(self.min, self.max)
+    }
+}
+
+/// Clamps the duty cycles to the limits of their channel.
+pub fn clamp_duty_cycles(limits: &[PwmLimits], channels: &[usize], duty_cycles: &mut [f32]) {
+    for (channel, duty_cycle) in channels.iter().zip(duty_cycles) {
+        if let Some(limits) = limits.get(*channel) {
+            *duty_cycle = limits.clamp(*duty_cycle);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file couldn't be read.
+    Io(PathBuf, std::io::Error),
+    /// The file isn't valid TOML, or an option has the wrong type.
+    Parse(String),
+    /// Options with invalid values, one message per problem.
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, error) => write!(f, "Failed to read {}: {error}", path.display()),
+            ConfigError::Parse(message) => write!(f, "Invalid configuration: {message}"),
+            ConfigError::Invalid(problems) => {
+                write!(f, "Invalid configuration:")?;
+                for problem in problems {
+                    write!(f, "\n  {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(feature = "python")]
+impl From<ConfigError> for pyo3::PyErr {
+    fn from(error: ConfigError) -> Self {
+        match error {
+            ConfigError::Io(..) => pyo3::exceptions::PyOSError::new_err(error.to_string()),
+            _ => pyo3::exceptions::PyValueError::new_err(error.to_string()),
+        }
+    }
+}
+
+// The file as written, checked by `RawConfig::validate`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    navigator_version: Option<toml::Value>,
+    raspberry_pi_version: Option<toml::Value>,
+    rgb_led_strip_size: Option<i64>,
+    orientation: Option<RawOrientation>,
+    pwm_clock_hz: Option<f64>,
+    pwm_frequency_hz: Option<f64>,
+    pwm_phase_stagger: Option<bool>,
+    #[serde(default)]
+    pwm: BTreeMap<String, RawPwmOutput>,
+    #[serde(default)]
+    adc: BTreeMap<String, RawAdcInput>,
+    #[serde(default)]
+    filter: BTreeMap<String, RawFilter>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawOrientation {
+    roll: Option<f64>,
+    pitch: Option<f64>,
+    yaw: Option<f64>,
+    matrix: Option<[[f64; 3]; 3]>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPwmOutput {
+    channel: String,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawAdcInput {
+    channel: String,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum RawFilter {
+    LowPass {
+        sample_rate: f64,
+        cutoff: f64,
+    },
+    Notch {
+        sample_rate: f64,
+        center: f64,
+        bandwidth: f64,
+    },
+    OneEuro {
+        min_cutoff: f64,
+        beta: f64,
+    },
+    MovingAverage {
+        window: usize,
+    },
+    Median {
+        window: usize,
+    },
+}
+
+impl From<&RawFilter> for FilterSpec {
+    fn from(filter: &RawFilter) -> Self {
+        match *filter {
+            RawFilter::LowPass {
+                sample_rate,
+                cutoff,
+            } => FilterSpec::LowPass {
+                sample_rate: sample_rate as f32,
+                cutoff: cutoff as f32,
+            },
+            RawFilter::Notch {
+                sample_rate,
+                center,
+                bandwidth,
+            } => FilterSpec::Notch {
+                sample_rate: sample_rate as f32,
+                center: center as f32,
+                bandwidth: bandwidth as f32,
+            },
+            RawFilter::OneEuro { min_cutoff, beta } => FilterSpec::OneEuro {
+                min_cutoff: min_cutoff as f32,
+                beta: beta as f32,
+            },
+            RawFilter::MovingAverage { window } => FilterSpec::MovingAverage { window },
+            RawFilter::Median { window } => FilterSpec::Median { window },
+        }
+    }
+}
+
+/// Reads and validates the configuration file at `path`.
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+    let text =
+        std::fs::read_to_string(path).map_err(|error| ConfigError::Io(path.to_owned(), error))?;
+    parse(&text)
+}
+
+/// Parses and validates a configuration, reporting every invalid option at once.
+pub fn parse(text: &str) -> Result<Config, ConfigError> {
+    let raw: RawConfig =
+        toml::from_str(text).map_err(|error| ConfigError::Parse(error.to_string()))?;
+    raw.validate()
+}
+
+/// Loads the file named by `NAVIGATOR_CONFIG`, if set.
+pub fn from_env() -> Option<Result<Config, ConfigError>> {
+    let path = std::env::var_os(PATH_ENV)?;
+    Some(load(Path::new(&path)))
+}
+
+impl RawConfig {
+    fn validate(self) -> Result<Config, ConfigError> {
+        let mut problems = Vec::new();
+
+        let navigator_version = self.navigator_version.and_then(|version| {
+            match (version.as_integer(), version.as_str()) {
+                (Some(1), _) => Some(NavigatorVersion::Version1),
+                (Some(2), _) => Some(NavigatorVersion::Version2),
+                (_, Some("auto")) => Some(NavigatorVersion::Auto),
+                _ => {
+                    problems.push(format!(
+                        "navigator_version: expected 1, 2 or \"auto\", found {version}"
+                    ));
+                    None
+                }
+            }
+        });
+
+        let raspberry_pi_version = self.raspberry_pi_version.and_then(|version| {
+            match (version.as_integer(), version.as_str()) {
+                (Some(4), _) => Some(Raspberry::Pi4),
+                (Some(5), _) => Some(Raspberry::Pi5),
+                (_, Some("auto")) => Some(Raspberry::Auto),
+                _ => {
+                    problems.push(format!(
+                        "raspberry_pi_version: expected 4, 5 or \"auto\", found {version}"
+                    ));
+                    None
+                }
+            }
+        });
+
+        let rgb_led_strip_size = self.rgb_led_strip_size.and_then(|size| {
+            let size = usize::try_from(size).ok();
+            if size.is_none() {
+                problems.push("rgb_led_strip_size: must not be negative".to_string());
+            }
+            size
+        });
+
+        let pwm_clock_hz = self.pwm_clock_hz.and_then(|clock| {
+            if !(clock.is_finite() && clock > 0.0) {
+                problems.push(format!("pwm_clock_hz: must be positive, found {clock}"));
+                return None;
+            }
+            Some(clock as f32)
+        });
+
+        let pwm_frequency_hz = self.pwm_frequency_hz.and_then(|freq| {
+            // The prescale register holds 3..255, a limited range of frequencies for each clock
+            let clock = pwm_clock_hz.unwrap_or(crate::backend::DEFAULT_PWM_CLOCK_HZ);
+            let range = pwm_frequency_range(clock);
+            if !range.contains(&(freq as f32)) {
+                problems.push(format!(
+                    "pwm_frequency_hz: must be between {:.1} and {:.0} Hz, found {freq}",
+                    range.start(),
+                    range.end()
+                ));
+                return None;
+            }
+            Some(freq as f32)
+        });
+
+        let orientation = self.orientation.and_then(|orientation| {
+            let angles = [orientation.roll, orientation.pitch, orientation.yaw];
+            match orientation.matrix {
+                Some(_) if angles.iter().any(Option::is_some) => {
+                    problems
+                        .push("orientation: set either roll, pitch and yaw, or matrix".to_string());
+                    None
+                }
+                Some(matrix) if !is_rotation(&matrix) => {
+                    problems.push("orientation.matrix: not a rotation matrix".to_string());
+                    None
+                }
+                Some(matrix) => Some(matrix.map(|row| row.map(|value| value as f32))),
+                None => {
+                    let [roll, pitch, yaw] = angles.map(|angle| angle.unwrap_or(0.0) as f32);
+                    Some(orientation::from_euler(roll, pitch, yaw))
+                }
+            }
+        });
+
+        let mut pwm = BTreeMap::new();
+        let mut pwm_names: [Option<&str>; PWM_CHANNELS] = Default::default();
+        for (name, output) in &self.pwm {
+            let key = format!("pwm.{name}");
+            if name.trim().is_empty() {
+                problems.push(format!("{key}: channel names can't be empty"));
+                continue;
+            }
+            let Some(channel) = pwm_channel_from_name(&output.channel) else {
+                problems.push(format!(
+                    "{key}.channel: expected \"Ch1\" to \"Ch16\", found {:?}",
+                    output.channel
+                ));
+                continue;
+            };
+            if let Some(other) = pwm_names[channel].replace(name) {
+                problems.push(format!(
+                    "{key}.channel: {} is already named {other:?}",
+                    output.channel
+                ));
+                continue;
+            }
+
+            let min = output.min.unwrap_or(0.0);
+            let max = output.max.unwrap_or(1.0);
+            let mut valid = true;
+            for (bound, value) in [("min", min), ("max", max)] {
+                if !(0.0..=1.0).contains(&value) {
+                    problems.push(format!(
+                        "{key}.{bound}: duty cycle must be between 0.0 and 1.0, found {value}"
+                    ));
+                    valid = false;
+                }
+            }
+            if valid && min > max {
+                problems.push(format!("{key}: min ({min}) is greater than max ({max})"));
+                valid = false;
+            }
+            if valid {
+                let limits = PwmLimits {
+                    min: min as f32,
+                    max: max as f32,
+                };
+                pwm.insert(name.clone(), PwmOutput { channel, limits });
+            }
+        }
+
+        let mut adc = BTreeMap::new();
+        let mut adc_names: [Option<&str>; 4] = Default::default();
+        for (name, input) in &self.adc {
+            let key = format!("adc.{name}");
+            if name.trim().is_empty() {
+                problems.push(format!("{key}: channel names can't be empty"));
+                continue;
+            }
+            let Some((index, channel)) = adc_channel_from_name(&input.channel) else {
+                problems.push(format!(
+                    "{key}.channel: expected \"Ch0\" to \"Ch3\", found {:?}",
+                    input.channel
+                ));
+                continue;
+            };
+            if let Some(other) = adc_names[index].replace(name) {
+                problems.push(format!(
+                    "{key}.channel: {} is already named {other:?}",
+                    input.channel
+                ));
+                continue;
+            }
+            adc.insert(name.clone(), channel);
+        }
+
+        let mut filters = BTreeMap::new();
+        for (name, filter) in &self.filter {
+            let key = format!("filter.{name}");
+            let Some(channel) = filter_channel_from_name(name) else {
+                problems.push(format!(
+                    "{key}: expected accel, gyro, mag, pressure, temperature or adc"
+                ));
+                continue;
+            };
+            let spec = FilterSpec::from(filter);
+            match spec.validate() {
+                Ok(()) => {
+                    filters.insert(channel as usize, spec);
+                }
+                Err(error) => problems.push(format!("{key}: {error}")),
+            }
+        }
+
+        match problems.is_empty() {
+            true => Ok(Config {
+                navigator_version,
+                raspberry_pi_version,
+                rgb_led_strip_size,
+                orientation,
+                pwm_clock_hz,
+                pwm_frequency_hz,
+                pwm_phase_stagger: self.pwm_phase_stagger,
+                pwm,
+                adc,
+                filters,
+            }),
+            false => Err(ConfigError::Invalid(problems)),
+        }
+    }
+}
+
+/// Index of a `PwmChannel` from its name, `All` can't be named.
+fn pwm_channel_from_name(name: &str) -> Option<usize> {
+    (1..=PWM_CHANNELS).position(|number| name == format!("Ch{number}"))
+}
+
+fn adc_channel_from_name(name: &str) -> Option<(usize, AdcChannel)> {
+    match name {
+        "Ch0" => Some((0, AdcChannel::Ch0)),
+        "Ch1" => Some((1, AdcChannel::Ch1)),
+        "Ch2" => Some((2, AdcChannel::Ch2)),
+        "Ch3" => Some((3, AdcChannel::Ch3)),
+        _ => None,
+    }
+}
+
+fn filter_channel_from_name(name: &str) -> Option<FilterChannel> {
+    Some(match name {
+        "accel" => FilterChannel::Accel,
+        "gyro" => FilterChannel::Gyro,
+        "mag" => FilterChannel::Mag,
+        "pressure" => FilterChannel::Pressure,
+        "temperature" => FilterChannel::Temperature,
+        "adc" => FilterChannel::Adc,
+        _ => return None,
+    })
+}
+
+/// Whether `matrix` is orthonormal and keeps handedness, within `ROTATION_TOLERANCE`.
+fn is_rotation(matrix: &[[f64; 3]; 3]) -> bool {
+    let orthonormal = (0..3).all(|i| {
+        (0..3).all(|j| {
+            let dot: f64 = (0..3).map(|k| matrix[i][k] * matrix[j][k]).sum();
+            let expected = if i == j { 1.0 } else { 0.0 };
+            (dot - expected).abs() < ROTATION_TOLERANCE
+        })
+    });
+    let [a, b, c] = matrix;
+    let determinant = a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+        + a[2] * (b[0] * c[1] - b[1] * c[0]);
+    orthonormal && determinant > 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_configuration() {
+        let config = parse(
+            r#"
+            navigator_version = 2
+            raspberry_pi_version = "auto"
+            rgb_led_strip_size = 8
+            pwm_clock_hz = 25_000_000
+            pwm_frequency_hz = 50
+            pwm_phase_stagger = true
+
+            [orientation]
+            roll = 180
+            yaw = 90
+
+            [pwm.thruster_port]
+            channel = "Ch1"
+            min = 0.05
+            max = 0.1
+
+            [pwm.lights]
+            channel = "Ch16"
+
+            [adc.battery_voltage]
+            channel = "Ch2"
+
+            [filter.gyro]
+            type = "low_pass"
+            sample_rate = 100
+            cutoff = 20
+
+            [filter.pressure]
+            type = "median"
+            window = 5
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            config.navigator_version,
+            Some(NavigatorVersion::Version2)
+        ));
+        assert!(matches!(config.raspberry_pi_version, Some(Raspberry::Auto)));
+        assert_eq!(config.rgb_led_strip_size, Some(8));
+        assert_eq!(config.pwm_clock_hz, Some(25_000_000.0));
+        assert_eq!(config.pwm_frequency_hz, Some(50.0));
+        assert_eq!(config.pwm_phase_stagger, Some(true));
+        assert_eq!(
+            config.orientation,
+            Some([[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]])
+        );
+
+        let thruster = &config.pwm["thruster_port"];
+        assert_eq!(thruster.channel, 0);
+        assert_eq!(
+            thruster.limits,
+            PwmLimits {
+                min: 0.05,
+                max: 0.1
+            }
+        );
+        assert_eq!(thruster.limits.clamp(0.5), 0.1);
+        let lights = &config.pwm["lights"];
+        assert_eq!(lights.channel, 15);
+        assert_eq!(lights.limits, PwmLimits::default());
+        assert!(matches!(config.adc["battery_voltage"], AdcChannel::Ch2));
+        assert_eq!(
+            format!("{:?}", config.filters[&(FilterChannel::Gyro as usize)]),
+            "LowPass { sample_rate: 100.0, cutoff: 20.0 }"
+        );
+        assert_eq!(
+            format!("{:?}", config.filters[&(FilterChannel::Pressure as usize)]),
+            "Median { window: 5 }"
+        );
+    }
+
+    #[test]
+    fn parses_empty_configuration() {
+        let config = parse("").unwrap();
+        assert!(config.navigator_version.is_none());
+        assert!(config.orientation.is_none());
+        assert!(config.pwm.is_empty());
+        assert!(config.adc.is_empty());
+        assert!(config.filters.is_empty());
+    }
+
+    #[test]
+    fn accepts_rotation_matrix() {
+        let config = parse(
+            r#"
+            [orientation]
+            matrix = [[0, 1, 0], [1, 0, 0], [0, 0, -1]]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.orientation,
+            Some([[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]])
+        );
+    }
+
+    #[test]
+    fn reports_every_invalid_option() {
+        let error = parse(
+            r#"
+            navigator_version = 3
+            raspberry_pi_version = "pi4"
+            rgb_led_strip_size = -1
+            pwm_frequency_hz = 5000
+
+            [orientation]
+            matrix = [[1, 0, 0], [0, 1, 0], [0, 0, -1]]
+
+            [pwm.thruster_port]
+            channel = "Ch17"
+
+            [pwm.lights]
+            channel = "Ch1"
+            min = 0.8
+            max = 0.2
+
+            [pwm.gripper]
+            channel = "Ch2"
+            max = 1.5
+
+            [pwm.thruster_starboard]
+            channel = "Ch1"
+
+            [adc.battery_voltage]
+            channel = "Ch4"
+
+            [filter.gyro]
+            type = "low_pass"
+            sample_rate = 100
+            cutoff = 50
+
+            [filter.depth]
+            type = "median"
+            window = 5
+            "#,
+        )
+        .unwrap_err();
+
+        let ConfigError::Invalid(problems) = &error else {
+            panic!("Unexpected error: {error}");
+        };
+        for key in [
+            "navigator_version:",
+            "raspberry_pi_version:",
+            "rgb_led_strip_size:",
+            "pwm_frequency_hz:",
+            "orientation.matrix:",
+            "pwm.thruster_port.channel:",
+            "pwm.lights:",
+            "pwm.gripper.max:",
+            "pwm.thruster_starboard.channel:",
+            "adc.battery_voltage.channel:",
+            "filter.gyro:",
+            "filter.depth:",
+        ] {
+            assert!(
+                problems.iter().any(|problem| problem.starts_with(key)),
+                "{key} not reported in {problems:?}"
+            );
+        }
+        assert_eq!(problems.len(), 12, "{problems:?}");
+    }
+
+    #[test]
+    fn rejects_angles_with_matrix() {
+        let error = parse(
+            r#"
+            [orientation]
+            roll = 90
+            matrix = [[1, 0, 0], [0, 1, 0], [0, 0, 1]]
+            "#,
+        )
+        .unwrap_err();
+        assert!(matches!(error, ConfigError::Invalid(_)), "{error}");
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        for text in [
+            "rgb_led_strip_size = \"eight\"",
+            "unknown_option = 1",
+            "[pwm.lights]\nchannel = \"Ch1\"\nlimit = 0.5",
+            "navigator_version = ",
+            "[filter.gyro]\ntype = \"kalman\"",
+            "[filter.gyro]\ntype = \"median\"\nwindow = 5\ncutoff = 20",
+        ] {
+            let error = parse(text).unwrap_err();
+            assert!(matches!(error, ConfigError::Parse(_)), "{text}: {error}");
+        }
+    }
+
+    #[test]
+    fn reports_missing_file() {
+        let path = std::env::temp_dir().join("navigator-config-missing.toml");
+        let error = load(&path).unwrap_err();
+        assert!(matches!(error, ConfigError::Io(..)));
+        assert!(error.to_string().contains("navigator-config-missing.toml"));
+    }
+}
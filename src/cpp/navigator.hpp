@@ -0,0 +1,513 @@
+// C++ wrapper around bindings.h, copied next to it by the build.
+//
+// `navigator::Navigator` instances release the hardware (or daemon connection)
+// when they go out of scope, and are independent from the default instance
+// used by the free functions, which `navigator::InitGuard` manages.
+//
+//   navigator::Navigator nav = navigator::Builder()
+//                                  .backend(Backend::Daemon)
+//                                  .build();
+//   printf("Temperature: %f\n", nav.read_temp());
+//
+// Requires C++11. `std::optional`, `std::span` and `std::expected` overloads
+// are available from C++17, C++20 and C++23 respectively.
+
+#pragma once
+
+#include "bindings.h"
+
+#include <array>
+#include <cmath>
+#include <cstddef>
+#include <ostream>
+#include <stdexcept>
+#include <string>
+#include <vector>
+
+#if defined(__has_include)
+#if __has_include(<version>)
+#include <version>
+#endif
+#endif
+
+#if __cplusplus >= 201703L
+#include <optional>
+#define NAVIGATOR_HAS_OPTIONAL 1
+#endif
+
+#if defined(__cpp_lib_span)
+#include <span>
+#define NAVIGATOR_HAS_SPAN 1
+#endif
+
+#if defined(__cpp_lib_expected)
+#include <expected>
+#define NAVIGATOR_HAS_EXPECTED 1
+#endif
+
+// AxisData is declared by bindings.h in the global namespace, so are its
+// operators to be found by argument-dependent lookup.
+
+inline AxisData operator+(const AxisData &a, const AxisData &b) {
+  return AxisData{a.x + b.x, a.y + b.y, a.z + b.z};
+}
+inline AxisData operator-(const AxisData &a, const AxisData &b) {
+  return AxisData{a.x - b.x, a.y - b.y, a.z - b.z};
+}
+inline AxisData operator-(const AxisData &a) { return AxisData{-a.x, -a.y, -a.z}; }
+inline AxisData operator*(const AxisData &a, float scale) {
+  return AxisData{a.x * scale, a.y * scale, a.z * scale};
+}
+inline AxisData operator*(float scale, const AxisData &a) { return a * scale; }
+inline AxisData operator/(const AxisData &a, float scale) {
+  return AxisData{a.x / scale, a.y / scale, a.z / scale};
+}
+inline AxisData &operator+=(AxisData &a, const AxisData &b) { return a = a + b; }
+inline AxisData &operator-=(AxisData &a, const AxisData &b) { return a = a - b; }
+inline AxisData &operator*=(AxisData &a, float scale) { return a = a * scale; }
+inline AxisData &operator/=(AxisData &a, float scale) { return a = a / scale; }
+inline bool operator==(const AxisData &a, const AxisData &b) {
+  return a.x == b.x && a.y == b.y && a.z == b.z;
+}
+inline bool operator!=(const AxisData &a, const AxisData &b) { return !(a == b); }
+inline std::ostream &operator<<(std::ostream &stream, const AxisData &a) {
+  return stream << "(" << a.x << ", " << a.y << ", " << a.z << ")";
+}
+
+namespace navigator {
+
+using Rgb = std::array<uint8_t, 3>;
+using Rgbw = std::array<uint8_t, 4>;
+
+// The C functions take colors as `const uint8_t (*)[N]`
+static_assert(sizeof(Rgb) == 3, "std::array<uint8_t, 3> must have no padding");
+static_assert(sizeof(Rgbw) == 4, "std::array<uint8_t, 4> must have no padding");
+
+inline float dot(const AxisData &a, const AxisData &b) {
+  return a.x * b.x + a.y * b.y + a.z * b.z;
+}
+inline AxisData cross(const AxisData &a, const AxisData &b) {
+  return AxisData{a.y * b.z - a.z * b.y, a.z * b.x - a.x * b.z, a.x * b.y - a.y * b.x};
+}
+inline float norm(const AxisData &a) { return std::sqrt(dot(a, a)); }
+
+namespace detail {
+template <size_t N>
+const uint8_t (*as_colors(const std::array<uint8_t, N> *colors))[N] {
+  return reinterpret_cast<const uint8_t(*)[N]>(colors);
+}
+} // namespace detail
+
+// Overloads of the free functions, which use the default instance
+
+inline std::array<float, 4> read_adc_all() {
+  std::array<float, 4> values{};
+  ::read_adc_all(values.data(), values.size());
+  return values;
+}
+inline void set_neopixel(const std::vector<Rgb> &colors) {
+  ::set_neopixel(detail::as_colors(colors.data()), colors.size());
+}
+template <size_t N> void set_neopixel(const std::array<Rgb, N> &colors) {
+  ::set_neopixel(detail::as_colors(colors.data()), N);
+}
+inline void set_neopixel_rgbw(const std::vector<Rgbw> &colors) {
+  ::set_neopixel_rgbw(detail::as_colors(colors.data()), colors.size());
+}
+template <size_t N> void set_neopixel_rgbw(const std::array<Rgbw, N> &colors) {
+  ::set_neopixel_rgbw(detail::as_colors(colors.data()), N);
+}
+// Replaces `samples` with up to `max_samples` samples of the IMU FIFO. Returns
+// false if the FIFO overflowed, see `navigator_last_error`
+inline bool read_imu_fifo(std::vector<ImuSample> &samples, size_t max_samples = 341) {
+  size_t count = 0;
+  samples.resize(max_samples);
+  bool read = ::read_imu_fifo(samples.data(), samples.size(), &count);
+  samples.resize(count);
+  return read;
+}
+
+#ifdef NAVIGATOR_HAS_SPAN
+inline void read_adc_all(std::span<float> values) {
+  ::read_adc_all(values.data(), values.size());
+}
+inline void set_neopixel(std::span<const Rgb> colors) {
+  ::set_neopixel(detail::as_colors(colors.data()), colors.size());
+}
+inline void set_neopixel_rgbw(std::span<const Rgbw> colors) {
+  ::set_neopixel_rgbw(detail::as_colors(colors.data()), colors.size());
+}
+#endif
+
+// Initializes the default instance, used by the free functions, and releases
+// it when going out of scope.
+class InitGuard {
+public:
+  InitGuard() { init(); }
+  ~InitGuard() { deinit(); }
+
+  InitGuard(const InitGuard &) = delete;
+  InitGuard &operator=(const InitGuard &) = delete;
+};
+
+class Builder;
+
+class Navigator {
+public:
+  // Creates an instance with the default configuration
+  Navigator();
+  ~Navigator() { navigator_free(handle_); }
+
+  Navigator(const Navigator &) = delete;
+  Navigator &operator=(const Navigator &) = delete;
+  Navigator(Navigator &&other) noexcept : handle_(other.handle_) {
+    other.handle_ = nullptr;
+  }
+  Navigator &operator=(Navigator &&other) noexcept {
+    if (this != &other) {
+      navigator_free(handle_);
+      handle_ = other.handle_;
+      other.handle_ = nullptr;
+    }
+    return *this;
+  }
+
+  // The underlying handle, to call the `navigator_*` functions directly
+  const NavigatorHandle *get() const { return handle_; }
+
+  void set_led(UserLed select, bool state) const {
+    navigator_set_led(handle_, select, state);
+  }
+  bool get_led(UserLed select) const { return navigator_get_led(handle_, select); }
+  void set_led_toggle(UserLed select) const {
+    navigator_set_led_toggle(handle_, select);
+  }
+  void set_neopixel(const uint8_t (*rgb_array)[3], size_t length) const {
+    navigator_set_neopixel(handle_, rgb_array, length);
+  }
+  void set_neopixel(const std::vector<Rgb> &colors) const {
+    set_neopixel(detail::as_colors(colors.data()), colors.size());
+  }
+  template <size_t N> void set_neopixel(const std::array<Rgb, N> &colors) const {
+    set_neopixel(detail::as_colors(colors.data()), N);
+  }
+  void set_neopixel_rgbw(const uint8_t (*rgb_array)[4], size_t length) const {
+    navigator_set_neopixel_rgbw(handle_, rgb_array, length);
+  }
+  void set_neopixel_rgbw(const std::vector<Rgbw> &colors) const {
+    set_neopixel_rgbw(detail::as_colors(colors.data()), colors.size());
+  }
+  template <size_t N> void set_neopixel_rgbw(const std::array<Rgbw, N> &colors) const {
+    set_neopixel_rgbw(detail::as_colors(colors.data()), N);
+  }
+
+  void read_adc_all(float *adc_array, size_t length) const {
+    navigator_read_adc_all(handle_, adc_array, length);
+  }
+  std::array<float, 4> read_adc_all() const {
+    std::array<float, 4> values{};
+    read_adc_all(values.data(), values.size());
+    return values;
+  }
+  float read_adc(AdcChannel channel) const {
+    return navigator_read_adc(handle_, channel);
+  }
+  float read_pressure() const { return navigator_read_pressure(handle_); }
+  float read_temp() const { return navigator_read_temp(handle_); }
+  bool read_leak() const { return navigator_read_leak(handle_); }
+  AxisData read_mag() const { return navigator_read_mag(handle_); }
+  AxisData read_accel() const { return navigator_read_accel(handle_); }
+  AxisData read_gyro() const { return navigator_read_gyro(handle_); }
+  SensorData read_all() const { return navigator_read_all(handle_); }
+  void set_accel_range(AccelRange range) const { navigator_set_accel_range(handle_, range); }
+  void set_gyro_range(GyroRange range) const { navigator_set_gyro_range(handle_, range); }
+  void set_imu_dlpf(ImuDlpf dlpf) const { navigator_set_imu_dlpf(handle_, dlpf); }
+  // Returns false if the rate is out of range, see `navigator_last_error`
+  bool set_imu_sample_rate(float rate_hz) const {
+    return navigator_set_imu_sample_rate(handle_, rate_hz);
+  }
+  // Returns false if the FIFO overflowed, see `navigator_last_error`
+  bool read_imu_fifo(ImuSample *samples, size_t capacity, size_t *count) const {
+    return navigator_read_imu_fifo(handle_, samples, capacity, count);
+  }
+  bool read_imu_fifo(std::vector<ImuSample> &samples, size_t max_samples = 341) const {
+    size_t count = 0;
+    samples.resize(max_samples);
+    bool read = read_imu_fifo(samples.data(), samples.size(), &count);
+    samples.resize(count);
+    return read;
+  }
+
+  VibrationData read_vibration() const { return navigator_read_vibration(handle_); }
+  void reset_vibration() const { navigator_reset_vibration(handle_); }
+  std::array<SensorHealth, 4> get_sensor_health() const {
+    std::array<SensorHealth, 4> health{};
+    navigator_get_sensor_health(handle_, health.data(), health.size());
+    return health;
+  }
+  void reset_sensor_health() const { navigator_reset_sensor_health(handle_); }
+  CheckedValue read_adc_checked(AdcChannel channel) const {
+    return navigator_read_adc_checked(handle_, channel);
+  }
+  CheckedValue read_pressure_checked() const {
+    return navigator_read_pressure_checked(handle_);
+  }
+  CheckedValue read_temp_checked() const {
+    return navigator_read_temp_checked(handle_);
+  }
+  CheckedAxisData read_mag_checked() const {
+    return navigator_read_mag_checked(handle_);
+  }
+  CheckedAxisData read_accel_checked() const {
+    return navigator_read_accel_checked(handle_);
+  }
+  CheckedAxisData read_gyro_checked() const {
+    return navigator_read_gyro_checked(handle_);
+  }
+
+  // The filter setters return false if the parameters are invalid, see
+  // `navigator_last_error`
+  bool set_low_pass_filter(FilterChannel channel, float sample_rate,
+                           float cutoff) const {
+    return navigator_set_low_pass_filter(handle_, channel, sample_rate, cutoff);
+  }
+  bool set_notch_filter(FilterChannel channel, float sample_rate, float center,
+                        float bandwidth) const {
+    return navigator_set_notch_filter(handle_, channel, sample_rate, center, bandwidth);
+  }
+  bool set_one_euro_filter(FilterChannel channel, float min_cutoff,
+                           float beta) const {
+    return navigator_set_one_euro_filter(handle_, channel, min_cutoff, beta);
+  }
+  bool set_moving_average_filter(FilterChannel channel, size_t window) const {
+    return navigator_set_moving_average_filter(handle_, channel, window);
+  }
+  bool set_median_filter(FilterChannel channel, size_t window) const {
+    return navigator_set_median_filter(handle_, channel, window);
+  }
+  void clear_filter(FilterChannel channel) const {
+    navigator_clear_filter(handle_, channel);
+  }
+  std::array<float, 4> read_adc_all_filtered() const {
+    std::array<float, 4> values{};
+    navigator_read_adc_all_filtered(handle_, values.data(), values.size());
+    return values;
+  }
+  float read_adc_filtered(AdcChannel channel) const {
+    return navigator_read_adc_filtered(handle_, channel);
+  }
+  float read_pressure_filtered() const {
+    return navigator_read_pressure_filtered(handle_);
+  }
+  float read_temp_filtered() const {
+    return navigator_read_temp_filtered(handle_);
+  }
+  AxisData read_mag_filtered() const { return navigator_read_mag_filtered(handle_); }
+  AxisData read_accel_filtered() const {
+    return navigator_read_accel_filtered(handle_);
+  }
+  AxisData read_gyro_filtered() const {
+    return navigator_read_gyro_filtered(handle_);
+  }
+
+  // Returns false if another client of `navigatord` claimed the PWM
+  // outputs, see `navigator_last_error`
+  bool claim_pwm() const {
+    return navigator_claim_pwm(handle_);
+  }
+  void release_pwm() const {
+    navigator_release_pwm(handle_);
+  }
+  void set_pwm_enable(bool state) const {
+    navigator_set_pwm_enable(handle_, state);
+  }
+  // Returns false if the frequency is out of range, see
+  // `navigator_last_error`
+  bool set_pwm_freq_hz(float freq) const {
+    return navigator_set_pwm_freq_hz(handle_, freq);
+  }
+  // Returns false if the prescale is below 3, see
+  // `navigator_last_error`
+  bool set_pwm_freq_prescale(uint8_t prescale) const {
+    return navigator_set_pwm_freq_prescale(handle_, prescale);
+  }
+  void set_pwm_output_mode(PwmOutputMode mode) const {
+    navigator_set_pwm_output_mode(handle_, mode);
+  }
+  void set_pwm_invert(bool inverted) const {
+    navigator_set_pwm_invert(handle_, inverted);
+  }
+  void set_pwm_channel_value(PwmChannel channel, float value) const {
+    navigator_set_pwm_channel_value(handle_, static_cast<size_t>(channel), value);
+  }
+  void set_pwm_channel_duty_cycle(PwmChannel channel, float duty_cycle) const {
+    navigator_set_pwm_channel_duty_cycle(handle_, static_cast<size_t>(channel),
+                                         duty_cycle);
+  }
+  void set_pwm_channel_full_on(PwmChannel channel) const {
+    navigator_set_pwm_channel_full_on(handle_, static_cast<size_t>(channel));
+  }
+  void set_pwm_channel_full_off(PwmChannel channel) const {
+    navigator_set_pwm_channel_full_off(handle_, static_cast<size_t>(channel));
+  }
+  // Returns false if a counter is above 4095, see
+  // `navigator_last_error`
+  bool set_pwm_channel_on_off(PwmChannel channel, uint16_t on, uint16_t off) const {
+    return navigator_set_pwm_channel_on_off(handle_, static_cast<size_t>(channel), on, off);
+  }
+  void set_pwm_phase_stagger(bool enabled) const {
+    navigator_set_pwm_phase_stagger(handle_, enabled);
+  }
+  void set_pwm_slew_rate(PwmChannel channel, float rate) const {
+    navigator_set_pwm_slew_rate(handle_, static_cast<size_t>(channel), rate);
+  }
+  void ramp_pwm_channel(PwmChannel channel, float target,
+                        uint32_t duration_ms) const {
+    navigator_ramp_pwm_channel(handle_, static_cast<size_t>(channel), target,
+                               duration_ms);
+  }
+  void stop_pwm_ramp(PwmChannel channel) const {
+    navigator_stop_pwm_ramp(handle_, static_cast<size_t>(channel));
+  }
+  // Throws std::invalid_argument if the sizes differ. Returns false if a
+  // channel is above 16, see `navigator_last_error`
+  bool set_pwm_channels_duty_cycle_values(const size_t *channels,
+                                          const float *duty_cycles,
+                                          size_t channels_length,
+                                          size_t duty_cycles_length) const {
+    if (channels_length != duty_cycles_length) {
+      throw std::invalid_argument(
+          "The number of channels and duty cycle values must match");
+    }
+    return navigator_set_pwm_channels_duty_cycle_values(handle_, channels, duty_cycles,
+                                                        channels_length);
+  }
+  bool set_pwm_channels_duty_cycle_values(const std::vector<size_t> &channels,
+                                          const std::vector<float> &duty_cycles) const {
+    return set_pwm_channels_duty_cycle_values(channels.data(), duty_cycles.data(),
+                                       channels.size(), duty_cycles.size());
+  }
+  template <size_t N>
+  bool set_pwm_channels_duty_cycle_values(const std::array<size_t, N> &channels,
+                                          const std::array<float, N> &duty_cycles) const {
+    return set_pwm_channels_duty_cycle_values(channels.data(), duty_cycles.data(), N, N);
+  }
+  PwmState get_pwm_state() const { return navigator_get_pwm_state(handle_); }
+
+#ifdef NAVIGATOR_HAS_OPTIONAL
+  // Duty cycle written to `channel`, none for `PwmChannel::All`
+  std::optional<float> get_pwm_channel_duty_cycle(PwmChannel channel) const {
+    if (channel == PwmChannel::All) {
+      return std::nullopt;
+    }
+    return get_pwm_state().duty_cycles[static_cast<size_t>(channel)];
+  }
+#endif
+
+#ifdef NAVIGATOR_HAS_SPAN
+  void set_neopixel(std::span<const Rgb> colors) const {
+    set_neopixel(detail::as_colors(colors.data()), colors.size());
+  }
+  void set_neopixel_rgbw(std::span<const Rgbw> colors) const {
+    set_neopixel_rgbw(detail::as_colors(colors.data()), colors.size());
+  }
+  void read_adc_all(std::span<float> values) const {
+    read_adc_all(values.data(), values.size());
+  }
+  bool set_pwm_channels_duty_cycle_values(std::span<const size_t> channels,
+                                          std::span<const float> duty_cycles) const {
+    return set_pwm_channels_duty_cycle_values(channels.data(), duty_cycles.data(),
+                                       channels.size(), duty_cycles.size());
+  }
+#endif
+
+private:
+  friend class Builder;
+  explicit Navigator(NavigatorHandle *handle) : handle_(handle) {}
+
+  NavigatorHandle *handle_;
+};
+
+// Navigator configuration, with the same options as the `set_*` functions
+class Builder {
+public:
+  Builder() : builder_(navigator_builder_new()) {}
+  ~Builder() { navigator_builder_free(builder_); }
+
+  Builder(const Builder &) = delete;
+  Builder &operator=(const Builder &) = delete;
+  Builder(Builder &&other) noexcept : builder_(other.builder_) {
+    other.builder_ = nullptr;
+  }
+  Builder &operator=(Builder &&other) noexcept {
+    if (this != &other) {
+      navigator_builder_free(builder_);
+      builder_ = other.builder_;
+      other.builder_ = nullptr;
+    }
+    return *this;
+  }
+
+  Builder &rgb_led_strip_size(size_t size) {
+    navigator_builder_set_rgb_led_strip_size(builder_, size);
+    return *this;
+  }
+  Builder &navigator_version(NavigatorVersion version) {
+    navigator_builder_set_navigator_version(builder_, version);
+    return *this;
+  }
+  Builder &raspberry_pi_version(Raspberry version) {
+    navigator_builder_set_raspberry_pi_version(builder_, version);
+    return *this;
+  }
+  // A null `socket_path` selects the default `/run/navigator.sock`
+  Builder &backend(Backend backend, const char *socket_path = nullptr) {
+    navigator_builder_set_backend(builder_, backend, socket_path);
+    return *this;
+  }
+  Builder &board_orientation(Rotation rotation) {
+    navigator_builder_set_board_orientation(builder_, rotation);
+    return *this;
+  }
+  Builder &pwm_clock_hz(float clock) {
+    navigator_builder_set_pwm_clock_hz(builder_, clock);
+    return *this;
+  }
+
+  // Throws std::runtime_error if the hardware or daemon can't be accessed
+  Navigator build() const {
+    NavigatorHandle *handle = navigator_builder_build(builder_);
+    if (handle == nullptr) {
+      throw std::runtime_error(navigator_last_error());
+    }
+    return Navigator(handle);
+  }
+
+#ifdef NAVIGATOR_HAS_OPTIONAL
+  std::optional<Navigator> try_build() const {
+    NavigatorHandle *handle = navigator_builder_build(builder_);
+    if (handle == nullptr) {
+      return std::nullopt;
+    }
+    return Navigator(handle);
+  }
+#endif
+
+#ifdef NAVIGATOR_HAS_EXPECTED
+  // The error is the failure message
+  std::expected<Navigator, std::string> build_expected() const {
+    NavigatorHandle *handle = navigator_builder_build(builder_);
+    if (handle == nullptr) {
+      return std::unexpected(std::string(navigator_last_error()));
+    }
+    return Navigator(handle);
+  }
+#endif
+
+private:
+  NavigatorBuilder *builder_;
+};
+
+inline Navigator::Navigator() : Navigator(Builder().build()) {}
+
+} // namespace navigator
@@ -0,0 +1,1129 @@
+//! Broker used to share a single Navigator board between multiple processes.
+//!
+//! `navigatord` owns the hardware and listens on a Unix socket, while clients forward every call
+//! as a single text line. Requests are answered with `ok` followed by the returned values, or with
+//! `error` followed by a message. PWM requests are only accepted from the client that claimed the
+//! outputs with `claim_pwm`, until it sends `release_pwm` or disconnects.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use navigator_rs::{AdcChannel, AxisData, SensorData, UserLed};
+
+use crate::backend::{NavigatorBackend, PwmState, PWM_CHANNELS};
+use crate::{
+    panic_message, AccelRange, DetectedHardware, GyroRange, ImuDlpf, NavigatorVersion,
+    PwmOutputMode, Raspberry,
+};
+
+pub const DEFAULT_SOCKET_PATH: &str = "/run/navigator.sock";
+
+/// A call forwarded to the daemon, sent as a line of text.
+#[derive(Debug)]
+enum Request {
+    SetLed(UserLed, bool),
+    GetLed(UserLed),
+    SetLedToggle(UserLed),
+    SetNeopixel(Vec<[u8; 3]>),
+    SetNeopixelRgbw(Vec<[u8; 4]>),
+    ReadAdcAll,
+    ReadAdc(AdcChannel),
+    ReadPressure,
+    ReadTemperature,
+    ReadMag,
+    ReadAccel,
+    ReadGyro,
+    ReadLeak,
+    SetAccelRange(AccelRange),
+    SetGyroRange(GyroRange),
+    SetImuDlpf(ImuDlpf),
+    SetImuSampleRate(f32),
+    ReadImuFifo(usize),
+    ReadImuBatch(usize),
+    SetPwmEnable(bool),
+    SetPwmFrequency(f32),
+    SetPwmPrescale(u8),
+    SetPwmDutyCycle(usize, f32),
+    SetPwmDutyCycles(Vec<(usize, f32)>),
+    SetPwmChannelOnOff(usize, u16, u16),
+    SetPwmPhaseStagger(bool),
+    SetPwmOutputMode(PwmOutputMode),
+    SetPwmInvert(bool),
+    GetPwmState,
+    GetDetectedHardware,
+    ReadAll,
+    ClaimPwm,
+    ReleasePwm,
+}
+
+impl Request {
+    fn is_pwm(&self) -> bool {
+        matches!(
+            self,
+            Request::SetPwmEnable(_)
+                | Request::SetPwmFrequency(_)
+                | Request::SetPwmPrescale(_)
+                | Request::SetPwmDutyCycle(..)
+                | Request::SetPwmDutyCycles(_)
+                | Request::SetPwmChannelOnOff(..)
+                | Request::SetPwmPhaseStagger(_)
+                | Request::SetPwmOutputMode(_)
+                | Request::SetPwmInvert(_)
+        )
+    }
+
+    /// Runs the request, returning its values or the error of a rejected argument.
+    fn execute(&self, navigator: &mut dyn NavigatorBackend) -> Result<Vec<f32>, String> {
+        match self {
+            Request::SetLed(select, state) => navigator.set_led(*select, *state),
+            Request::GetLed(select) => return Ok(vec![navigator.get_led(*select) as u8 as f32]),
+            Request::SetLedToggle(select) => navigator.set_led_toggle(*select),
+            Request::SetNeopixel(colors) => navigator.set_neopixel(colors),
+            Request::SetNeopixelRgbw(colors) => navigator.set_neopixel_rgbw(colors),
+            Request::ReadAdcAll => return Ok(navigator.read_adc_all()),
+            Request::ReadAdc(channel) => return Ok(vec![navigator.read_adc(*channel)]),
+            Request::ReadPressure => return Ok(vec![navigator.read_pressure()]),
+            Request::ReadTemperature => return Ok(vec![navigator.read_temperature()]),
+            Request::ReadMag => return Ok(axis_to_vec(navigator.read_mag())),
+            Request::ReadAccel => return Ok(axis_to_vec(navigator.read_accel())),
+            Request::ReadGyro => return Ok(axis_to_vec(navigator.read_gyro())),
+            Request::ReadLeak => return Ok(vec![navigator.read_leak() as u8 as f32]),
+            Request::SetAccelRange(range) => navigator.set_accel_range(range.clone()),
+            Request::SetGyroRange(range) => navigator.set_gyro_range(range.clone()),
+            Request::SetImuDlpf(dlpf) => navigator.set_imu_dlpf(dlpf.clone()),
+            Request::SetImuSampleRate(rate) => navigator.set_imu_sample_rate(*rate)?,
+            Request::ReadImuFifo(max_samples) => {
+                let mut values = vec![];
+                for (accel, gyro) in navigator.read_imu_fifo(*max_samples)? {
+                    values.extend(axis_to_vec(accel));
+                    values.extend(axis_to_vec(gyro));
+                }
+                return Ok(values);
+            }
+            Request::SetPwmEnable(state) => navigator.set_pwm_enable(*state),
+            Request::SetPwmFrequency(freq) => navigator.set_pwm_frequency(*freq)?,
+            Request::SetPwmPrescale(prescale) => navigator.set_pwm_prescale(*prescale)?,
+            Request::SetPwmDutyCycle(channel, duty_cycle) => {
+                navigator.set_pwm_duty_cycle(*channel, *duty_cycle)
+            }
+            Request::SetPwmDutyCycles(values) => {
+                let (channels, duty_cycles): (Vec<usize>, Vec<f32>) =
+                    values.iter().copied().unzip();
+                navigator.set_pwm_duty_cycles(&channels, &duty_cycles)
+            }
+            Request::SetPwmChannelOnOff(channel, on, off) => {
+                navigator.set_pwm_channel_on_off(*channel, *on, *off)
+            }
+            Request::SetPwmPhaseStagger(enabled) => navigator.set_pwm_phase_stagger(*enabled),
+            Request::SetPwmOutputMode(mode) => navigator.set_pwm_output_mode(mode.clone()),
+            Request::SetPwmInvert(inverted) => navigator.set_pwm_invert(*inverted),
+            Request::GetPwmState => {
+                let state = navigator.pwm_state();
+                let mut values = vec![
+                    state.enabled as u8 as f32,
+                    state.frequency,
+                    state.prescale as f32,
+                ];
+                values.extend(state.duty_cycles);
+                return Ok(values);
+            }
+            Request::GetDetectedHardware => {
+                let hardware = navigator.detected_hardware();
+                return Ok(vec![
+                    hardware.raspberry_pi_version as u8 as f32,
+                    hardware.navigator_version as u8 as f32,
+                    hardware.raspberry_pi_detected as u8 as f32,
+                    hardware.navigator_detected as u8 as f32,
+                ]);
+            }
+            Request::ReadImuBatch(n) => {
+                let mut values = Vec::with_capacity(n * 9);
+                for sample in navigator.read_imu_batch(*n) {
+                    for axis in sample {
+                        values.extend(axis_to_vec(axis));
+                    }
+                }
+                return Ok(values);
+            }
+            Request::ClaimPwm => navigator.claim_pwm()?,
+            Request::ReleasePwm => navigator.release_pwm(),
+            Request::ReadAll => {
+                let data = navigator.read_all();
+                let mut values = data.adc;
+                values.extend([data.temperature, data.pressure, data.leak as u8 as f32]);
+                for axis in [data.accelerometer, data.magnetometer, data.gyro] {
+                    values.extend(axis_to_vec(axis));
+                }
+                return Ok(values);
+            }
+        }
+        Ok(vec![])
+    }
+}
+
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Request::SetLed(select, state) => {
+                write!(f, "set_led {} {}", led_to_str(select), *state as u8)
+            }
+            Request::GetLed(select) => write!(f, "get_led {}", led_to_str(select)),
+            Request::SetLedToggle(select) => write!(f, "set_led_toggle {}", led_to_str(select)),
+            Request::SetNeopixel(colors) => {
+                write!(f, "set_neopixel")?;
+                for [r, g, b] in colors {
+                    write!(f, " {r},{g},{b}")?;
+                }
+                Ok(())
+            }
+            Request::SetNeopixelRgbw(colors) => {
+                write!(f, "set_neopixel_rgbw")?;
+                for [r, g, b, w] in colors {
+                    write!(f, " {r},{g},{b},{w}")?;
+                }
+                Ok(())
+            }
+            Request::ReadAdcAll => write!(f, "read_adc_all"),
+            Request::ReadAdc(channel) => write!(f, "read_adc {channel:?}"),
+            Request::ReadPressure => write!(f, "read_pressure"),
+            Request::ReadTemperature => write!(f, "read_temperature"),
+            Request::ReadMag => write!(f, "read_mag"),
+            Request::ReadAccel => write!(f, "read_accel"),
+            Request::ReadGyro => write!(f, "read_gyro"),
+            Request::ReadLeak => write!(f, "read_leak"),
+            Request::SetAccelRange(range) => write!(f, "set_accel_range {range:?}"),
+            Request::SetGyroRange(range) => write!(f, "set_gyro_range {range:?}"),
+            Request::SetImuDlpf(dlpf) => write!(f, "set_imu_dlpf {dlpf:?}"),
+            Request::SetImuSampleRate(rate) => write!(f, "set_imu_sample_rate {rate}"),
+            Request::ReadImuFifo(max_samples) => write!(f, "read_imu_fifo {max_samples}"),
+            Request::ReadImuBatch(n) => write!(f, "read_imu_batch {n}"),
+            Request::SetPwmEnable(state) => write!(f, "set_pwm_enable {}", *state as u8),
+            Request::SetPwmFrequency(freq) => write!(f, "set_pwm_frequency {freq}"),
+            Request::SetPwmPrescale(prescale) => write!(f, "set_pwm_prescale {prescale}"),
+            Request::SetPwmDutyCycle(channel, duty_cycle) => {
+                write!(f, "set_pwm_duty_cycle {channel} {duty_cycle}")
+            }
+            Request::SetPwmDutyCycles(values) => {
+                write!(f, "set_pwm_duty_cycles")?;
+                for (channel, duty_cycle) in values {
+                    write!(f, " {channel},{duty_cycle}")?;
+                }
+                Ok(())
+            }
+            Request::SetPwmChannelOnOff(channel, on, off) => {
+                write!(f, "set_pwm_channel_on_off {channel} {on} {off}")
+            }
+            Request::SetPwmPhaseStagger(enabled) => {
+                write!(f, "set_pwm_phase_stagger {}", *enabled as u8)
+            }
+            Request::SetPwmOutputMode(mode) => {
+                write!(f, "set_pwm_output_mode {}", output_mode_to_str(mode))
+            }
+            Request::SetPwmInvert(inverted) => write!(f, "set_pwm_invert {}", *inverted as u8),
+            Request::GetPwmState => write!(f, "get_pwm_state"),
+            Request::GetDetectedHardware => write!(f, "get_detected_hardware"),
+            Request::ReadAll => write!(f, "read_all"),
+            Request::ClaimPwm => write!(f, "claim_pwm"),
+            Request::ReleasePwm => write!(f, "release_pwm"),
+        }
+    }
+}
+
+impl FromStr for Request {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = words.next().ok_or("Empty request")?;
+        let mut arg = || {
+            words
+                .next()
+                .ok_or(format!("Missing argument for {command}"))
+        };
+
+        let request = match command {
+            "set_led" => Request::SetLed(led_from_str(arg()?)?, parse_bool(arg()?)?),
+            "get_led" => Request::GetLed(led_from_str(arg()?)?),
+            "set_led_toggle" => Request::SetLedToggle(led_from_str(arg()?)?),
+            "set_neopixel" => {
+                Request::SetNeopixel(words.map(parse_color::<3>).collect::<Result<Vec<_>, _>>()?)
+            }
+            "set_neopixel_rgbw" => Request::SetNeopixelRgbw(
+                words.map(parse_color::<4>).collect::<Result<Vec<_>, _>>()?,
+            ),
+            "read_adc_all" => Request::ReadAdcAll,
+            "read_adc" => Request::ReadAdc(adc_from_str(arg()?)?),
+            "read_pressure" => Request::ReadPressure,
+            "read_temperature" => Request::ReadTemperature,
+            "read_mag" => Request::ReadMag,
+            "read_accel" => Request::ReadAccel,
+            "read_gyro" => Request::ReadGyro,
+            "read_leak" => Request::ReadLeak,
+            "set_accel_range" => Request::SetAccelRange(accel_range_from_str(arg()?)?),
+            "set_gyro_range" => Request::SetGyroRange(gyro_range_from_str(arg()?)?),
+            "set_imu_dlpf" => Request::SetImuDlpf(imu_dlpf_from_str(arg()?)?),
+            "set_imu_sample_rate" => Request::SetImuSampleRate(parse_number(arg()?)?),
+            "read_imu_fifo" => Request::ReadImuFifo(parse_number(arg()?)?),
+            "read_imu_batch" => Request::ReadImuBatch(parse_number(arg()?)?),
+            "set_pwm_enable" => Request::SetPwmEnable(parse_bool(arg()?)?),
+            "set_pwm_frequency" => Request::SetPwmFrequency(parse_number(arg()?)?),
+            "set_pwm_prescale" => Request::SetPwmPrescale(parse_number(arg()?)?),
+            "set_pwm_duty_cycle" => {
+                Request::SetPwmDutyCycle(parse_number(arg()?)?, parse_number(arg()?)?)
+            }
+            "set_pwm_duty_cycles" => Request::SetPwmDutyCycles(
+                words
+                    .map(parse_channel_duty_cycle)
+                    .collect::<Result<_, _>>()?,
+            ),
+            "set_pwm_channel_on_off" => Request::SetPwmChannelOnOff(
+                parse_number(arg()?)?,
+                parse_number(arg()?)?,
+                parse_number(arg()?)?,
+            ),
+            "set_pwm_phase_stagger" => Request::SetPwmPhaseStagger(parse_bool(arg()?)?),
+            "set_pwm_output_mode" => Request::SetPwmOutputMode(output_mode_from_str(arg()?)?),
+            "set_pwm_invert" => Request::SetPwmInvert(parse_bool(arg()?)?),
+            "get_pwm_state" => Request::GetPwmState,
+            "get_detected_hardware" => Request::GetDetectedHardware,
+            "read_all" => Request::ReadAll,
+            "claim_pwm" => Request::ClaimPwm,
+            "release_pwm" => Request::ReleasePwm,
+            _ => return Err(format!("Unknown request: {command}")),
+        };
+        Ok(request)
+    }
+}
+
+fn axis_to_vec(axis: AxisData) -> Vec<f32> {
+    vec![axis.x, axis.y, axis.z]
+}
+
+fn vec_to_axis(values: &[f32]) -> AxisData {
+    AxisData {
+        x: values[0],
+        y: values[1],
+        z: values[2],
+    }
+}
+
+fn led_to_str(select: &UserLed) -> &'static str {
+    match select {
+        UserLed::Led1 => "Led1",
+        UserLed::Led2 => "Led2",
+        UserLed::Led3 => "Led3",
+    }
+}
+
+fn led_from_str(value: &str) -> Result<UserLed, String> {
+    match value {
+        "Led1" => Ok(UserLed::Led1),
+        "Led2" => Ok(UserLed::Led2),
+        "Led3" => Ok(UserLed::Led3),
+        _ => Err(format!("Invalid LED: {value}")),
+    }
+}
+
+fn adc_from_str(value: &str) -> Result<AdcChannel, String> {
+    match value {
+        "Ch0" => Ok(AdcChannel::Ch0),
+        "Ch1" => Ok(AdcChannel::Ch1),
+        "Ch2" => Ok(AdcChannel::Ch2),
+        "Ch3" => Ok(AdcChannel::Ch3),
+        _ => Err(format!("Invalid ADC channel: {value}")),
+    }
+}
+
+fn accel_range_from_str(value: &str) -> Result<AccelRange, String> {
+    match value {
+        "G2" => Ok(AccelRange::G2),
+        "G4" => Ok(AccelRange::G4),
+        "G8" => Ok(AccelRange::G8),
+        "G16" => Ok(AccelRange::G16),
+        _ => Err(format!("Invalid accelerometer range: {value}")),
+    }
+}
+
+fn gyro_range_from_str(value: &str) -> Result<GyroRange, String> {
+    match value {
+        "Dps250" => Ok(GyroRange::Dps250),
+        "Dps500" => Ok(GyroRange::Dps500),
+        "Dps1000" => Ok(GyroRange::Dps1000),
+        "Dps2000" => Ok(GyroRange::Dps2000),
+        _ => Err(format!("Invalid gyroscope range: {value}")),
+    }
+}
+
+fn imu_dlpf_from_str(value: &str) -> Result<ImuDlpf, String> {
+    match value {
+        "Hz176" => Ok(ImuDlpf::Hz176),
+        "Hz92" => Ok(ImuDlpf::Hz92),
+        "Hz41" => Ok(ImuDlpf::Hz41),
+        "Hz20" => Ok(ImuDlpf::Hz20),
+        "Hz10" => Ok(ImuDlpf::Hz10),
+        "Hz5" => Ok(ImuDlpf::Hz5),
+        _ => Err(format!("Invalid IMU low-pass filter: {value}")),
+    }
+}
+
+fn output_mode_to_str(mode: &PwmOutputMode) -> &'static str {
+    match mode {
+        PwmOutputMode::TotemPole => "TotemPole",
+        PwmOutputMode::OpenDrain => "OpenDrain",
+    }
+}
+
+fn output_mode_from_str(value: &str) -> Result<PwmOutputMode, String> {
+    match value {
+        "TotemPole" => Ok(PwmOutputMode::TotemPole),
+        "OpenDrain" => Ok(PwmOutputMode::OpenDrain),
+        _ => Err(format!("Invalid PWM output mode: {value}")),
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(format!("Invalid boolean: {value}")),
+    }
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid number: {value}"))
+}
+
+fn parse_color<const N: usize>(value: &str) -> Result<[u8; N], String> {
+    let components = value
+        .split(',')
+        .map(parse_number::<u8>)
+        .collect::<Result<Vec<_>, _>>()?;
+    components
+        .try_into()
+        .map_err(|_| format!("Invalid color: {value}"))
+}
+
+fn parse_channel_duty_cycle(value: &str) -> Result<(usize, f32), String> {
+    let (channel, duty_cycle) = value
+        .split_once(',')
+        .ok_or(format!("Invalid channel duty cycle: {value}"))?;
+    Ok((parse_number(channel)?, parse_number(duty_cycle)?))
+}
+
+/// Failure of a request sent to the daemon.
+#[derive(Debug)]
+pub enum DaemonError {
+    /// The daemon can't be reached, or closed the connection.
+    Io(io::Error),
+    /// The daemon failed to execute the request.
+    Rejected(String),
+    /// The response isn't valid for the request.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for DaemonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DaemonError::Io(error) => write!(f, "Navigator daemon is unreachable: {error}"),
+            DaemonError::Rejected(message) => write!(f, "Navigator daemon failed: {message}"),
+            DaemonError::InvalidResponse(response) => {
+                write!(f, "Invalid response from navigator daemon: {response}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DaemonError {}
+
+impl From<io::Error> for DaemonError {
+    fn from(error: io::Error) -> Self {
+        DaemonError::Io(error)
+    }
+}
+
+/// Parses a response line, `ok` followed by the returned values or `error` followed by a message.
+fn parse_response(line: &str) -> Result<Vec<f32>, DaemonError> {
+    let mut words = line.split_whitespace();
+    match words.next() {
+        Some("ok") => words
+            .map(parse_number)
+            .collect::<Result<_, _>>()
+            .map_err(|_| DaemonError::InvalidResponse(line.trim_end().to_string())),
+        Some("error") => Err(DaemonError::Rejected(words.collect::<Vec<_>>().join(" "))),
+        _ => Err(DaemonError::InvalidResponse(line.trim_end().to_string())),
+    }
+}
+
+/// Writes `line` without raising SIGPIPE when the daemon closed the connection, which would kill
+/// C programs keeping the default signal disposition.
+fn send_line(stream: &UnixStream, line: &str) -> io::Result<()> {
+    let mut data = line.as_bytes();
+    while !data.is_empty() {
+        let sent = unsafe {
+            libc::send(
+                stream.as_raw_fd(),
+                data.as_ptr().cast(),
+                data.len(),
+                libc::MSG_NOSIGNAL,
+            )
+        };
+        if sent < 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+            continue;
+        }
+        data = &data[sent as usize..];
+    }
+    Ok(())
+}
+
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Connection {
+    fn open(socket_path: &Path) -> io::Result<Self> {
+        let writer = UnixStream::connect(socket_path)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(Self { reader, writer })
+    }
+
+    fn read_response(&mut self) -> Result<Vec<f32>, DaemonError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        parse_response(&line)
+    }
+}
+
+/// Client side of the broker, used when the backend is set to [`crate::Backend::Daemon`].
+///
+/// A lost connection, e.g. when `navigatord` restarts, is opened again by the next request.
+pub struct DaemonClient {
+    socket_path: PathBuf,
+    // None after a connection failure
+    connection: Option<Connection>,
+}
+
+impl DaemonClient {
+    pub fn connect<P: AsRef<Path>>(socket_path: P) -> io::Result<Self> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+        let connection = Connection::open(&socket_path)?;
+        Ok(Self {
+            socket_path,
+            connection: Some(connection),
+        })
+    }
+
+    /// Sends `request` and returns its values. A request that couldn't be sent is sent again on a
+    /// new connection, but not one lost while waiting for its response, which the daemon may have
+    /// executed.
+    fn request(&mut self, request: &Request) -> Result<Vec<f32>, DaemonError> {
+        let line = format!("{request}\n");
+        let mut connection = match self.connection.take() {
+            Some(connection) => connection,
+            None => Connection::open(&self.socket_path)?,
+        };
+        if send_line(&connection.writer, &line).is_err() {
+            connection = Connection::open(&self.socket_path)?;
+            send_line(&connection.writer, &line)?;
+        }
+        let response = connection.read_response();
+        // The connection is still in sync unless the response couldn't be read
+        if !matches!(response, Err(DaemonError::Io(_))) {
+            self.connection = Some(connection);
+        }
+        response
+    }
+
+    /// Sends `request`, expecting at least `count` values. Failures panic like the hardware
+    /// failures of navigator-rs, so the bindings report both the same way, as exceptions or through
+    /// `navigator_last_error`, and they are counted in the sensor health.
+    fn call(&mut self, request: Request, count: usize) -> Vec<f32> {
+        let response = self
+            .request(&request)
+            .and_then(|values| match values.len() < count {
+                true => Err(DaemonError::InvalidResponse(format!(
+                    "{} values instead of {count}",
+                    values.len()
+                ))),
+                false => Ok(values),
+            });
+        response.unwrap_or_else(|error| panic!("{error} (request `{request}`)"))
+    }
+
+    /// Sends a request the daemon can reject, returning its values or its error. Other failures
+    /// panic like in `call`.
+    fn try_call(&mut self, request: Request) -> Result<Vec<f32>, String> {
+        match self.request(&request) {
+            Ok(values) => Ok(values),
+            Err(DaemonError::Rejected(message)) => Err(message),
+            Err(error) => panic!("{error} (request `{request}`)"),
+        }
+    }
+
+    fn call_axis(&mut self, request: Request) -> AxisData {
+        vec_to_axis(&self.call(request, 3))
+    }
+}
+
+impl NavigatorBackend for DaemonClient {
+    fn set_led(&mut self, select: UserLed, state: bool) {
+        self.call(Request::SetLed(select, state), 0);
+    }
+
+    fn get_led(&mut self, select: UserLed) -> bool {
+        self.call(Request::GetLed(select), 1)[0] != 0.0
+    }
+
+    fn set_led_toggle(&mut self, select: UserLed) {
+        self.call(Request::SetLedToggle(select), 0);
+    }
+
+    fn set_neopixel(&mut self, colors: &[[u8; 3]]) {
+        self.call(Request::SetNeopixel(colors.to_vec()), 0);
+    }
+
+    fn set_neopixel_rgbw(&mut self, colors: &[[u8; 4]]) {
+        self.call(Request::SetNeopixelRgbw(colors.to_vec()), 0);
+    }
+
+    fn read_adc_all(&mut self) -> Vec<f32> {
+        self.call(Request::ReadAdcAll, 0)
+    }
+
+    fn read_adc(&mut self, channel: AdcChannel) -> f32 {
+        self.call(Request::ReadAdc(channel), 1)[0]
+    }
+
+    fn read_pressure(&mut self) -> f32 {
+        self.call(Request::ReadPressure, 1)[0]
+    }
+
+    fn read_temperature(&mut self) -> f32 {
+        self.call(Request::ReadTemperature, 1)[0]
+    }
+
+    fn read_mag(&mut self) -> AxisData {
+        self.call_axis(Request::ReadMag)
+    }
+
+    fn read_accel(&mut self) -> AxisData {
+        self.call_axis(Request::ReadAccel)
+    }
+
+    fn read_gyro(&mut self) -> AxisData {
+        self.call_axis(Request::ReadGyro)
+    }
+
+    fn read_leak(&mut self) -> bool {
+        self.call(Request::ReadLeak, 1)[0] != 0.0
+    }
+
+    fn set_accel_range(&mut self, range: AccelRange) {
+        self.call(Request::SetAccelRange(range), 0);
+    }
+
+    fn set_gyro_range(&mut self, range: GyroRange) {
+        self.call(Request::SetGyroRange(range), 0);
+    }
+
+    fn set_imu_dlpf(&mut self, dlpf: ImuDlpf) {
+        self.call(Request::SetImuDlpf(dlpf), 0);
+    }
+
+    fn set_imu_sample_rate(&mut self, rate_hz: f32) -> Result<(), String> {
+        self.try_call(Request::SetImuSampleRate(rate_hz)).map(drop)
+    }
+
+    fn read_imu_fifo(&mut self, max_samples: usize) -> Result<Vec<(AxisData, AxisData)>, String> {
+        let values = self.try_call(Request::ReadImuFifo(max_samples))?;
+        Ok(values
+            .chunks_exact(6)
+            .map(|sample| (vec_to_axis(&sample[..3]), vec_to_axis(&sample[3..])))
+            .collect())
+    }
+
+    fn set_pwm_enable(&mut self, enable: bool) {
+        self.call(Request::SetPwmEnable(enable), 0);
+    }
+
+    fn set_pwm_frequency(&mut self, freq_hz: f32) -> Result<(), String> {
+        self.try_call(Request::SetPwmFrequency(freq_hz)).map(drop)
+    }
+
+    fn set_pwm_prescale(&mut self, prescale: u8) -> Result<(), String> {
+        self.try_call(Request::SetPwmPrescale(prescale)).map(drop)
+    }
+
+    fn set_pwm_output_mode(&mut self, mode: PwmOutputMode) {
+        self.call(Request::SetPwmOutputMode(mode), 0);
+    }
+
+    fn set_pwm_invert(&mut self, inverted: bool) {
+        self.call(Request::SetPwmInvert(inverted), 0);
+    }
+
+    fn set_pwm_duty_cycle(&mut self, channel: usize, duty_cycle: f32) {
+        self.call(Request::SetPwmDutyCycle(channel, duty_cycle), 0);
+    }
+
+    fn set_pwm_duty_cycles(&mut self, channels: &[usize], duty_cycles: &[f32]) {
+        let values = channels.iter().copied().zip(duty_cycles.iter().copied());
+        self.call(Request::SetPwmDutyCycles(values.collect()), 0);
+    }
+
+    fn set_pwm_channel_on_off(&mut self, channel: usize, on: u16, off: u16) {
+        self.call(Request::SetPwmChannelOnOff(channel, on, off), 0);
+    }
+
+    fn set_pwm_phase_stagger(&mut self, enabled: bool) {
+        self.call(Request::SetPwmPhaseStagger(enabled), 0);
+    }
+
+    fn pwm_state(&mut self) -> PwmState {
+        let values = self.call(Request::GetPwmState, 3 + PWM_CHANNELS);
+        let mut duty_cycles = [0.0; PWM_CHANNELS];
+        duty_cycles.copy_from_slice(&values[3..3 + PWM_CHANNELS]);
+        PwmState {
+            enabled: values[0] != 0.0,
+            frequency: values[1],
+            prescale: values[2] as u8,
+            duty_cycles,
+        }
+    }
+
+    fn claim_pwm(&mut self) -> Result<(), String> {
+        self.try_call(Request::ClaimPwm).map(drop)
+    }
+
+    fn release_pwm(&mut self) {
+        self.call(Request::ReleasePwm, 0);
+    }
+
+    fn detected_hardware(&mut self) -> DetectedHardware {
+        let values = self.call(Request::GetDetectedHardware, 4);
+        DetectedHardware {
+            raspberry_pi_version: match values[0] as u8 {
+                0 => Raspberry::Pi4,
+                _ => Raspberry::Pi5,
+            },
+            navigator_version: match values[1] as u8 {
+                0 => NavigatorVersion::Version1,
+                _ => NavigatorVersion::Version2,
+            },
+            raspberry_pi_detected: values[2] != 0.0,
+            navigator_detected: values[3] != 0.0,
+        }
+    }
+
+    fn read_imu_batch(&mut self, n: usize) -> Vec<[AxisData; 3]> {
+        let values = self.call(Request::ReadImuBatch(n), n * 9);
+        values
+            .chunks_exact(9)
+            .map(|sample| {
+                [
+                    vec_to_axis(&sample[..3]),
+                    vec_to_axis(&sample[3..6]),
+                    vec_to_axis(&sample[6..]),
+                ]
+            })
+            .collect()
+    }
+
+    fn read_all(&mut self) -> SensorData {
+        let values = self.call(Request::ReadAll, 12);
+        // The ADC channels come first, followed by a fixed number of values
+        let (adc, values) = values.split_at(values.len() - 12);
+        SensorData {
+            adc: adc.to_vec(),
+            temperature: values[0],
+            pressure: values[1],
+            leak: values[2] != 0.0,
+            accelerometer: vec_to_axis(&values[3..6]),
+            magnetometer: vec_to_axis(&values[6..9]),
+            gyro: vec_to_axis(&values[9..12]),
+        }
+    }
+}
+
+struct Broker {
+    navigator: Box<dyn NavigatorBackend>,
+    // Only a single client is allowed to drive the PWM outputs, the one that claimed them
+    pwm_owner: Option<usize>,
+}
+
+/// Serves `navigator` on `socket_path` until the listener fails.
+pub fn serve<P: AsRef<Path>>(
+    navigator: Box<dyn NavigatorBackend>,
+    socket_path: P,
+) -> io::Result<()> {
+    let socket_path = socket_path.as_ref();
+    if socket_path.exists() {
+        std::fs::remove_file(socket_path)?;
+    }
+    let listener = UnixListener::bind(socket_path)?;
+    let broker = Arc::new(Mutex::new(Broker {
+        navigator,
+        pwm_owner: None,
+    }));
+
+    for (client_id, stream) in listener.incoming().enumerate() {
+        let stream = stream?;
+        let broker = broker.clone();
+        std::thread::spawn(move || {
+            if let Err(error) = handle_client(client_id, stream, &broker) {
+                eprintln!("Client {client_id} disconnected: {error}");
+            }
+            let mut broker = broker.lock().unwrap();
+            if broker.pwm_owner == Some(client_id) {
+                println!("Client {client_id} released the PWM outputs by disconnecting");
+                broker.pwm_owner = None;
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_client(client_id: usize, stream: UnixStream, broker: &Mutex<Broker>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let response = match line?.parse::<Request>() {
+            Ok(request) => process(client_id, &request, &mut broker.lock().unwrap()),
+            Err(error) => Err(error),
+        };
+        match response {
+            Ok(values) => {
+                let values: Vec<String> = values.iter().map(f32::to_string).collect();
+                writeln!(writer, "ok {}", values.join(" "))?
+            }
+            Err(error) => writeln!(writer, "error {error}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Gives the PWM outputs to the client claiming them, and rejects the PWM requests of the other
+/// clients.
+fn arbitrate_pwm(
+    pwm_owner: &mut Option<usize>,
+    client_id: usize,
+    request: &Request,
+) -> Result<(), String> {
+    match (request, *pwm_owner) {
+        (Request::ClaimPwm | Request::ReleasePwm, Some(owner)) if owner != client_id => {
+            Err(format!("PWM is owned by client {owner}"))
+        }
+        (Request::ClaimPwm, None) => {
+            println!("Client {client_id} claimed the PWM outputs");
+            *pwm_owner = Some(client_id);
+            Ok(())
+        }
+        (Request::ReleasePwm, Some(_)) => {
+            println!("Client {client_id} released the PWM outputs");
+            *pwm_owner = None;
+            Ok(())
+        }
+        (request, Some(owner)) if request.is_pwm() && owner != client_id => {
+            Err(format!("PWM is owned by client {owner}"))
+        }
+        (request, None) if request.is_pwm() => {
+            Err("PWM outputs must be claimed with claim_pwm first".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn process(client_id: usize, request: &Request, broker: &mut Broker) -> Result<Vec<f32>, String> {
+    arbitrate_pwm(&mut broker.pwm_owner, client_id, request)?;
+
+    // navigator-rs panics on hardware failures, which should not take the daemon down
+    let navigator = &mut broker.navigator;
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        request.execute(navigator.as_mut())
+    }))
+    .unwrap_or_else(|error| Err(panic_message(error)))
+    .map_err(|error| error.replace('\n', " "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("navigator-{name}-{}.sock", std::process::id()))
+    }
+
+    /// Answers the request read from `stream` with `response`.
+    fn answer(mut stream: &UnixStream, response: &str) {
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        writeln!(stream, "{response}").unwrap();
+    }
+
+    #[test]
+    fn requests_round_trip() {
+        let requests = [
+            Request::SetLed(UserLed::Led2, true),
+            Request::GetLed(UserLed::Led3),
+            Request::SetLedToggle(UserLed::Led1),
+            Request::SetNeopixel(vec![[255, 0, 10], [1, 2, 3]]),
+            Request::SetNeopixelRgbw(vec![[255, 0, 10, 7]]),
+            Request::SetNeopixel(vec![]),
+            Request::ReadAdcAll,
+            Request::ReadAdc(AdcChannel::Ch2),
+            Request::ReadPressure,
+            Request::ReadTemperature,
+            Request::ReadMag,
+            Request::ReadAccel,
+            Request::ReadGyro,
+            Request::ReadLeak,
+            Request::SetAccelRange(AccelRange::G16),
+            Request::SetGyroRange(GyroRange::Dps250),
+            Request::SetImuDlpf(ImuDlpf::Hz5),
+            Request::SetImuSampleRate(200.0),
+            Request::ReadImuFifo(341),
+            Request::ReadImuBatch(100),
+            Request::SetPwmEnable(false),
+            Request::SetPwmFrequency(333.5),
+            Request::SetPwmPrescale(121),
+            Request::SetPwmDutyCycle(15, 0.125),
+            Request::SetPwmDutyCycles(vec![(0, 0.5), (7, 1.0)]),
+            Request::SetPwmChannelOnOff(3, 256, 4095),
+            Request::SetPwmPhaseStagger(true),
+            Request::SetPwmOutputMode(PwmOutputMode::OpenDrain),
+            Request::SetPwmInvert(true),
+            Request::GetPwmState,
+            Request::GetDetectedHardware,
+            Request::ReadAll,
+            Request::ClaimPwm,
+            Request::ReleasePwm,
+        ];
+        for request in requests {
+            let line = request.to_string();
+            let parsed: Request = line.parse().unwrap();
+            assert_eq!(format!("{parsed:?}"), format!("{request:?}"), "{line}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        for line in [
+            "",
+            "read_everything",
+            "set_led Led4 1",
+            "set_led Led1 yes",
+            "get_led",
+            "read_adc Ch4",
+            "set_pwm_prescale 300",
+            "set_pwm_duty_cycle one 0.5",
+            "set_pwm_duty_cycles 1:0.5",
+            "set_pwm_channel_on_off 1 0",
+            "set_pwm_output_mode PushPull",
+            "set_accel_range G32",
+            "set_imu_dlpf 176",
+            "read_imu_fifo",
+            "set_neopixel 1,2",
+        ] {
+            assert!(line.parse::<Request>().is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn parses_responses() {
+        assert_eq!(
+            parse_response("ok 1 -2.5 3e2\n").unwrap(),
+            [1.0, -2.5, 300.0]
+        );
+        assert!(parse_response("ok\n").unwrap().is_empty());
+        assert!(matches!(
+            parse_response("error PWM is owned by client 1\n"),
+            Err(DaemonError::Rejected(message)) if message == "PWM is owned by client 1"
+        ));
+        for line in ["ok 1 x\n", "\n", "done 1\n"] {
+            assert!(matches!(
+                parse_response(line),
+                Err(DaemonError::InvalidResponse(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn pwm_is_driven_by_the_claiming_client() {
+        let mut owner = None;
+        let set_pwm = Request::SetPwmDutyCycle(0, 0.5);
+        let error = arbitrate_pwm(&mut owner, 1, &set_pwm).unwrap_err();
+        assert!(error.contains("claim_pwm"), "{error}");
+        // Other requests don't need the PWM
+        assert!(arbitrate_pwm(&mut owner, 1, &Request::GetPwmState).is_ok());
+
+        assert!(arbitrate_pwm(&mut owner, 1, &Request::ClaimPwm).is_ok());
+        assert!(arbitrate_pwm(&mut owner, 1, &Request::ClaimPwm).is_ok());
+        assert!(arbitrate_pwm(&mut owner, 1, &set_pwm).is_ok());
+        for request in [&Request::ClaimPwm, &Request::ReleasePwm, &set_pwm] {
+            assert_eq!(
+                arbitrate_pwm(&mut owner, 2, request),
+                Err("PWM is owned by client 1".to_string())
+            );
+        }
+        assert_eq!(owner, Some(1));
+
+        assert!(arbitrate_pwm(&mut owner, 1, &Request::ReleasePwm).is_ok());
+        assert_eq!(owner, None);
+        assert!(arbitrate_pwm(&mut owner, 1, &set_pwm).is_err());
+        assert!(arbitrate_pwm(&mut owner, 2, &Request::ClaimPwm).is_ok());
+        assert_eq!(owner, Some(2));
+    }
+
+    #[test]
+    fn reconnects_after_the_daemon_restarts() {
+        let path = socket_path("restart");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let (restarted, wait_restart) = mpsc::channel();
+        let daemon = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            answer(&stream, "ok 21.5");
+            drop(stream);
+            restarted.send(()).unwrap();
+            let (stream, _) = listener.accept().unwrap();
+            answer(&stream, "ok 22");
+        });
+
+        let mut client = DaemonClient::connect(&path).unwrap();
+        assert_eq!(client.request(&Request::ReadTemperature).unwrap(), [21.5]);
+        wait_restart.recv().unwrap();
+        assert_eq!(client.request(&Request::ReadTemperature).unwrap(), [22.0]);
+        daemon.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn lost_responses_are_reported() {
+        let path = socket_path("lost");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let daemon = std::thread::spawn(move || {
+            // Closes the connection without answering
+            let (stream, _) = listener.accept().unwrap();
+            BufReader::new(&stream)
+                .read_line(&mut String::new())
+                .unwrap();
+            drop(stream);
+            let (stream, _) = listener.accept().unwrap();
+            answer(&stream, "error Failed to read");
+            answer(&stream, "ok 3");
+        });
+
+        let mut client = DaemonClient::connect(&path).unwrap();
+        assert!(matches!(
+            client.request(&Request::SetLedToggle(UserLed::Led1)),
+            Err(DaemonError::Io(_))
+        ));
+        assert!(matches!(
+            client.request(&Request::ReadPressure),
+            Err(DaemonError::Rejected(_))
+        ));
+        // A rejected request keeps the connection
+        assert_eq!(client.request(&Request::ReadPressure).unwrap(), [3.0]);
+        daemon.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn imu_batches_are_a_single_request() {
+        let path = socket_path("batch");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let daemon = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            assert_eq!(line.trim(), "read_imu_batch 2");
+            writeln!(stream, "ok 1 2 3 4 5 6 7 8 9 11 12 13 14 15 16 17 18 19").unwrap();
+            // Nothing else is requested for the batch
+            line.clear();
+            assert_eq!(reader.read_line(&mut line).unwrap(), 0);
+        });
+
+        let mut client = DaemonClient::connect(&path).unwrap();
+        let samples = client.read_imu_batch(2);
+        drop(client);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0][1].y, 5.0);
+        assert_eq!(samples[1][2].z, 19.0);
+        daemon.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn detection_comes_from_the_daemon() {
+        let path = socket_path("detection");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let daemon = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            assert_eq!(line.trim(), "get_detected_hardware");
+            writeln!(stream, "ok 1 0 1 0").unwrap();
+        });
+
+        let mut client = DaemonClient::connect(&path).unwrap();
+        let hardware = client.detected_hardware();
+        assert!(matches!(hardware.raspberry_pi_version, Raspberry::Pi5));
+        assert!(matches!(
+            hardware.navigator_version,
+            NavigatorVersion::Version1
+        ));
+        assert!(hardware.raspberry_pi_detected);
+        assert!(!hardware.navigator_detected);
+        daemon.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(not(feature = "python"))]
+    #[test]
+    fn failed_requests_are_c_errors() {
+        let path = socket_path("c-error");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let daemon = std::thread::spawn(move || {
+            // Closes the connection without answering
+            let (stream, _) = listener.accept().unwrap();
+            BufReader::new(&stream)
+                .read_line(&mut String::new())
+                .unwrap();
+        });
+
+        let socket = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        crate::set_backend(crate::Backend::Daemon, socket.as_ptr());
+        assert!(crate::read_temp().is_nan());
+        let error = unsafe { std::ffi::CStr::from_ptr(crate::navigator_last_error()) };
+        assert!(error
+            .to_string_lossy()
+            .starts_with("Navigator daemon is unreachable"));
+        crate::deinit();
+        daemon.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unreachable_daemon_is_an_error() {
+        let path = socket_path("missing");
+        assert!(DaemonClient::connect(path).is_err());
+    }
+}
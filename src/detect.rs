@@ -0,0 +1,207 @@
+//! Detection of the Raspberry Pi model and Navigator board version, used by the `Auto` versions.
+//!
+//! Paths are relative to a root directory, `/` unless `NAVIGATOR_DETECTION_ROOT` is set, so the
+//! detection can run against a copy of the device tree and sysfs.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use crate::{NavigatorVersion, Raspberry};
+
+/// Environment variable replacing `/` as the root of the detection paths.
+pub const ROOT_ENV: &str = "NAVIGATOR_DETECTION_ROOT";
+
+// Board model written by the firmware, e.g. "Raspberry Pi 5 Model B Rev 1.0"
+const MODEL_PATHS: [&str; 2] = [
+    "proc/device-tree/model",
+    "sys/firmware/devicetree/base/model",
+];
+
+// Both board versions have a barometer at this address, a BMP280 on V1 and a BMP390 on V2
+const BAROMETER_BUS: &str = "dev/i2c-1";
+const BAROMETER_SYSFS_DEVICE: &str = "sys/bus/i2c/devices/1-0076";
+const BAROMETER_ADDRESS: u8 = 0x76;
+
+// Chip ID (register, value) of each barometer
+const BMP280_CHIP_ID: (u8, u8) = (0xD0, 0x58);
+const BMP390_CHIP_ID: (u8, u8) = (0x00, 0x60);
+
+// From linux/i2c-dev.h
+const I2C_SLAVE: u32 = 0x0703;
+
+/// Hardware found by [`detect`], `None` when it couldn't be identified.
+#[derive(Clone, Debug, Default)]
+pub struct Detection {
+    pub raspberry_pi_version: Option<Raspberry>,
+    pub navigator_version: Option<NavigatorVersion>,
+}
+
+/// Root directory of the detection paths, from `NAVIGATOR_DETECTION_ROOT`.
+pub fn root() -> PathBuf {
+    std::env::var_os(ROOT_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/"))
+}
+
+/// Detects the Raspberry Pi model from the device tree, and the Navigator version from its
+/// barometer, as named by sysfs when a kernel driver is bound to it or by its chip ID otherwise.
+pub fn detect(root: &Path) -> Detection {
+    let model = MODEL_PATHS
+        .iter()
+        .find_map(|path| std::fs::read(root.join(path)).ok())
+        .map(|model| {
+            String::from_utf8_lossy(&model)
+                .trim_end_matches('\0')
+                .trim()
+                .to_string()
+        });
+
+    Detection {
+        raspberry_pi_version: model.as_deref().and_then(raspberry_from_model),
+        navigator_version: detect_navigator(root),
+    }
+}
+
+fn raspberry_from_model(model: &str) -> Option<Raspberry> {
+    // Also matches the Raspberry Pi 400/500 and the Compute Modules
+    if model.contains("Raspberry Pi 5") || model.contains("Compute Module 5") {
+        Some(Raspberry::Pi5)
+    } else if model.contains("Raspberry Pi 4") || model.contains("Compute Module 4") {
+        Some(Raspberry::Pi4)
+    } else {
+        None
+    }
+}
+
+fn detect_navigator(root: &Path) -> Option<NavigatorVersion> {
+    // A bound driver keeps the address busy, its name is the only way to identify the chip
+    if let Ok(name) = std::fs::read_to_string(root.join(BAROMETER_SYSFS_DEVICE).join("name")) {
+        return match name.trim() {
+            "bmp280" => Some(NavigatorVersion::Version1),
+            name if name.starts_with("bmp3") => Some(NavigatorVersion::Version2),
+            _ => None,
+        };
+    }
+
+    let bus = root.join(BAROMETER_BUS);
+    let (register, chip_id) = BMP390_CHIP_ID;
+    if read_register(&bus, BAROMETER_ADDRESS, register).ok() == Some(chip_id) {
+        return Some(NavigatorVersion::Version2);
+    }
+    let (register, chip_id) = BMP280_CHIP_ID;
+    if read_register(&bus, BAROMETER_ADDRESS, register).ok() == Some(chip_id) {
+        return Some(NavigatorVersion::Version1);
+    }
+    None
+}
+
+/// Opens the I2C device at `address` on `bus`, reads and writes are transfers with the device.
+pub(crate) fn open_i2c_device(bus: &Path, address: u8) -> std::io::Result<File> {
+    let device = OpenOptions::new().read(true).write(true).open(bus)?;
+    let result =
+        unsafe { libc::ioctl(device.as_raw_fd(), I2C_SLAVE as _, address as libc::c_ulong) };
+    if result < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(device)
+}
+
+fn read_register(bus: &Path, address: u8, register: u8) -> std::io::Result<u8> {
+    let mut device = open_i2c_device(bus, address)?;
+    device.write_all(&[register])?;
+    let mut value = [0];
+    device.read_exact(&mut value)?;
+    Ok(value[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty root directory for a test, with the given files.
+    fn fake_root(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("navigator-detect-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    #[test]
+    fn detects_raspberry_pi_model() {
+        let cases: [(&[u8], Option<Raspberry>); 5] = [
+            (b"Raspberry Pi 5 Model B Rev 1.0\0", Some(Raspberry::Pi5)),
+            (b"Raspberry Pi 4 Model B Rev 1.4\0", Some(Raspberry::Pi4)),
+            (
+                b"Raspberry Pi Compute Module 4 Rev 1.0\0",
+                Some(Raspberry::Pi4),
+            ),
+            (b"Raspberry Pi 3 Model B Plus Rev 1.3\0", None),
+            (b"Generic x86 board", None),
+        ];
+        for (index, (model, expected)) in cases.into_iter().enumerate() {
+            let root = fake_root(
+                &format!("model-{index}"),
+                &[("proc/device-tree/model", model)],
+            );
+            let detection = detect(&root);
+            assert_eq!(
+                format!("{:?}", detection.raspberry_pi_version),
+                format!("{expected:?}"),
+                "model {model:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reads_model_from_sysfs_device_tree() {
+        let root = fake_root(
+            "sysfs-model",
+            &[(
+                "sys/firmware/devicetree/base/model",
+                b"Raspberry Pi 5 Model B Rev 1.0\0",
+            )],
+        );
+        assert!(matches!(
+            detect(&root).raspberry_pi_version,
+            Some(Raspberry::Pi5)
+        ));
+    }
+
+    #[test]
+    fn detects_navigator_from_bound_barometer_driver() {
+        let root = fake_root(
+            "bmp280",
+            &[("sys/bus/i2c/devices/1-0076/name", b"bmp280\n")],
+        );
+        assert!(matches!(
+            detect(&root).navigator_version,
+            Some(NavigatorVersion::Version1)
+        ));
+
+        let root = fake_root(
+            "bmp390",
+            &[("sys/bus/i2c/devices/1-0076/name", b"bmp380\n")],
+        );
+        assert!(matches!(
+            detect(&root).navigator_version,
+            Some(NavigatorVersion::Version2)
+        ));
+    }
+
+    #[test]
+    fn reports_unknown_hardware() {
+        // A regular file can't be used as an I2C bus
+        let root = fake_root("unknown", &[("dev/i2c-1", b"")]);
+        let detection = detect(&root);
+        assert!(detection.raspberry_pi_version.is_none());
+        assert!(detection.navigator_version.is_none());
+    }
+}
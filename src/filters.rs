@@ -0,0 +1,522 @@
+//! Digital filters for sensor readings.
+//!
+//! Every filter processes one sample at a time, so the same implementation is used for the
+//! per-channel filters applied by the `read_*_filtered` functions and for filtering arrays.
+
+use std::collections::{HashMap, VecDeque};
+use std::f32::consts::PI;
+use std::time::Instant;
+
+/// Default derivative cutoff of the 1-euro filter, as recommended by its authors.
+const ONE_EURO_DERIVATIVE_CUTOFF_HZ: f32 = 1.0;
+
+/// Smoothing factor of a first order low-pass filter with `cutoff` [Hz], for samples `dt` apart.
+fn smoothing(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+pub trait Filter: Send {
+    /// Filters `value`, sampled `dt` seconds after the previous one.
+    fn apply(&mut self, value: f32, dt: f32) -> f32;
+}
+
+/// Filter configuration, used to create independent filters for each value of a channel.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterSpec {
+    LowPass {
+        sample_rate: f32,
+        cutoff: f32,
+    },
+    Notch {
+        sample_rate: f32,
+        center: f32,
+        bandwidth: f32,
+    },
+    OneEuro {
+        min_cutoff: f32,
+        beta: f32,
+    },
+    MovingAverage {
+        window: usize,
+    },
+    Median {
+        window: usize,
+    },
+}
+
+impl FilterSpec {
+    /// Checks the parameters, which `build` expects to be valid.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            FilterSpec::LowPass {
+                sample_rate,
+                cutoff,
+            } => {
+                validate_sample_rate(sample_rate)?;
+                if !(cutoff > 0.0 && cutoff < sample_rate / 2.0) {
+                    return Err(format!(
+                        "Low-pass cutoff must be between 0 and half the sample rate, found {cutoff} Hz."
+                    ));
+                }
+            }
+            FilterSpec::Notch {
+                sample_rate,
+                center,
+                bandwidth,
+            } => {
+                validate_sample_rate(sample_rate)?;
+                if !(center > 0.0 && center < sample_rate / 2.0) {
+                    return Err(format!(
+                        "Notch center must be between 0 and half the sample rate, found {center} Hz."
+                    ));
+                }
+                if !(bandwidth > 0.0 && bandwidth.is_finite()) {
+                    return Err(format!(
+                        "Notch bandwidth must be positive, found {bandwidth} Hz."
+                    ));
+                }
+            }
+            FilterSpec::OneEuro { min_cutoff, beta } => {
+                if !(min_cutoff > 0.0 && min_cutoff.is_finite()) {
+                    return Err(format!(
+                        "1-euro minimum cutoff must be positive, found {min_cutoff} Hz."
+                    ));
+                }
+                if !(beta >= 0.0 && beta.is_finite()) {
+                    return Err(format!("1-euro beta must not be negative, found {beta}."));
+                }
+            }
+            FilterSpec::MovingAverage { window } | FilterSpec::Median { window } => {
+                if window == 0 {
+                    return Err("Filter window must not be empty.".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn build(&self) -> Box<dyn Filter> {
+        match *self {
+            FilterSpec::LowPass {
+                sample_rate,
+                cutoff,
+            } => Box::new(Biquad::low_pass(sample_rate, cutoff)),
+            FilterSpec::Notch {
+                sample_rate,
+                center,
+                bandwidth,
+            } => Box::new(Biquad::notch(sample_rate, center, bandwidth)),
+            FilterSpec::OneEuro { min_cutoff, beta } => Box::new(OneEuro::new(min_cutoff, beta)),
+            FilterSpec::MovingAverage { window } => Box::new(MovingAverage::new(window)),
+            FilterSpec::Median { window } => Box::new(Median::new(window)),
+        }
+    }
+
+    /// Filters `values` in place, assuming they are evenly spaced at `sample_rate` [Hz].
+    pub fn apply_slice(&self, values: &mut [f32], sample_rate: f32) -> Result<(), String> {
+        validate_sample_rate(sample_rate)?;
+        self.validate()?;
+        let mut filter = self.build();
+        let dt = 1.0 / sample_rate;
+        for value in values.iter_mut() {
+            *value = filter.apply(*value, dt);
+        }
+        Ok(())
+    }
+}
+
+fn validate_sample_rate(sample_rate: f32) -> Result<(), String> {
+    match sample_rate > 0.0 && sample_rate.is_finite() {
+        true => Ok(()),
+        false => Err(format!(
+            "Sample rate must be positive, found {sample_rate} Hz."
+        )),
+    }
+}
+
+struct ValueFilter {
+    filter: Box<dyn Filter>,
+    last_sample: Option<Instant>,
+}
+
+/// Filters attached to the sensor channels of an instance, keyed by channel index. Each value of
+/// a channel, e.g. an axis or an ADC channel, is filtered independently.
+#[derive(Default)]
+pub struct ChannelFilters {
+    channels: HashMap<usize, (FilterSpec, Vec<ValueFilter>)>,
+}
+
+impl ChannelFilters {
+    /// Attaches `spec` to the channel, an invalid `spec` leaves the channel unchanged.
+    pub fn set(&mut self, channel: usize, spec: FilterSpec) -> Result<(), String> {
+        spec.validate()?;
+        self.channels.insert(channel, (spec, Vec::new()));
+        Ok(())
+    }
+
+    /// Removes the filter of the channel.
+    pub fn clear(&mut self, channel: usize) {
+        self.channels.remove(&channel);
+    }
+
+    /// Attaches the filters of `specs` to their channels, keeping the state of the unchanged ones.
+    /// The specs are validated when added to the configuration.
+    pub fn configure(&mut self, specs: &HashMap<usize, FilterSpec>) {
+        self.channels
+            .retain(|channel, (spec, _)| specs.get(channel) == Some(spec));
+        for (&channel, spec) in specs {
+            self.channels
+                .entry(channel)
+                .or_insert_with(|| (spec.clone(), Vec::new()));
+        }
+    }
+
+    /// Filters `values` read at `now`, starting at the `first` value of the channel, e.g. the ADC
+    /// channel index.
+    pub fn apply(&mut self, channel: usize, first: usize, values: &mut [f32], now: Instant) {
+        let Some((spec, value_filters)) = self.channels.get_mut(&channel) else {
+            return;
+        };
+        if value_filters.len() < first + values.len() {
+            value_filters.resize_with(first + values.len(), || ValueFilter {
+                filter: spec.build(),
+                last_sample: None,
+            });
+        }
+
+        for (value, value_filter) in values.iter_mut().zip(&mut value_filters[first..]) {
+            let dt = value_filter
+                .last_sample
+                .map_or(0.0, |last_sample| (now - last_sample).as_secs_f32());
+            value_filter.last_sample = Some(now);
+            *value = value_filter.filter.apply(*value, dt);
+        }
+    }
+}
+
+/// Second order IIR filter, with coefficients from the Audio EQ Cookbook.
+pub struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    x: [f32; 2],
+    y: [f32; 2],
+    initialized: bool,
+}
+
+impl Biquad {
+    fn new(b: [f32; 3], a: [f32; 3]) -> Self {
+        Self {
+            b: b.map(|b| b / a[0]),
+            a: [a[1] / a[0], a[2] / a[0]],
+            x: [0.0; 2],
+            y: [0.0; 2],
+            initialized: false,
+        }
+    }
+
+    /// Butterworth low-pass filter, see `FilterSpec::validate` for the valid parameters.
+    pub fn low_pass(sample_rate: f32, cutoff: f32) -> Self {
+        let w0 = 2.0 * PI * cutoff / sample_rate;
+        let alpha = w0.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = w0.cos();
+        Self::new(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// Notch filter rejecting `center` [Hz], `bandwidth` [Hz] wide.
+    pub fn notch(sample_rate: f32, center: f32, bandwidth: f32) -> Self {
+        let w0 = 2.0 * PI * center / sample_rate;
+        let alpha = w0.sin() * bandwidth / (2.0 * center);
+        let cos = w0.cos();
+        Self::new(
+            [1.0, -2.0 * cos, 1.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+}
+
+impl Filter for Biquad {
+    fn apply(&mut self, value: f32, _dt: f32) -> f32 {
+        // Both filters have unity DC gain, starting from the first value avoids the step response
+        if !self.initialized {
+            self.x = [value; 2];
+            self.y = [value; 2];
+            self.initialized = true;
+        }
+        let output = self.b[0] * value + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [value, self.x[0]];
+        self.y = [output, self.y[0]];
+        output
+    }
+}
+
+/// Adaptive low-pass filter, smooth at low speeds and responsive at high speeds.
+///
+/// See <https://gery.casiez.net/1euro/>.
+pub struct OneEuro {
+    min_cutoff: f32,
+    beta: f32,
+    previous: Option<(f32, f32)>,
+}
+
+impl OneEuro {
+    pub fn new(min_cutoff: f32, beta: f32) -> Self {
+        Self {
+            min_cutoff,
+            beta,
+            previous: None,
+        }
+    }
+}
+
+impl Filter for OneEuro {
+    fn apply(&mut self, value: f32, dt: f32) -> f32 {
+        let Some((previous, previous_derivative)) = self.previous else {
+            self.previous = Some((value, 0.0));
+            return value;
+        };
+        if dt <= 0.0 {
+            return previous;
+        }
+
+        let alpha = smoothing(ONE_EURO_DERIVATIVE_CUTOFF_HZ, dt);
+        let derivative =
+            previous_derivative + alpha * ((value - previous) / dt - previous_derivative);
+        let cutoff = self.min_cutoff + self.beta * derivative.abs();
+        let output = previous + smoothing(cutoff, dt) * (value - previous);
+        self.previous = Some((output, derivative));
+        output
+    }
+}
+
+/// First order low-pass filter, following the time between samples, `cutoff` must be positive.
+pub struct FirstOrderLowPass {
+    cutoff: f32,
+    value: Option<f32>,
+}
+
+impl FirstOrderLowPass {
+    pub fn new(cutoff: f32) -> Self {
+        Self {
+            cutoff,
+            value: None,
+        }
+    }
+
+    /// The last filtered value.
+    pub fn value(&self) -> f32 {
+        self.value.unwrap_or_default()
+    }
+}
+
+impl Filter for FirstOrderLowPass {
+    fn apply(&mut self, value: f32, dt: f32) -> f32 {
+        let output = match self.value {
+            Some(previous) if dt > 0.0 => {
+                previous + smoothing(self.cutoff, dt) * (value - previous)
+            }
+            Some(previous) => previous,
+            None => value,
+        };
+        self.value = Some(output);
+        output
+    }
+}
+
+pub struct MovingAverage {
+    window: usize,
+    values: VecDeque<f32>,
+}
+
+impl MovingAverage {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+        }
+    }
+}
+
+impl Filter for MovingAverage {
+    fn apply(&mut self, value: f32, _dt: f32) -> f32 {
+        if self.values.len() == self.window {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+        self.values.iter().sum::<f32>() / self.values.len() as f32
+    }
+}
+
+/// Moving median, removes outliers without smoothing edges.
+pub struct Median {
+    window: usize,
+    values: VecDeque<f32>,
+}
+
+impl Median {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            values: VecDeque::with_capacity(window),
+        }
+    }
+}
+
+impl Filter for Median {
+    fn apply(&mut self, value: f32, _dt: f32) -> f32 {
+        if self.values.len() == self.window {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+
+        let mut sorted: Vec<f32> = self.values.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let middle = sorted.len() / 2;
+        if sorted.len() % 2 == 0 {
+            (sorted[middle - 1] + sorted[middle]) / 2.0
+        } else {
+            sorted[middle]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output of `spec` for a unit step at the first of `samples`, sampled at `sample_rate`.
+    fn step_response(spec: &FilterSpec, sample_rate: f32, samples: usize) -> Vec<f32> {
+        let mut values = vec![0.0; samples];
+        values[1..].fill(1.0);
+        spec.apply_slice(&mut values, sample_rate).unwrap();
+        values
+    }
+
+    #[test]
+    fn low_pass_step_settles_with_butterworth_overshoot() {
+        let spec = FilterSpec::LowPass {
+            sample_rate: 1000.0,
+            cutoff: 10.0,
+        };
+        let response = step_response(&spec, 1000.0, 1000);
+        assert_eq!(response[0], 0.0);
+        // Still rising after a fraction of the cutoff period
+        assert!((0.05..0.5).contains(&response[20]), "{}", response[20]);
+        // A second order Butterworth overshoots by about 4%
+        let peak = response.iter().copied().fold(0.0, f32::max);
+        assert!((1.02..1.06).contains(&peak), "peak {peak}");
+        assert!((response[999] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn notch_passes_steps_and_rejects_its_center() {
+        let spec = FilterSpec::Notch {
+            sample_rate: 1000.0,
+            center: 50.0,
+            bandwidth: 10.0,
+        };
+        let response = step_response(&spec, 1000.0, 1000);
+        assert!((response[999] - 1.0).abs() < 1e-3);
+
+        let mut sine: Vec<f32> = (0..2000)
+            .map(|index| (2.0 * PI * 50.0 * index as f32 / 1000.0).sin())
+            .collect();
+        spec.apply_slice(&mut sine, 1000.0).unwrap();
+        let amplitude = sine[1000..]
+            .iter()
+            .fold(0.0, |max: f32, value| max.max(value.abs()));
+        assert!(amplitude < 0.05, "amplitude {amplitude}");
+    }
+
+    #[test]
+    fn one_euro_step_follows_its_cutoff() {
+        // Without beta the filter is a first order low-pass, at 63% after its time constant
+        let slow = FilterSpec::OneEuro {
+            min_cutoff: 1.0,
+            beta: 0.0,
+        };
+        let samples = (1000.0 / (2.0 * PI)) as usize + 1;
+        let response = step_response(&slow, 1000.0, samples + 1);
+        assert!(
+            (0.62..0.64).contains(&response[samples]),
+            "{}",
+            response[samples]
+        );
+
+        // Fast changes raise the cutoff
+        let fast = FilterSpec::OneEuro {
+            min_cutoff: 1.0,
+            beta: 0.1,
+        };
+        let fast_response = step_response(&fast, 1000.0, samples + 1);
+        assert!(fast_response[samples] > response[samples] + 0.1);
+    }
+
+    #[test]
+    fn moving_average_step_settles_after_its_window() {
+        let spec = FilterSpec::MovingAverage { window: 4 };
+        let response = step_response(&spec, 1.0, 6);
+        assert_eq!(response, [0.0, 0.5, 2.0 / 3.0, 0.75, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn median_keeps_steps_and_removes_outliers() {
+        let spec = FilterSpec::Median { window: 3 };
+        assert_eq!(step_response(&spec, 1.0, 4), [0.0, 0.5, 1.0, 1.0]);
+
+        let mut values = [1.0, 1.0, 9.0, 1.0, 1.0];
+        spec.apply_slice(&mut values, 1.0).unwrap();
+        assert_eq!(values, [1.0; 5]);
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        for spec in [
+            FilterSpec::LowPass {
+                sample_rate: 100.0,
+                cutoff: 50.0,
+            },
+            FilterSpec::LowPass {
+                sample_rate: f32::NAN,
+                cutoff: 10.0,
+            },
+            FilterSpec::Notch {
+                sample_rate: 100.0,
+                center: 10.0,
+                bandwidth: 0.0,
+            },
+            FilterSpec::OneEuro {
+                min_cutoff: 1.0,
+                beta: -1.0,
+            },
+            FilterSpec::MovingAverage { window: 0 },
+            FilterSpec::Median { window: 0 },
+        ] {
+            assert!(spec.validate().is_err(), "{spec:?}");
+        }
+
+        let mut values = [1.0, 2.0];
+        let spec = FilterSpec::Median { window: 3 };
+        assert!(spec.apply_slice(&mut values, 0.0).is_err());
+        assert_eq!(values, [1.0, 2.0]);
+    }
+
+    #[test]
+    fn invalid_filters_keep_the_previous_one() {
+        let mut filters = ChannelFilters::default();
+        filters.set(0, FilterSpec::Median { window: 3 }).unwrap();
+        assert!(filters.set(0, FilterSpec::Median { window: 0 }).is_err());
+
+        let now = Instant::now();
+        for value in [1.0, 1.0, 9.0] {
+            let mut values = [value];
+            filters.apply(0, 0, &mut values, now);
+            assert_eq!(values, [1.0]);
+        }
+    }
+}
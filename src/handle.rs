@@ -0,0 +1,2003 @@
+//! Navigator objects, for applications managing their own instance instead of the default one
+//! used by the free functions.
+//!
+//! Each handle owns its backend, so it is released when the handle is closed or dropped. Only one
+//! `Backend::Local` instance, including the default one, can access the hardware at a time, while
+//! `Backend::Daemon` handles can be created as needed. The default instance is a handle as well,
+//! so both offer the same health monitoring, vibration metrics, filters and PWM ramps.
+
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::{Duration, Instant};
+
+use crate::backend::{NavigatorBackend, PWM_CHANNELS};
+use crate::channels::ChannelNames;
+use crate::filters::{ChannelFilters, FilterSpec};
+use crate::health::{HealthMonitor, Quantity, Sample};
+use crate::pca9685::{Counters, COUNTER_MAX};
+use crate::ramp::Ramps;
+use crate::vibration::VibrationMonitor;
+use crate::{
+    config, orientation, panic_message, pwm_channels, timestamp_us, AccelRange, AdcChannel,
+    AxisData, Backend, CheckedAxisData, CheckedValue, FilterChannel, GyroRange, ImuDlpf, ImuSample,
+    NavigatorBuilderManager, NavigatorVersion, PwmOutputMode, PwmState, Raspberry, Rotation,
+    Sensor, SensorData, SensorHealth, UserLed, VibrationData,
+};
+
+/// Navigator configuration, with the same options as the `set_*` functions used before `init`.
+/// Starts from the file named by `NAVIGATOR_CONFIG`, if any.
+#[cfg_attr(feature = "python", pyo3::pyclass)]
+#[derive(Clone)]
+pub struct NavigatorBuilder {
+    configuration: NavigatorBuilderManager,
+}
+
+impl Default for NavigatorBuilder {
+    fn default() -> Self {
+        Self {
+            configuration: NavigatorBuilderManager::from_env(),
+        }
+    }
+}
+
+impl NavigatorBuilder {
+    /// Creates an instance, fails if the hardware or the daemon can't be accessed.
+    pub fn build(&self) -> Result<NavigatorHandle, String> {
+        let handle = NavigatorHandle::closed();
+        handle.open(&self.configuration)?;
+        Ok(handle)
+    }
+
+    /// Applies the configuration file at `path`.
+    pub fn load_config(&mut self, path: &std::path::Path) -> Result<(), config::ConfigError> {
+        let config = config::load(path)?;
+        self.configuration.apply_config(&config);
+        Ok(())
+    }
+}
+
+// Slot of a handle's instance, None while closed
+type Slot = Mutex<Option<Instance>>;
+
+pub(crate) struct Instance {
+    pub(crate) navigator: Box<dyn NavigatorBackend>,
+    orientation: orientation::Matrix,
+    pwm_limits: [config::PwmLimits; PWM_CHANNELS],
+    pwm_phase_stagger: bool,
+    channel_names: ChannelNames,
+    filters: ChannelFilters,
+    vibration: VibrationMonitor,
+    health: HealthMonitor,
+    ramps: Ramps,
+    // Wakes the thread stepping the ramps, started by the first moving channel
+    ramp_thread: Option<Sender<()>>,
+    slot: Weak<Slot>,
+}
+
+impl Instance {
+    fn new(configuration: &NavigatorBuilderManager, slot: Weak<Slot>) -> Result<Self, String> {
+        let mut instance = Self {
+            navigator: configuration.build()?,
+            orientation: configuration.orientation,
+            pwm_limits: configuration.pwm_limits,
+            pwm_phase_stagger: false,
+            channel_names: configuration.channel_names.clone(),
+            filters: ChannelFilters::default(),
+            vibration: VibrationMonitor::new(),
+            health: HealthMonitor::new(),
+            ramps: Ramps::default(),
+            ramp_thread: None,
+            slot,
+        };
+        instance.reconfigure(configuration);
+        Ok(instance)
+    }
+
+    /// Applies the options of `configuration` that can change while running: the orientation, PWM
+    /// limits, slew rates and phase staggering, channel names and filters.
+    pub(crate) fn reconfigure(&mut self, configuration: &NavigatorBuilderManager) {
+        self.orientation = configuration.orientation;
+        self.pwm_limits = configuration.pwm_limits;
+        // Only sent when changed, since the daemon rejects it unless this client claimed the PWM
+        if self.pwm_phase_stagger != configuration.pwm_phase_stagger {
+            self.set_pwm_phase_stagger(configuration.pwm_phase_stagger);
+        }
+        self.channel_names = configuration.channel_names.clone();
+        self.filters.configure(&configuration.filters);
+        for (channel, &rate) in configuration.pwm_slew_rates.iter().enumerate() {
+            self.ramps.set_slew_rate(channel, rate);
+        }
+    }
+
+    /// Reads `quantity`, recording the outcome in the health of its sensor. Returns the value and
+    /// whether it's valid, or the panic of a failed read.
+    fn try_monitored<T, F>(&mut self, quantity: Quantity, read: F) -> std::thread::Result<(T, bool)>
+    where
+        T: Sample,
+        F: FnOnce(&mut dyn NavigatorBackend) -> T,
+    {
+        let navigator = &mut *self.navigator;
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| read(navigator))) {
+            Ok(value) => {
+                let values = value.values();
+                let sample = [(quantity, values.as_slice())];
+                let valid = self.health.record_sample(
+                    quantity.sensor(),
+                    &sample,
+                    std::time::Instant::now(),
+                );
+                Ok((value, valid))
+            }
+            Err(panic) => {
+                self.health.record_error(quantity.sensor());
+                Err(panic)
+            }
+        }
+    }
+
+    /// Same as [`Instance::try_monitored`], a failed read still panics.
+    fn monitored<T, F>(&mut self, quantity: Quantity, read: F) -> T
+    where
+        T: Sample,
+        F: FnOnce(&mut dyn NavigatorBackend) -> T,
+    {
+        match self.try_monitored(quantity, read) {
+            Ok((value, _)) => value,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    /// Updates the vibration metrics with a raw accelerometer sample, before the board rotation,
+    /// so clipping is counted against the sensor axes.
+    fn update_vibration(&mut self, accel: &navigator_rs::AxisData, now: std::time::Instant) {
+        self.vibration.update([accel.x, accel.y, accel.z], now);
+    }
+
+    fn rotate(&self, axis: navigator_rs::AxisData) -> AxisData {
+        orientation::rotate(&self.orientation, axis).into()
+    }
+
+    pub(crate) fn read_adc_all(&mut self) -> Vec<f32> {
+        self.monitored(Quantity::Voltage, |navigator| navigator.read_adc_all())
+    }
+
+    pub(crate) fn read_adc(&mut self, channel: AdcChannel) -> f32 {
+        self.monitored(Quantity::Voltage, |navigator| {
+            navigator.read_adc(channel.into())
+        })
+    }
+
+    pub(crate) fn read_pressure(&mut self) -> f32 {
+        self.monitored(Quantity::Pressure, |navigator| navigator.read_pressure())
+    }
+
+    pub(crate) fn read_temp(&mut self) -> f32 {
+        self.monitored(Quantity::Temperature, |navigator| {
+            navigator.read_temperature()
+        })
+    }
+
+    pub(crate) fn read_mag(&mut self) -> AxisData {
+        let mag = self.monitored(Quantity::MagneticField, |navigator| navigator.read_mag());
+        self.rotate(mag)
+    }
+
+    pub(crate) fn read_accel(&mut self) -> AxisData {
+        let accel = self.monitored(Quantity::Acceleration, |navigator| navigator.read_accel());
+        self.update_vibration(&accel, std::time::Instant::now());
+        self.rotate(accel)
+    }
+
+    pub(crate) fn read_gyro(&mut self) -> AxisData {
+        let gyro = self.monitored(Quantity::AngularVelocity, |navigator| navigator.read_gyro());
+        self.rotate(gyro)
+    }
+
+    /// Reads all sensors at once, recording the outcome in the health of each one.
+    pub(crate) fn read_all(&mut self) -> SensorData {
+        let timestamp = timestamp_us();
+        let navigator = &mut *self.navigator;
+        let data =
+            match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| navigator.read_all())) {
+                Ok(data) => data,
+                Err(panic) => {
+                    for sensor in crate::health::SENSORS {
+                        self.health.record_error(sensor);
+                    }
+                    std::panic::resume_unwind(panic)
+                }
+            };
+
+        let now = std::time::Instant::now();
+        let pressure = [data.pressure];
+        let temperature = [data.temperature];
+        let barometer = [
+            (Quantity::Pressure, pressure.as_slice()),
+            (Quantity::Temperature, temperature.as_slice()),
+        ];
+        self.health
+            .record_sample(Sensor::Barometer, &barometer, now);
+        let magnetometer = data.magnetometer.values();
+        self.health.record_sample(
+            Sensor::Magnetometer,
+            &[(Quantity::MagneticField, magnetometer.as_slice())],
+            now,
+        );
+        let (accelerometer, gyro) = (data.accelerometer.values(), data.gyro.values());
+        let imu = [
+            (Quantity::Acceleration, accelerometer.as_slice()),
+            (Quantity::AngularVelocity, gyro.as_slice()),
+        ];
+        self.health.record_sample(Sensor::Imu, &imu, now);
+        self.health.record_sample(
+            Sensor::Adc,
+            &[(Quantity::Voltage, data.adc.as_slice())],
+            now,
+        );
+
+        self.update_vibration(&data.accelerometer, now);
+        SensorData::new(data, &self.orientation, timestamp)
+    }
+
+    /// Reads `n` accelerometer, gyroscope and magnetometer samples in a single backend call, as
+    /// rows of 9 values. The samples are recorded as spread over the time the batch took.
+    #[cfg(feature = "numpy")]
+    pub(crate) fn read_imu_batch(&mut self, n: usize) -> Vec<f32> {
+        let start = std::time::Instant::now();
+        let navigator = &mut *self.navigator;
+        let samples = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            navigator.read_imu_batch(n)
+        })) {
+            Ok(samples) => samples,
+            Err(panic) => {
+                self.health.record_error(Sensor::Imu);
+                self.health.record_error(Sensor::Magnetometer);
+                std::panic::resume_unwind(panic)
+            }
+        };
+
+        let elapsed = start.elapsed();
+        let mut values = Vec::with_capacity(n * 9);
+        for (i, [accel, gyro, mag]) in samples.into_iter().enumerate() {
+            let now = start + elapsed.mul_f64((i + 1) as f64 / n as f64);
+            let (accelerometer, angular_velocity) = (accel.values(), gyro.values());
+            let imu = [
+                (Quantity::Acceleration, accelerometer.as_slice()),
+                (Quantity::AngularVelocity, angular_velocity.as_slice()),
+            ];
+            self.health.record_sample(Sensor::Imu, &imu, now);
+            let magnetometer = mag.values();
+            self.health.record_sample(
+                Sensor::Magnetometer,
+                &[(Quantity::MagneticField, magnetometer.as_slice())],
+                now,
+            );
+            self.update_vibration(&accel, now);
+
+            for axis in [accel, gyro, mag] {
+                let axis = self.rotate(axis);
+                values.extend([axis.x, axis.y, axis.z]);
+            }
+        }
+        values
+    }
+
+    /// Sets the accelerometer range, and the clipping limit of the vibration metrics with it.
+    pub(crate) fn set_accel_range(&mut self, range: AccelRange) {
+        self.vibration.set_accel_range(range.full_scale());
+        self.navigator.set_accel_range(range);
+    }
+
+    /// Reads up to `max_samples` from the IMU FIFO, rotated to the vehicle frame.
+    pub(crate) fn read_imu_fifo(&mut self, max_samples: usize) -> Result<Vec<ImuSample>, String> {
+        let samples = self.navigator.read_imu_fifo(max_samples)?;
+        Ok(samples
+            .into_iter()
+            .map(|(accel, gyro)| ImuSample {
+                accelerometer: self.rotate(accel),
+                gyro: self.rotate(gyro),
+            })
+            .collect())
+    }
+
+    pub(crate) fn read_adc_checked(&mut self, channel: AdcChannel) -> CheckedValue {
+        CheckedValue::new(self.try_monitored(Quantity::Voltage, |navigator| {
+            navigator.read_adc(channel.into())
+        }))
+    }
+
+    pub(crate) fn read_pressure_checked(&mut self) -> CheckedValue {
+        CheckedValue::new(
+            self.try_monitored(Quantity::Pressure, |navigator| navigator.read_pressure()),
+        )
+    }
+
+    pub(crate) fn read_temp_checked(&mut self) -> CheckedValue {
+        CheckedValue::new(self.try_monitored(Quantity::Temperature, |navigator| {
+            navigator.read_temperature()
+        }))
+    }
+
+    pub(crate) fn read_mag_checked(&mut self) -> CheckedAxisData {
+        let reading = self.try_monitored(Quantity::MagneticField, |navigator| navigator.read_mag());
+        CheckedAxisData::new(reading, &self.orientation)
+    }
+
+    /// Valid readings update the vibration metrics.
+    pub(crate) fn read_accel_checked(&mut self) -> CheckedAxisData {
+        let reading =
+            self.try_monitored(Quantity::Acceleration, |navigator| navigator.read_accel());
+        if let Ok((accel, true)) = &reading {
+            self.update_vibration(accel, std::time::Instant::now());
+        }
+        CheckedAxisData::new(reading, &self.orientation)
+    }
+
+    pub(crate) fn read_gyro_checked(&mut self) -> CheckedAxisData {
+        let reading =
+            self.try_monitored(Quantity::AngularVelocity, |navigator| navigator.read_gyro());
+        CheckedAxisData::new(reading, &self.orientation)
+    }
+
+    pub(crate) fn set_filter(
+        &mut self,
+        channel: FilterChannel,
+        spec: FilterSpec,
+    ) -> Result<(), String> {
+        self.filters.set(channel as usize, spec)
+    }
+
+    pub(crate) fn clear_filter(&mut self, channel: FilterChannel) {
+        self.filters.clear(channel as usize);
+    }
+
+    /// Filters `values`, starting at the `first` value of the channel, e.g. the ADC channel index.
+    fn filter(&mut self, channel: FilterChannel, first: usize, values: &mut [f32]) {
+        self.filters
+            .apply(channel as usize, first, values, Instant::now());
+    }
+
+    fn filter_value(&mut self, channel: FilterChannel, value: f32) -> f32 {
+        let mut values = [value];
+        self.filter(channel, 0, &mut values);
+        values[0]
+    }
+
+    fn filter_axis(&mut self, channel: FilterChannel, axis: AxisData) -> AxisData {
+        let mut values = [axis.x, axis.y, axis.z];
+        self.filter(channel, 0, &mut values);
+        let [x, y, z] = values;
+        AxisData { x, y, z }
+    }
+
+    pub(crate) fn read_adc_all_filtered(&mut self) -> Vec<f32> {
+        let mut values = self.read_adc_all();
+        self.filter(FilterChannel::Adc, 0, &mut values);
+        values
+    }
+
+    pub(crate) fn read_adc_filtered(&mut self, channel: AdcChannel) -> f32 {
+        let mut values = [self.read_adc(channel.clone())];
+        self.filter(FilterChannel::Adc, channel as usize, &mut values);
+        values[0]
+    }
+
+    pub(crate) fn read_pressure_filtered(&mut self) -> f32 {
+        let pressure = self.read_pressure();
+        self.filter_value(FilterChannel::Pressure, pressure)
+    }
+
+    pub(crate) fn read_temp_filtered(&mut self) -> f32 {
+        let temperature = self.read_temp();
+        self.filter_value(FilterChannel::Temperature, temperature)
+    }
+
+    pub(crate) fn read_mag_filtered(&mut self) -> AxisData {
+        let mag = self.read_mag();
+        self.filter_axis(FilterChannel::Mag, mag)
+    }
+
+    pub(crate) fn read_accel_filtered(&mut self) -> AxisData {
+        let accel = self.read_accel();
+        self.filter_axis(FilterChannel::Accel, accel)
+    }
+
+    pub(crate) fn read_gyro_filtered(&mut self) -> AxisData {
+        let gyro = self.read_gyro();
+        self.filter_axis(FilterChannel::Gyro, gyro)
+    }
+
+    pub(crate) fn read_vibration(&self) -> VibrationData {
+        VibrationData::new(&self.vibration)
+    }
+
+    pub(crate) fn reset_vibration(&mut self) {
+        self.vibration.reset();
+    }
+
+    pub(crate) fn sensor_health(&self) -> Vec<SensorHealth> {
+        SensorHealth::all(&self.health)
+    }
+
+    pub(crate) fn reset_sensor_health(&mut self) {
+        self.health = HealthMonitor::new();
+    }
+
+    /// Sets multiple channels at once, so they are updated together, with `All` expanded. Duty
+    /// cycles are clamped to the limits of the configuration, and channels with a slew-rate limit
+    /// are moved by the ramp thread instead. Nothing is written if a channel is invalid.
+    pub(crate) fn set_pwm_duty_cycles(
+        &mut self,
+        channels: &[usize],
+        duty_cycles: &[f32],
+    ) -> Result<(), String> {
+        let mut expanded = Vec::with_capacity(channels.len());
+        for (&index, &duty_cycle) in channels.iter().zip(duty_cycles) {
+            expanded.extend(
+                pwm_channels(index)?
+                    .into_iter()
+                    .map(|channel| (channel, duty_cycle)),
+            );
+        }
+        let (channels, mut duty_cycles): (Vec<usize>, Vec<f32>) = expanded.into_iter().unzip();
+        config::clamp_duty_cycles(&self.pwm_limits, &channels, &mut duty_cycles);
+
+        let current = match channels
+            .iter()
+            .any(|&channel| self.ramps.is_slew_limited(channel))
+        {
+            true => self.navigator.pwm_state().duty_cycles,
+            false => Default::default(),
+        };
+        let ramps = &mut self.ramps;
+        let (channels, duty_cycles): (Vec<usize>, Vec<f32>) = channels
+            .into_iter()
+            .zip(duty_cycles)
+            .filter(|&(channel, duty_cycle)| {
+                ramps.set_target(channel, duty_cycle, current[channel])
+            })
+            .unzip();
+        self.navigator.set_pwm_duty_cycles(&channels, &duty_cycles);
+        self.wake_ramps();
+        Ok(())
+    }
+
+    /// Sets both counters of the channel, with `All` expanded, replacing its ramp. The OFF counter
+    /// is moved if the duty cycle is outside the channel's limits, counters above 4095 and invalid
+    /// channels are rejected.
+    pub(crate) fn set_pwm_channel_on_off(
+        &mut self,
+        channel: usize,
+        on: u16,
+        off: u16,
+    ) -> Result<(), String> {
+        if on > COUNTER_MAX || off > COUNTER_MAX {
+            return Err(format!(
+                "PWM counters must be between 0 and {COUNTER_MAX}, found {on} and {off}."
+            ));
+        }
+        for channel in pwm_channels(channel)? {
+            self.ramps.stop(channel);
+            let counters = Counters::new(on, off);
+            let duty_cycle = self.pwm_limits[channel].clamp(counters.duty_cycle());
+            let counters = match duty_cycle == counters.duty_cycle() {
+                true => counters,
+                false => counters.with_duty_cycle(duty_cycle),
+            };
+            self.navigator
+                .set_pwm_channel_on_off(channel, counters.on(), counters.off());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_pwm_phase_stagger(&mut self, enabled: bool) {
+        self.pwm_phase_stagger = enabled;
+        self.navigator.set_pwm_phase_stagger(enabled);
+    }
+
+    pub(crate) fn set_pwm_slew_rate(&mut self, channel: usize, rate: f32) -> Result<(), String> {
+        for channel in pwm_channels(channel)? {
+            self.ramps.set_slew_rate(channel, rate);
+        }
+        Ok(())
+    }
+
+    /// Moves the channel linearly to `target`, clamped to its limits, over `duration`.
+    pub(crate) fn ramp_pwm_channel(
+        &mut self,
+        channel: usize,
+        target: f32,
+        duration: Duration,
+    ) -> Result<(), String> {
+        let channels = pwm_channels(channel)?;
+        let target = target.clamp(0.0, 1.0);
+        let current = self.navigator.pwm_state().duty_cycles;
+        for channel in channels {
+            let target = self.pwm_limits[channel].clamp(target);
+            self.ramps
+                .ramp(channel, target, duration, current[channel], Instant::now());
+        }
+        self.wake_ramps();
+        Ok(())
+    }
+
+    pub(crate) fn stop_pwm_ramp(&mut self, channel: usize) -> Result<(), String> {
+        for channel in pwm_channels(channel)? {
+            self.ramps.stop(channel);
+        }
+        Ok(())
+    }
+
+    /// Wakes the ramp thread if a channel moves, starting it the first time.
+    fn wake_ramps(&mut self) {
+        if self.ramps.is_idle() {
+            return;
+        }
+        let woken = self
+            .ramp_thread
+            .as_ref()
+            .map_or(false, |thread| thread.send(()).is_ok());
+        if !woken {
+            let (sender, receiver) = mpsc::channel();
+            let slot = self.slot.clone();
+            std::thread::spawn(move || step_ramps(slot, receiver));
+            sender.send(()).ok();
+            self.ramp_thread = Some(sender);
+        }
+    }
+
+    pub(crate) fn set_output(&mut self, name: &str, duty_cycle: f32) -> Result<(), String> {
+        let channel = self.channel_names.pwm(name)?;
+        self.set_pwm_duty_cycles(&[channel], &[duty_cycle])
+    }
+
+    pub(crate) fn read_analog(&mut self, name: &str) -> Result<f32, String> {
+        let channel = self.channel_names.adc(name)?;
+        Ok(self.read_adc(channel))
+    }
+}
+
+/// Ramp thread of an instance, writing the moving channels until they reach their target, then
+/// waiting to be woken. Exits with its instance, once the instance's sender is dropped.
+fn step_ramps(slot: Weak<Slot>, wake: Receiver<()>) {
+    while wake.recv().is_ok() {
+        loop {
+            std::thread::sleep(crate::ramp::STEP_PERIOD);
+            let Some(slot) = slot.upgrade() else {
+                return;
+            };
+            let mut instance = slot.lock().unwrap_or_else(PoisonError::into_inner);
+            // Another instance may have replaced this one while sleeping
+            loop {
+                match wake.try_recv() {
+                    Ok(()) => continue,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+            let Some(instance) = instance.as_mut() else {
+                return;
+            };
+
+            let (channels, duty_cycles) = instance.ramps.step(Instant::now());
+            let navigator = &mut instance.navigator;
+            // A failed hardware access should not stop the ramps of the other channels
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                navigator.set_pwm_duty_cycles(&channels, &duty_cycles)
+            }));
+            if let Err(error) = result {
+                eprintln!("Failed to update PWM ramps: {}", panic_message(error));
+            }
+            if instance.ramps.is_idle() {
+                break;
+            }
+        }
+    }
+}
+
+/// A Navigator instance, independent from the default one used by the free functions.
+///
+/// Created from a `NavigatorBuilder` (the default configuration if omitted), the hardware or daemon
+/// connection is released by `close`, or when leaving a `with` block.
+///
+/// Examples:
+///     >>> from bluerobotics_navigator import Backend, Navigator, NavigatorBuilder
+///     >>> builder = NavigatorBuilder().with_backend(Backend.Daemon)
+///     >>> with Navigator(builder) as nav:
+///     ...     print(nav.read_temp())
+#[cfg_attr(feature = "python", pyo3::pyclass(name = "Navigator"))]
+pub struct NavigatorHandle {
+    instance: Arc<Slot>,
+}
+
+impl NavigatorHandle {
+    /// A handle without instance, opened by `open`.
+    pub(crate) fn closed() -> Self {
+        Self {
+            instance: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Creates the instance with `configuration`.
+    fn open(&self, configuration: &NavigatorBuilderManager) -> Result<(), String> {
+        *self.lock() = Some(Instance::new(
+            configuration,
+            Arc::downgrade(&self.instance),
+        )?);
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<Option<Instance>> {
+        // A failed hardware access panics while holding the lock, the instance is still usable
+        self.instance.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Releases the instance, stopping its PWM ramps.
+    pub(crate) fn close(&self) {
+        self.lock().take();
+    }
+
+    /// Locks the instance, created with `configuration` if closed. Panics if it can't be created,
+    /// like a failed hardware access, so the next call tries again.
+    pub(crate) fn lock_or_open(
+        &self,
+        configuration: impl FnOnce() -> NavigatorBuilderManager,
+    ) -> MutexGuard<Option<Instance>> {
+        let mut instance = self.lock();
+        if instance.is_none() {
+            let opened = Instance::new(&configuration(), Arc::downgrade(&self.instance));
+            *instance = Some(opened.unwrap_or_else(|error| panic!("{error}")));
+        }
+        instance
+    }
+
+    /// Runs `f` on the instance, or returns `None` if the handle was closed.
+    pub(crate) fn with_instance<T>(&self, f: impl FnOnce(&mut Instance) -> T) -> Option<T> {
+        self.lock().as_mut().map(f)
+    }
+}
+
+#[cfg(not(feature = "python"))]
+pub use c::navigator_last_error;
+#[cfg(not(feature = "python"))]
+pub(crate) use c::set_last_error;
+
+#[cfg(feature = "python")]
+mod python {
+    use pyo3::exceptions::{PyKeyError, PyOSError, PyRuntimeError, PyValueError};
+    use pyo3::prelude::*;
+
+    use super::*;
+    use crate::{pwm_channel_index, PwmChannelArg};
+
+    impl NavigatorHandle {
+        /// Runs `f` without the GIL, raising `RuntimeError` if the handle was closed.
+        fn run<T, F>(&self, py: Python, f: F) -> PyResult<T>
+        where
+            T: Send,
+            F: FnOnce(&mut Instance) -> T + Send,
+        {
+            py.allow_threads(|| self.with_instance(f))
+                .ok_or_else(|| PyRuntimeError::new_err("Navigator is closed"))
+        }
+
+        fn set_filter(&self, py: Python, channel: FilterChannel, spec: FilterSpec) -> PyResult<()> {
+            self.run(py, |instance| instance.set_filter(channel, spec))?
+                .map_err(PyValueError::new_err)
+        }
+    }
+
+    #[pymethods]
+    impl NavigatorBuilder {
+        #[new]
+        fn new() -> Self {
+            Self::default()
+        }
+
+        /// Sets the size of the navigator led strip (1 is the default).
+        fn with_rgb_led_strip_size(mut slf: PyRefMut<Self>, size: usize) -> PyRefMut<Self> {
+            slf.configuration.rgb_led_strip_size = size;
+            slf
+        }
+
+        /// Sets the navigator version, `Auto` (the default) detects it.
+        fn with_navigator_version(
+            mut slf: PyRefMut<Self>,
+            version: NavigatorVersion,
+        ) -> PyRefMut<Self> {
+            slf.configuration.navigator_version = version;
+            slf
+        }
+
+        /// Sets the raspberry pi version, `Auto` (the default) detects it.
+        fn with_raspberry_pi_version(
+            mut slf: PyRefMut<Self>,
+            version: Raspberry,
+        ) -> PyRefMut<Self> {
+            slf.configuration.raspberry_pi_version = version;
+            slf
+        }
+
+        /// Selects how the hardware is accessed, see :py:func:`set_backend`.
+        #[pyo3(signature = (backend, socket_path = None))]
+        fn with_backend(
+            mut slf: PyRefMut<Self>,
+            backend: Backend,
+            socket_path: Option<String>,
+        ) -> PyRefMut<Self> {
+            slf.configuration.backend = backend;
+            slf.configuration.daemon_socket_path =
+                socket_path.unwrap_or_else(|| crate::daemon::DEFAULT_SOCKET_PATH.to_string());
+            slf
+        }
+
+        /// Sets how the board is mounted in the vehicle, see :py:func:`set_board_orientation`.
+        fn with_board_orientation(mut slf: PyRefMut<Self>, rotation: Rotation) -> PyRefMut<Self> {
+            slf.configuration.orientation = rotation.into();
+            slf
+        }
+
+        /// Sets the board rotation with a custom matrix, see :py:func:`set_board_orientation_matrix`.
+        fn with_board_orientation_matrix(
+            mut slf: PyRefMut<Self>,
+            matrix: [[f32; 3]; 3],
+        ) -> PyRefMut<Self> {
+            slf.configuration.orientation = matrix;
+            slf
+        }
+
+        /// Sets the PWM chip external clock frequency [Hz], see :py:func:`set_pwm_clock_hz`.
+        fn with_pwm_clock_hz(mut slf: PyRefMut<Self>, clock: f32) -> PyRefMut<Self> {
+            slf.configuration.pwm_clock_hz = clock;
+            slf
+        }
+
+        /// Applies a configuration file, see :py:func:`load_config`.
+        fn with_config(
+            mut slf: PyRefMut<Self>,
+            path: std::path::PathBuf,
+        ) -> PyResult<PyRefMut<Self>> {
+            slf.load_config(&path)?;
+            Ok(slf)
+        }
+
+        /// Creates a :py:class:`Navigator` with this configuration, raises `OSError` if the
+        /// hardware or the daemon can't be accessed.
+        #[pyo3(name = "build")]
+        fn build_py(&self, py: Python) -> PyResult<NavigatorHandle> {
+            py.allow_threads(|| self.build())
+                .map_err(PyOSError::new_err)
+        }
+    }
+
+    #[pymethods]
+    impl NavigatorHandle {
+        #[new]
+        #[pyo3(signature = (builder = None))]
+        fn new(py: Python, builder: Option<NavigatorBuilder>) -> PyResult<Self> {
+            py.allow_threads(|| builder.unwrap_or_default().build())
+                .map_err(PyOSError::new_err)
+        }
+
+        /// Releases the hardware or daemon connection, following calls raise `RuntimeError`.
+        #[pyo3(name = "close")]
+        fn close_py(&self, py: Python) {
+            py.allow_threads(|| self.close());
+        }
+
+        fn __enter__(slf: PyRef<Self>) -> PyRef<Self> {
+            slf
+        }
+
+        fn __exit__(
+            &self,
+            py: Python,
+            _exc_type: PyObject,
+            _exc_value: PyObject,
+            _traceback: PyObject,
+        ) {
+            self.close_py(py)
+        }
+
+        /// Same as :py:func:`set_led`.
+        fn set_led(&self, py: Python, select: UserLed, state: bool) -> PyResult<()> {
+            self.run(py, |instance| {
+                instance.navigator.set_led(select.into(), state)
+            })
+        }
+
+        /// Same as :py:func:`get_led`.
+        fn get_led(&self, py: Python, select: UserLed) -> PyResult<bool> {
+            self.run(py, |instance| instance.navigator.get_led(select.into()))
+        }
+
+        /// Same as :py:func:`set_led_toggle`.
+        fn set_led_toggle(&self, py: Python, select: UserLed) -> PyResult<()> {
+            self.run(py, |instance| {
+                instance.navigator.set_led_toggle(select.into())
+            })
+        }
+
+        /// Same as :py:func:`set_neopixel`.
+        fn set_neopixel(&self, py: Python, rgb_array: Vec<[u8; 3]>) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_neopixel(&rgb_array))
+        }
+
+        /// Same as :py:func:`set_neopixel_rgbw`.
+        fn set_neopixel_rgbw(&self, py: Python, rgb_array: Vec<[u8; 4]>) -> PyResult<()> {
+            self.run(py, |instance| {
+                instance.navigator.set_neopixel_rgbw(&rgb_array)
+            })
+        }
+
+        /// Same as :py:func:`read_adc_all`.
+        fn read_adc_all(&self, py: Python) -> PyResult<Vec<f32>> {
+            self.run(py, Instance::read_adc_all)
+        }
+
+        /// Same as :py:func:`read_adc`.
+        fn read_adc(&self, py: Python, channel: AdcChannel) -> PyResult<f32> {
+            self.run(py, |instance| instance.read_adc(channel))
+        }
+
+        /// Same as :py:func:`read_pressure`.
+        fn read_pressure(&self, py: Python) -> PyResult<f32> {
+            self.run(py, Instance::read_pressure)
+        }
+
+        /// Same as :py:func:`read_temp`.
+        fn read_temp(&self, py: Python) -> PyResult<f32> {
+            self.run(py, Instance::read_temp)
+        }
+
+        /// Same as :py:func:`read_leak`.
+        fn read_leak(&self, py: Python) -> PyResult<bool> {
+            self.run(py, |instance| instance.navigator.read_leak())
+        }
+
+        /// Same as :py:func:`read_mag`.
+        fn read_mag(&self, py: Python) -> PyResult<AxisData> {
+            self.run(py, Instance::read_mag)
+        }
+
+        /// Same as :py:func:`read_accel`.
+        fn read_accel(&self, py: Python) -> PyResult<AxisData> {
+            self.run(py, Instance::read_accel)
+        }
+
+        /// Same as :py:func:`read_gyro`.
+        fn read_gyro(&self, py: Python) -> PyResult<AxisData> {
+            self.run(py, Instance::read_gyro)
+        }
+
+        /// Same as :py:func:`read_all`.
+        fn read_all(&self, py: Python) -> PyResult<SensorData> {
+            self.run(py, Instance::read_all)
+        }
+
+        /// Same as :py:func:`read_imu_batch`.
+        #[cfg(feature = "numpy")]
+        fn read_imu_batch(&self, py: Python, n: usize) -> PyResult<PyObject> {
+            let values = self.run(py, |instance| instance.read_imu_batch(n))?;
+            crate::into_numpy_array(py, &[n, 9], || values)
+        }
+
+        /// Same as :py:func:`set_accel_range`.
+        fn set_accel_range(&self, py: Python, range: AccelRange) -> PyResult<()> {
+            self.run(py, |instance| instance.set_accel_range(range))
+        }
+
+        /// Same as :py:func:`set_gyro_range`.
+        fn set_gyro_range(&self, py: Python, range: GyroRange) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_gyro_range(range))
+        }
+
+        /// Same as :py:func:`set_imu_dlpf`.
+        fn set_imu_dlpf(&self, py: Python, dlpf: ImuDlpf) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_imu_dlpf(dlpf))
+        }
+
+        /// Same as :py:func:`set_imu_sample_rate`.
+        fn set_imu_sample_rate(&self, py: Python, rate_hz: f32) -> PyResult<()> {
+            self.run(py, |instance| {
+                instance.navigator.set_imu_sample_rate(rate_hz)
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`read_imu_fifo`.
+        fn read_imu_fifo(
+            &self,
+            py: Python,
+            max_samples: Option<usize>,
+        ) -> PyResult<Vec<ImuSample>> {
+            let max_samples = max_samples.unwrap_or(usize::MAX);
+            self.run(py, |instance| instance.read_imu_fifo(max_samples))?
+                .map_err(PyRuntimeError::new_err)
+        }
+
+        /// Same as :py:func:`read_vibration`, from the readings of this instance.
+        fn read_vibration(&self, py: Python) -> PyResult<VibrationData> {
+            self.run(py, |instance| instance.read_vibration())
+        }
+
+        /// Same as :py:func:`reset_vibration`.
+        fn reset_vibration(&self, py: Python) -> PyResult<()> {
+            self.run(py, Instance::reset_vibration)
+        }
+
+        /// Same as :py:func:`get_sensor_health`, from the reads of this instance.
+        fn get_sensor_health(&self, py: Python) -> PyResult<Vec<SensorHealth>> {
+            self.run(py, |instance| instance.sensor_health())
+        }
+
+        /// Same as :py:func:`reset_sensor_health`.
+        fn reset_sensor_health(&self, py: Python) -> PyResult<()> {
+            self.run(py, Instance::reset_sensor_health)
+        }
+
+        /// Same as :py:func:`read_adc_checked`.
+        fn read_adc_checked(&self, py: Python, channel: AdcChannel) -> PyResult<CheckedValue> {
+            self.run(py, |instance| instance.read_adc_checked(channel))
+        }
+
+        /// Same as :py:func:`read_pressure_checked`.
+        fn read_pressure_checked(&self, py: Python) -> PyResult<CheckedValue> {
+            self.run(py, Instance::read_pressure_checked)
+        }
+
+        /// Same as :py:func:`read_temp_checked`.
+        fn read_temp_checked(&self, py: Python) -> PyResult<CheckedValue> {
+            self.run(py, Instance::read_temp_checked)
+        }
+
+        /// Same as :py:func:`read_mag_checked`.
+        fn read_mag_checked(&self, py: Python) -> PyResult<CheckedAxisData> {
+            self.run(py, Instance::read_mag_checked)
+        }
+
+        /// Same as :py:func:`read_accel_checked`.
+        fn read_accel_checked(&self, py: Python) -> PyResult<CheckedAxisData> {
+            self.run(py, Instance::read_accel_checked)
+        }
+
+        /// Same as :py:func:`read_gyro_checked`.
+        fn read_gyro_checked(&self, py: Python) -> PyResult<CheckedAxisData> {
+            self.run(py, Instance::read_gyro_checked)
+        }
+
+        /// Same as :py:func:`set_low_pass_filter`, for the reads of this instance.
+        fn set_low_pass_filter(
+            &self,
+            py: Python,
+            channel: FilterChannel,
+            sample_rate: f32,
+            cutoff: f32,
+        ) -> PyResult<()> {
+            let spec = FilterSpec::LowPass {
+                sample_rate,
+                cutoff,
+            };
+            self.set_filter(py, channel, spec)
+        }
+
+        /// Same as :py:func:`set_notch_filter`, for the reads of this instance.
+        fn set_notch_filter(
+            &self,
+            py: Python,
+            channel: FilterChannel,
+            sample_rate: f32,
+            center: f32,
+            bandwidth: f32,
+        ) -> PyResult<()> {
+            let spec = FilterSpec::Notch {
+                sample_rate,
+                center,
+                bandwidth,
+            };
+            self.set_filter(py, channel, spec)
+        }
+
+        /// Same as :py:func:`set_one_euro_filter`, for the reads of this instance.
+        fn set_one_euro_filter(
+            &self,
+            py: Python,
+            channel: FilterChannel,
+            min_cutoff: f32,
+            beta: f32,
+        ) -> PyResult<()> {
+            let spec = FilterSpec::OneEuro { min_cutoff, beta };
+            self.set_filter(py, channel, spec)
+        }
+
+        /// Same as :py:func:`set_moving_average_filter`, for the reads of this instance.
+        fn set_moving_average_filter(
+            &self,
+            py: Python,
+            channel: FilterChannel,
+            window: usize,
+        ) -> PyResult<()> {
+            let spec = FilterSpec::MovingAverage { window };
+            self.set_filter(py, channel, spec)
+        }
+
+        /// Same as :py:func:`set_median_filter`, for the reads of this instance.
+        fn set_median_filter(
+            &self,
+            py: Python,
+            channel: FilterChannel,
+            window: usize,
+        ) -> PyResult<()> {
+            let spec = FilterSpec::Median { window };
+            self.set_filter(py, channel, spec)
+        }
+
+        /// Same as :py:func:`clear_filter`.
+        fn clear_filter(&self, py: Python, channel: FilterChannel) -> PyResult<()> {
+            self.run(py, |instance| instance.clear_filter(channel))
+        }
+
+        /// Same as :py:func:`read_adc_all_filtered`.
+        fn read_adc_all_filtered(&self, py: Python) -> PyResult<Vec<f32>> {
+            self.run(py, Instance::read_adc_all_filtered)
+        }
+
+        /// Same as :py:func:`read_adc_filtered`.
+        fn read_adc_filtered(&self, py: Python, channel: AdcChannel) -> PyResult<f32> {
+            self.run(py, |instance| instance.read_adc_filtered(channel))
+        }
+
+        /// Same as :py:func:`read_pressure_filtered`.
+        fn read_pressure_filtered(&self, py: Python) -> PyResult<f32> {
+            self.run(py, Instance::read_pressure_filtered)
+        }
+
+        /// Same as :py:func:`read_temp_filtered`.
+        fn read_temp_filtered(&self, py: Python) -> PyResult<f32> {
+            self.run(py, Instance::read_temp_filtered)
+        }
+
+        /// Same as :py:func:`read_mag_filtered`.
+        fn read_mag_filtered(&self, py: Python) -> PyResult<AxisData> {
+            self.run(py, Instance::read_mag_filtered)
+        }
+
+        /// Same as :py:func:`read_accel_filtered`.
+        fn read_accel_filtered(&self, py: Python) -> PyResult<AxisData> {
+            self.run(py, Instance::read_accel_filtered)
+        }
+
+        /// Same as :py:func:`read_gyro_filtered`.
+        fn read_gyro_filtered(&self, py: Python) -> PyResult<AxisData> {
+            self.run(py, Instance::read_gyro_filtered)
+        }
+
+        /// Same as :py:func:`claim_pwm`.
+        fn claim_pwm(&self, py: Python) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.claim_pwm())?
+                .map_err(PyRuntimeError::new_err)
+        }
+
+        /// Same as :py:func:`release_pwm`.
+        fn release_pwm(&self, py: Python) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.release_pwm())
+        }
+
+        /// Same as :py:func:`set_pwm_enable`.
+        fn set_pwm_enable(&self, py: Python, state: bool) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_pwm_enable(state))
+        }
+
+        /// Same as :py:func:`set_pwm_freq_hz`.
+        fn set_pwm_freq_hz(&self, py: Python, freq: f32) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_pwm_frequency(freq))?
+                .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_freq_prescale`.
+        fn set_pwm_freq_prescale(&self, py: Python, prescale: u8) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_pwm_prescale(prescale))?
+                .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_output_mode`.
+        fn set_pwm_output_mode(&self, py: Python, mode: PwmOutputMode) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_pwm_output_mode(mode))
+        }
+
+        /// Same as :py:func:`set_pwm_invert`.
+        fn set_pwm_invert(&self, py: Python, inverted: bool) -> PyResult<()> {
+            self.run(py, |instance| instance.navigator.set_pwm_invert(inverted))
+        }
+
+        /// Same as :py:func:`set_pwm_channel_value`.
+        fn set_pwm_channel_value(
+            &self,
+            py: Python,
+            channel: PwmChannelArg,
+            value: f32,
+        ) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            self.run(py, |instance| {
+                instance.set_pwm_duty_cycles(&[channel], &[value / 4096.0])
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_channel_duty_cycle`.
+        fn set_pwm_channel_duty_cycle(
+            &self,
+            py: Python,
+            channel: PwmChannelArg,
+            duty_cycle: f32,
+        ) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            self.run(py, |instance| {
+                instance.set_pwm_duty_cycles(&[channel], &[duty_cycle])
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_channel_full_on`.
+        fn set_pwm_channel_full_on(&self, py: Python, channel: PwmChannelArg) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            self.run(py, |instance| {
+                instance.set_pwm_duty_cycles(&[channel], &[1.0])
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_channel_full_off`.
+        fn set_pwm_channel_full_off(&self, py: Python, channel: PwmChannelArg) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            self.run(py, |instance| {
+                instance.set_pwm_duty_cycles(&[channel], &[0.0])
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_channel_on_off`.
+        fn set_pwm_channel_on_off(
+            &self,
+            py: Python,
+            channel: PwmChannelArg,
+            on: u16,
+            off: u16,
+        ) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            self.run(py, |instance| {
+                instance.set_pwm_channel_on_off(channel, on, off)
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_phase_stagger`.
+        fn set_pwm_phase_stagger(&self, py: Python, enabled: bool) -> PyResult<()> {
+            self.run(py, |instance| instance.set_pwm_phase_stagger(enabled))
+        }
+
+        /// Same as :py:func:`set_output`. Names come from the configuration file, see
+        /// :py:meth:`NavigatorBuilder.with_config`.
+        fn set_output(&self, py: Python, name: String, duty_cycle: f32) -> PyResult<()> {
+            self.run(py, |instance| instance.set_output(&name, duty_cycle))?
+                .map_err(PyKeyError::new_err)
+        }
+
+        /// Same as :py:func:`read_analog`, with the names of the configuration file.
+        fn read_analog(&self, py: Python, name: String) -> PyResult<f32> {
+            self.run(py, |instance| instance.read_analog(&name))?
+                .map_err(PyKeyError::new_err)
+        }
+
+        /// Same as :py:func:`set_pwm_channels_duty_cycle_values`.
+        fn set_pwm_channels_duty_cycle_values(
+            &self,
+            py: Python,
+            channels: Vec<PwmChannelArg>,
+            duty_cycle_values: Vec<f32>,
+        ) -> PyResult<()> {
+            if channels.len() != duty_cycle_values.len() {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "The number of channels and duty cycle values must match",
+                ));
+            }
+            let channels: Vec<usize> = channels.into_iter().map(pwm_channel_index).collect();
+            self.run(py, |instance| {
+                instance.set_pwm_duty_cycles(&channels, &duty_cycle_values)
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`get_pwm_state`.
+        fn get_pwm_state(&self, py: Python) -> PyResult<PwmState> {
+            self.run(py, |instance| instance.navigator.pwm_state().into())
+        }
+
+        /// Same as :py:func:`set_pwm_slew_rate`.
+        fn set_pwm_slew_rate(&self, py: Python, channel: PwmChannelArg, rate: f32) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            self.run(py, |instance| instance.set_pwm_slew_rate(channel, rate))?
+                .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`ramp_pwm_channel`.
+        fn ramp_pwm_channel(
+            &self,
+            py: Python,
+            channel: PwmChannelArg,
+            target: f32,
+            duration_ms: u32,
+        ) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            let duration = Duration::from_millis(duration_ms.into());
+            self.run(py, |instance| {
+                instance.ramp_pwm_channel(channel, target, duration)
+            })?
+            .map_err(PyValueError::new_err)
+        }
+
+        /// Same as :py:func:`stop_pwm_ramp`.
+        fn stop_pwm_ramp(&self, py: Python, channel: PwmChannelArg) -> PyResult<()> {
+            let channel = pwm_channel_index(channel);
+            self.run(py, |instance| instance.stop_pwm_ramp(channel))?
+                .map_err(PyValueError::new_err)
+        }
+    }
+}
+
+#[cfg(not(feature = "python"))]
+mod c {
+    use std::cell::RefCell;
+    use std::ffi::CString;
+    use std::os::raw::c_char;
+
+    use cpy_binder::cpy_fn_c;
+
+    use super::*;
+    use crate::{slice_from_c, slice_from_c_mut, Failure, PwmChannelArg};
+
+    thread_local! {
+        // Message of the last failure reported by a null handle or false, for `navigator_last_error`
+        static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+    }
+
+    /// Keeps `message` for `navigator_last_error`.
+    pub(crate) fn set_last_error(message: String) {
+        let message = message.replace('\0', " ");
+        LAST_ERROR.with(|last_error| {
+            *last_error.borrow_mut() = CString::new(message).unwrap_or_default()
+        });
+    }
+
+    fn builder<'a>(builder: *mut NavigatorBuilder) -> &'a mut NavigatorBuilder {
+        unsafe {
+            assert!(!builder.is_null());
+            &mut *builder
+        }
+    }
+
+    fn handle<'a>(handle: *const NavigatorHandle) -> &'a NavigatorHandle {
+        unsafe {
+            assert!(!handle.is_null());
+            &*handle
+        }
+    }
+
+    /// Runs `f` on the instance, handles can only be closed by `navigator_free`. A failed hardware
+    /// or daemon access returns a failure value, see `crate::allow_threads`.
+    fn run<T: Failure>(navigator: *const NavigatorHandle, f: impl FnOnce(&mut Instance) -> T) -> T {
+        let navigator = handle(navigator);
+        crate::allow_threads(|| navigator.with_instance(f).expect("Navigator is closed"))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Creates a navigator configuration with the default options, released by `navigator_builder_free`."]
+    fn navigator_builder_new_c() -> *mut NavigatorBuilder {
+        Box::into_raw(Box::default())
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Releases a configuration created by `navigator_builder_new`."]
+    fn navigator_builder_free_c(builder: *mut NavigatorBuilder) {
+        if !builder.is_null() {
+            drop(unsafe { Box::from_raw(builder) });
+        }
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Sets the size of the navigator led strip (1 is the default)."]
+    fn navigator_builder_set_rgb_led_strip_size_c(builder: *mut NavigatorBuilder, size: usize) {
+        self::builder(builder).configuration.rgb_led_strip_size = size;
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Sets the navigator version, `Auto` (the default) detects it."]
+    fn navigator_builder_set_navigator_version_c(
+        builder: *mut NavigatorBuilder,
+        version: NavigatorVersion,
+    ) {
+        self::builder(builder).configuration.navigator_version = version;
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Sets the raspberry pi version, `Auto` (the default) detects it."]
+    fn navigator_builder_set_raspberry_pi_version_c(
+        builder: *mut NavigatorBuilder,
+        version: Raspberry,
+    ) {
+        self::builder(builder).configuration.raspberry_pi_version = version;
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Selects how the hardware is accessed, see `set_backend`."]
+    fn navigator_builder_set_backend_c(
+        builder: *mut NavigatorBuilder,
+        backend: Backend,
+        socket_path: *const c_char,
+    ) {
+        let configuration = &mut self::builder(builder).configuration;
+        configuration.backend = backend;
+        configuration.daemon_socket_path = if socket_path.is_null() {
+            crate::daemon::DEFAULT_SOCKET_PATH.to_string()
+        } else {
+            unsafe { std::ffi::CStr::from_ptr(socket_path) }
+                .to_string_lossy()
+                .into_owned()
+        };
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Sets how the board is mounted in the vehicle, see `set_board_orientation`."]
+    fn navigator_builder_set_board_orientation_c(
+        builder: *mut NavigatorBuilder,
+        rotation: Rotation,
+    ) {
+        self::builder(builder).configuration.orientation = rotation.into();
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Sets the PWM chip external clock frequency [Hz], see `set_pwm_clock_hz`."]
+    fn navigator_builder_set_pwm_clock_hz_c(builder: *mut NavigatorBuilder, clock: f32) {
+        self::builder(builder).configuration.pwm_clock_hz = clock;
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Applies a configuration file, see `load_config`. Returns false if it can't be read \
+        or is invalid, see `navigator_last_error`."]
+    fn navigator_builder_load_config_c(
+        builder: *mut NavigatorBuilder,
+        path: *const c_char,
+    ) -> bool {
+        let path = unsafe {
+            assert!(!path.is_null());
+            std::ffi::CStr::from_ptr(path)
+        };
+        let path = std::path::PathBuf::from(path.to_string_lossy().into_owned());
+        match self::builder(builder).load_config(&path) {
+            Ok(()) => true,
+            Err(error) => {
+                set_last_error(error.to_string());
+                false
+            }
+        }
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Creates a navigator instance with this configuration, released by `navigator_free`. \
+        Returns null if the hardware or daemon can't be accessed, see `navigator_last_error`."]
+    fn navigator_builder_build_c(builder: *const NavigatorBuilder) -> *mut NavigatorHandle {
+        let builder = unsafe {
+            assert!(!builder.is_null());
+            &*builder
+        };
+        match builder.build() {
+            Ok(navigator) => Box::into_raw(Box::new(navigator)),
+            Err(error) => {
+                set_last_error(error);
+                std::ptr::null_mut()
+            }
+        }
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Describes the last failure of this thread: why a function returned false, null or NaN, or the \
+        failed hardware or daemon access of any function, which then returns NaN, false or 0 values. Valid until \
+        the next failure on this thread."]
+    fn navigator_last_error_c() -> *const c_char {
+        LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Releases a navigator instance created by `navigator_builder_build`."]
+    fn navigator_free_c(navigator: *mut NavigatorHandle) {
+        if !navigator.is_null() {
+            drop(unsafe { Box::from_raw(navigator) });
+        }
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_led`, for a navigator instance."]
+    fn navigator_set_led_c(navigator: *const NavigatorHandle, select: UserLed, state: bool) {
+        run(navigator, |instance| {
+            instance.navigator.set_led(select.into(), state)
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `get_led`, for a navigator instance."]
+    fn navigator_get_led_c(navigator: *const NavigatorHandle, select: UserLed) -> bool {
+        run(navigator, |instance| {
+            instance.navigator.get_led(select.into())
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_led_toggle`, for a navigator instance."]
+    fn navigator_set_led_toggle_c(navigator: *const NavigatorHandle, select: UserLed) {
+        run(navigator, |instance| {
+            instance.navigator.set_led_toggle(select.into())
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_neopixel`, for a navigator instance."]
+    fn navigator_set_neopixel_c(
+        navigator: *const NavigatorHandle,
+        rgb_array: *const [u8; 3],
+        length: usize,
+    ) {
+        let array = unsafe { slice_from_c(rgb_array, length) };
+        run(navigator, |instance| instance.navigator.set_neopixel(array))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_neopixel_rgbw`, for a navigator instance."]
+    fn navigator_set_neopixel_rgbw_c(
+        navigator: *const NavigatorHandle,
+        rgb_array: *const [u8; 4],
+        length: usize,
+    ) {
+        let array = unsafe { slice_from_c(rgb_array, length) };
+        run(navigator, |instance| {
+            instance.navigator.set_neopixel_rgbw(array)
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_adc_all`, for a navigator instance."]
+    fn navigator_read_adc_all_c(
+        navigator: *const NavigatorHandle,
+        adc_array: *mut f32,
+        length: usize,
+    ) {
+        let array = unsafe { slice_from_c_mut(adc_array, length) };
+        let values = run(navigator, Instance::read_adc_all);
+        array[..length].copy_from_slice(&values[..length]);
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_adc`, for a navigator instance."]
+    fn navigator_read_adc_c(navigator: *const NavigatorHandle, channel: AdcChannel) -> f32 {
+        run(navigator, |instance| instance.read_adc(channel))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_analog`, for a navigator instance, with the channel names of its configuration \
+        file."]
+    fn navigator_read_analog_c(navigator: *const NavigatorHandle, name: *const c_char) -> f32 {
+        let name = unsafe { crate::string_from_c(name) };
+        run(navigator, |instance| instance.read_analog(&name)).unwrap_or_else(|error| {
+            set_last_error(error);
+            f32::NAN
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_pressure`, for a navigator instance."]
+    fn navigator_read_pressure_c(navigator: *const NavigatorHandle) -> f32 {
+        run(navigator, Instance::read_pressure)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_temp`, for a navigator instance."]
+    fn navigator_read_temp_c(navigator: *const NavigatorHandle) -> f32 {
+        run(navigator, Instance::read_temp)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_leak`, for a navigator instance."]
+    fn navigator_read_leak_c(navigator: *const NavigatorHandle) -> bool {
+        run(navigator, |instance| instance.navigator.read_leak())
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_mag`, for a navigator instance."]
+    fn navigator_read_mag_c(navigator: *const NavigatorHandle) -> AxisData {
+        run(navigator, Instance::read_mag)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_accel`, for a navigator instance."]
+    fn navigator_read_accel_c(navigator: *const NavigatorHandle) -> AxisData {
+        run(navigator, Instance::read_accel)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_gyro`, for a navigator instance."]
+    fn navigator_read_gyro_c(navigator: *const NavigatorHandle) -> AxisData {
+        run(navigator, Instance::read_gyro)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_all`, for a navigator instance."]
+    fn navigator_read_all_c(navigator: *const NavigatorHandle) -> SensorData {
+        run(navigator, Instance::read_all)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_accel_range`, for a navigator instance."]
+    fn navigator_set_accel_range_c(navigator: *const NavigatorHandle, range: AccelRange) {
+        run(navigator, |instance| instance.set_accel_range(range))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_gyro_range`, for a navigator instance."]
+    fn navigator_set_gyro_range_c(navigator: *const NavigatorHandle, range: GyroRange) {
+        run(navigator, |instance| {
+            instance.navigator.set_gyro_range(range)
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_imu_dlpf`, for a navigator instance."]
+    fn navigator_set_imu_dlpf_c(navigator: *const NavigatorHandle, dlpf: ImuDlpf) {
+        run(navigator, |instance| instance.navigator.set_imu_dlpf(dlpf))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_imu_sample_rate`, for a navigator instance. Returns false if the rate is out of range, \
+        see `navigator_last_error`."]
+    fn navigator_set_imu_sample_rate_c(navigator: *const NavigatorHandle, rate_hz: f32) -> bool {
+        run(navigator, |instance| {
+            instance.navigator.set_imu_sample_rate(rate_hz)
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_imu_fifo`, for a navigator instance."]
+    fn navigator_read_imu_fifo_c(
+        navigator: *const NavigatorHandle,
+        samples: *mut ImuSample,
+        capacity: usize,
+        count: *mut usize,
+    ) -> bool {
+        let read = run(navigator, |instance| instance.read_imu_fifo(capacity));
+        unsafe { crate::copy_imu_samples(read, samples, capacity, count) }
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_vibration`, from the readings of a navigator instance."]
+    fn navigator_read_vibration_c(navigator: *const NavigatorHandle) -> VibrationData {
+        run(navigator, |instance| instance.read_vibration())
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `reset_vibration`, for a navigator instance."]
+    fn navigator_reset_vibration_c(navigator: *const NavigatorHandle) {
+        run(navigator, Instance::reset_vibration)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `get_sensor_health`, from the reads of a navigator instance."]
+    fn navigator_get_sensor_health_c(
+        navigator: *const NavigatorHandle,
+        health: *mut SensorHealth,
+        length: usize,
+    ) -> usize {
+        let sensors = run(navigator, |instance| instance.sensor_health());
+        unsafe { crate::copy_sensor_health(sensors, health, length) }
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `reset_sensor_health`, for a navigator instance."]
+    fn navigator_reset_sensor_health_c(navigator: *const NavigatorHandle) {
+        run(navigator, Instance::reset_sensor_health)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_adc_checked`, for a navigator instance."]
+    fn navigator_read_adc_checked_c(
+        navigator: *const NavigatorHandle,
+        channel: AdcChannel,
+    ) -> CheckedValue {
+        run(navigator, |instance| instance.read_adc_checked(channel))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_pressure_checked`, for a navigator instance."]
+    fn navigator_read_pressure_checked_c(navigator: *const NavigatorHandle) -> CheckedValue {
+        run(navigator, Instance::read_pressure_checked)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_temp_checked`, for a navigator instance."]
+    fn navigator_read_temp_checked_c(navigator: *const NavigatorHandle) -> CheckedValue {
+        run(navigator, Instance::read_temp_checked)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_mag_checked`, for a navigator instance."]
+    fn navigator_read_mag_checked_c(navigator: *const NavigatorHandle) -> CheckedAxisData {
+        run(navigator, Instance::read_mag_checked)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_accel_checked`, for a navigator instance."]
+    fn navigator_read_accel_checked_c(navigator: *const NavigatorHandle) -> CheckedAxisData {
+        run(navigator, Instance::read_accel_checked)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_gyro_checked`, for a navigator instance."]
+    fn navigator_read_gyro_checked_c(navigator: *const NavigatorHandle) -> CheckedAxisData {
+        run(navigator, Instance::read_gyro_checked)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_low_pass_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`."]
+    fn navigator_set_low_pass_filter_c(
+        navigator: *const NavigatorHandle,
+        channel: FilterChannel,
+        sample_rate: f32,
+        cutoff: f32,
+    ) -> bool {
+        let spec = FilterSpec::LowPass {
+            sample_rate,
+            cutoff,
+        };
+        run(navigator, |instance| instance.set_filter(channel, spec))
+            .map_err(set_last_error)
+            .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_notch_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`."]
+    fn navigator_set_notch_filter_c(
+        navigator: *const NavigatorHandle,
+        channel: FilterChannel,
+        sample_rate: f32,
+        center: f32,
+        bandwidth: f32,
+    ) -> bool {
+        let spec = FilterSpec::Notch {
+            sample_rate,
+            center,
+            bandwidth,
+        };
+        run(navigator, |instance| instance.set_filter(channel, spec))
+            .map_err(set_last_error)
+            .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_one_euro_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`."]
+    fn navigator_set_one_euro_filter_c(
+        navigator: *const NavigatorHandle,
+        channel: FilterChannel,
+        min_cutoff: f32,
+        beta: f32,
+    ) -> bool {
+        let spec = FilterSpec::OneEuro { min_cutoff, beta };
+        run(navigator, |instance| instance.set_filter(channel, spec))
+            .map_err(set_last_error)
+            .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_moving_average_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`."]
+    fn navigator_set_moving_average_filter_c(
+        navigator: *const NavigatorHandle,
+        channel: FilterChannel,
+        window: usize,
+    ) -> bool {
+        let spec = FilterSpec::MovingAverage { window };
+        run(navigator, |instance| instance.set_filter(channel, spec))
+            .map_err(set_last_error)
+            .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_median_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`."]
+    fn navigator_set_median_filter_c(
+        navigator: *const NavigatorHandle,
+        channel: FilterChannel,
+        window: usize,
+    ) -> bool {
+        let spec = FilterSpec::Median { window };
+        run(navigator, |instance| instance.set_filter(channel, spec))
+            .map_err(set_last_error)
+            .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `clear_filter`, for a navigator instance."]
+    fn navigator_clear_filter_c(navigator: *const NavigatorHandle, channel: FilterChannel) {
+        run(navigator, |instance| instance.clear_filter(channel))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_adc_all_filtered`, for a navigator instance."]
+    fn navigator_read_adc_all_filtered_c(
+        navigator: *const NavigatorHandle,
+        adc_array: *mut f32,
+        length: usize,
+    ) {
+        let array = unsafe { slice_from_c_mut(adc_array, length) };
+        let values = run(navigator, Instance::read_adc_all_filtered);
+        array[..length].copy_from_slice(&values[..length]);
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_adc_filtered`, for a navigator instance."]
+    fn navigator_read_adc_filtered_c(
+        navigator: *const NavigatorHandle,
+        channel: AdcChannel,
+    ) -> f32 {
+        run(navigator, |instance| instance.read_adc_filtered(channel))
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_pressure_filtered`, for a navigator instance."]
+    fn navigator_read_pressure_filtered_c(navigator: *const NavigatorHandle) -> f32 {
+        run(navigator, Instance::read_pressure_filtered)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_temp_filtered`, for a navigator instance."]
+    fn navigator_read_temp_filtered_c(navigator: *const NavigatorHandle) -> f32 {
+        run(navigator, Instance::read_temp_filtered)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_mag_filtered`, for a navigator instance."]
+    fn navigator_read_mag_filtered_c(navigator: *const NavigatorHandle) -> AxisData {
+        run(navigator, Instance::read_mag_filtered)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_accel_filtered`, for a navigator instance."]
+    fn navigator_read_accel_filtered_c(navigator: *const NavigatorHandle) -> AxisData {
+        run(navigator, Instance::read_accel_filtered)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `read_gyro_filtered`, for a navigator instance."]
+    fn navigator_read_gyro_filtered_c(navigator: *const NavigatorHandle) -> AxisData {
+        run(navigator, Instance::read_gyro_filtered)
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `claim_pwm`, for a navigator instance. Returns false if another client claimed the PWM \
+        outputs, see `navigator_last_error`."]
+    fn navigator_claim_pwm_c(navigator: *const NavigatorHandle) -> bool {
+        run(navigator, |instance| instance.navigator.claim_pwm())
+            .map_err(set_last_error)
+            .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `release_pwm`, for a navigator instance."]
+    fn navigator_release_pwm_c(navigator: *const NavigatorHandle) {
+        run(navigator, |instance| instance.navigator.release_pwm())
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_enable`, for a navigator instance."]
+    fn navigator_set_pwm_enable_c(navigator: *const NavigatorHandle, state: bool) {
+        run(navigator, |instance| {
+            instance.navigator.set_pwm_enable(state)
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_freq_hz`, for a navigator instance. Returns false if the frequency is out of range, see `navigator_last_error`."]
+    fn navigator_set_pwm_freq_hz_c(navigator: *const NavigatorHandle, freq: f32) -> bool {
+        run(navigator, |instance| {
+            instance.navigator.set_pwm_frequency(freq)
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_freq_prescale`, for a navigator instance. Returns false if the value is below 3, see `navigator_last_error`."]
+    fn navigator_set_pwm_freq_prescale_c(navigator: *const NavigatorHandle, prescale: u8) -> bool {
+        run(navigator, |instance| {
+            instance.navigator.set_pwm_prescale(prescale)
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_output_mode`, for a navigator instance."]
+    fn navigator_set_pwm_output_mode_c(navigator: *const NavigatorHandle, mode: PwmOutputMode) {
+        run(navigator, |instance| {
+            instance.navigator.set_pwm_output_mode(mode)
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_invert`, for a navigator instance."]
+    fn navigator_set_pwm_invert_c(navigator: *const NavigatorHandle, inverted: bool) {
+        run(navigator, |instance| {
+            instance.navigator.set_pwm_invert(inverted)
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_channel_value`, for a navigator instance. \
+        Returns false if the channel is invalid, see `navigator_last_error`."]
+    fn navigator_set_pwm_channel_value_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+        value: f32,
+    ) -> bool {
+        run(navigator, |instance| {
+            instance.set_pwm_duty_cycles(&[channel], &[value / 4096.0])
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_channel_duty_cycle`, for a navigator instance. \
+        Returns false if the channel is invalid, see `navigator_last_error`."]
+    fn navigator_set_pwm_channel_duty_cycle_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+        duty_cycle: f32,
+    ) -> bool {
+        run(navigator, |instance| {
+            instance.set_pwm_duty_cycles(&[channel], &[duty_cycle])
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_channel_full_on`, for a navigator instance. \
+        Returns false if the channel is invalid, see `navigator_last_error`."]
+    fn navigator_set_pwm_channel_full_on_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+    ) -> bool {
+        run(navigator, |instance| {
+            instance.set_pwm_duty_cycles(&[channel], &[1.0])
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_channel_full_off`, for a navigator instance. \
+        Returns false if the channel is invalid, see `navigator_last_error`."]
+    fn navigator_set_pwm_channel_full_off_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+    ) -> bool {
+        run(navigator, |instance| {
+            instance.set_pwm_duty_cycles(&[channel], &[0.0])
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_channel_on_off`, for a navigator instance. Returns false if a counter is above 4095 \
+        or the channel is invalid, see `navigator_last_error`."]
+    fn navigator_set_pwm_channel_on_off_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+        on: u16,
+        off: u16,
+    ) -> bool {
+        run(navigator, |instance| {
+            instance.set_pwm_channel_on_off(channel, on, off)
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_phase_stagger`, for a navigator instance."]
+    fn navigator_set_pwm_phase_stagger_c(navigator: *const NavigatorHandle, enabled: bool) {
+        run(navigator, |instance| {
+            instance.set_pwm_phase_stagger(enabled)
+        })
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_output`, for a navigator instance, with the channel names of its configuration \
+        file."]
+    fn navigator_set_output_c(
+        navigator: *const NavigatorHandle,
+        name: *const c_char,
+        duty_cycle: f32,
+    ) -> bool {
+        let name = unsafe { crate::string_from_c(name) };
+        run(navigator, |instance| instance.set_output(&name, duty_cycle))
+            .map_err(set_last_error)
+            .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_channels_duty_cycle_values`, for a navigator instance. \
+        Returns false if a channel is invalid, see `navigator_last_error`."]
+    fn navigator_set_pwm_channels_duty_cycle_values_c(
+        navigator: *const NavigatorHandle,
+        channels: *const usize,
+        duty_cycle_values: *const f32,
+        length: usize,
+    ) -> bool {
+        let channels = unsafe { slice_from_c(channels, length) };
+        let duty_cycle_values = unsafe { slice_from_c(duty_cycle_values, length) };
+        run(navigator, |instance| {
+            instance.set_pwm_duty_cycles(channels, duty_cycle_values)
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `get_pwm_state`, for a navigator instance."]
+    fn navigator_get_pwm_state_c(navigator: *const NavigatorHandle) -> PwmState {
+        run(navigator, |instance| instance.navigator.pwm_state()).into()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `set_pwm_slew_rate`, for a navigator instance. \
+        Returns false if the channel is invalid, see `navigator_last_error`."]
+    fn navigator_set_pwm_slew_rate_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+        rate: f32,
+    ) -> bool {
+        run(navigator, |instance| {
+            instance.set_pwm_slew_rate(channel, rate)
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `ramp_pwm_channel`, for a navigator instance. \
+        Returns false if the channel is invalid, see `navigator_last_error`."]
+    fn navigator_ramp_pwm_channel_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+        target: f32,
+        duration_ms: u32,
+    ) -> bool {
+        let duration = Duration::from_millis(duration_ms.into());
+        run(navigator, |instance| {
+            instance.ramp_pwm_channel(channel, target, duration)
+        })
+        .map_err(set_last_error)
+        .is_ok()
+    }
+
+    #[cpy_fn_c]
+    #[comment = "Same as `stop_pwm_ramp`, for a navigator instance. \
+        Returns false if the channel is invalid, see `navigator_last_error`."]
+    fn navigator_stop_pwm_ramp_c(
+        navigator: *const NavigatorHandle,
+        channel: PwmChannelArg,
+    ) -> bool {
+        run(navigator, |instance| instance.stop_pwm_ramp(channel))
+            .map_err(set_last_error)
+            .is_ok()
+    }
+}
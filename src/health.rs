@@ -0,0 +1,361 @@
+//! Health of the sensors, from the outcome of their readings.
+//!
+//! A sensor is failing while its reads fail, stuck when a quantity keeps the exact same value, and
+//! out of range when a value is beyond what the sensor can measure. Values that aren't finite are
+//! out of range.
+
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+use crate::{Sensor, SensorStatus};
+
+pub const SENSORS: [Sensor; 4] = [
+    Sensor::Barometer,
+    Sensor::Magnetometer,
+    Sensor::Imu,
+    Sensor::Adc,
+];
+
+const QUANTITIES: [Quantity; 6] = [
+    Quantity::Pressure,
+    Quantity::Temperature,
+    Quantity::MagneticField,
+    Quantity::Acceleration,
+    Quantity::AngularVelocity,
+    Quantity::Voltage,
+];
+
+/// Identical consecutive samples after which a quantity is stuck.
+const STUCK_SAMPLES: u32 = 10;
+/// Time a quantity must keep its value to be stuck, sensors read faster than their output data
+/// rate legitimately repeat their last sample.
+const STUCK_TIME: Duration = Duration::from_secs(2);
+
+/// Quantity measured by a sensor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantity {
+    /// Barometer pressure [kPa].
+    Pressure,
+    /// Barometer temperature [°C].
+    Temperature,
+    /// Magnetometer field of each axis [µT].
+    MagneticField,
+    /// Accelerometer acceleration of each axis [m/s²].
+    Acceleration,
+    /// Gyroscope angular velocity of each axis [rad/s].
+    AngularVelocity,
+    /// ADC channel voltages [V].
+    Voltage,
+}
+
+impl Quantity {
+    pub fn sensor(self) -> Sensor {
+        match self {
+            Quantity::Pressure | Quantity::Temperature => Sensor::Barometer,
+            Quantity::MagneticField => Sensor::Magnetometer,
+            Quantity::Acceleration | Quantity::AngularVelocity => Sensor::Imu,
+            Quantity::Voltage => Sensor::Adc,
+        }
+    }
+
+    /// Values the sensors can measure, for both Navigator versions.
+    fn range(self) -> RangeInclusive<f32> {
+        match self {
+            // BMP280 and BMP390
+            Quantity::Pressure => 30.0..=125.0,
+            Quantity::Temperature => -40.0..=85.0,
+            // AK09915
+            Quantity::MagneticField => -4912.0..=4912.0,
+            // ICM20689 widest full scales, ±16g and ±2000°/s
+            Quantity::Acceleration => -16.0 * 9.80665..=16.0 * 9.80665,
+            Quantity::AngularVelocity => -34.91..=34.91,
+            // ADS1115 widest full scale
+            Quantity::Voltage => -6.144..=6.144,
+        }
+    }
+
+    /// Whether a constant value is a fault, a voltage or a temperature can legitimately be stable.
+    fn can_stick(self) -> bool {
+        !matches!(self, Quantity::Temperature | Quantity::Voltage)
+    }
+}
+
+/// Values of a reading, as recorded by [`HealthMonitor::record_sample`].
+pub trait Sample {
+    fn values(&self) -> Vec<f32>;
+}
+
+impl Sample for f32 {
+    fn values(&self) -> Vec<f32> {
+        vec![*self]
+    }
+}
+
+impl Sample for Vec<f32> {
+    fn values(&self) -> Vec<f32> {
+        self.clone()
+    }
+}
+
+impl Sample for navigator_rs::AxisData {
+    fn values(&self) -> Vec<f32> {
+        vec![self.x, self.y, self.z]
+    }
+}
+
+/// Health of a sensor, see [`HealthMonitor::health`].
+#[derive(Clone, Debug)]
+pub struct Health {
+    pub status: SensorStatus,
+    /// Successful reads.
+    pub sample_count: u64,
+    /// Failed reads.
+    pub error_count: u64,
+    /// Successful reads with a stuck or out of range value.
+    pub invalid_count: u64,
+    /// Failed reads since the last successful one.
+    pub consecutive_errors: u32,
+    /// Time of the last valid sample.
+    pub last_good: Option<Instant>,
+    pub stuck: bool,
+    pub out_of_range: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+struct SensorCounters {
+    sample_count: u64,
+    error_count: u64,
+    invalid_count: u64,
+    consecutive_errors: u32,
+    last_good: Option<Instant>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct QuantityState {
+    last: Vec<f32>,
+    unchanged_samples: u32,
+    unchanged_since: Option<Instant>,
+    stuck: bool,
+    out_of_range: bool,
+}
+
+impl QuantityState {
+    /// Updates the state with the values of a new sample, returns whether they are valid.
+    fn update(&mut self, quantity: Quantity, values: &[f32], now: Instant) -> bool {
+        let range = quantity.range();
+        self.out_of_range = !values.iter().all(|value| range.contains(value));
+
+        if quantity.can_stick() {
+            if values == self.last {
+                self.unchanged_samples += 1;
+            } else {
+                self.last = values.to_vec();
+                self.unchanged_samples = 1;
+                self.unchanged_since = Some(now);
+            }
+            self.stuck = self.unchanged_samples >= STUCK_SAMPLES
+                && self.unchanged_since.map_or(false, |since| {
+                    now.saturating_duration_since(since) >= STUCK_TIME
+                });
+        }
+        !self.out_of_range && !self.stuck
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HealthMonitor {
+    sensors: [SensorCounters; SENSORS.len()],
+    quantities: [QuantityState; QUANTITIES.len()],
+}
+
+impl HealthMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a successful read of `sensor` taken at `now`, with the values of each quantity
+    /// read. Returns whether every value is valid.
+    pub fn record_sample(
+        &mut self,
+        sensor: Sensor,
+        samples: &[(Quantity, &[f32])],
+        now: Instant,
+    ) -> bool {
+        let mut valid = true;
+        for (quantity, values) in samples {
+            valid &= self.quantities[*quantity as usize].update(*quantity, values, now);
+        }
+
+        let counters = &mut self.sensors[sensor as usize];
+        counters.sample_count += 1;
+        counters.consecutive_errors = 0;
+        match valid {
+            true => counters.last_good = Some(now),
+            false => counters.invalid_count += 1,
+        }
+        valid
+    }
+
+    /// Records a failed read of `sensor`.
+    pub fn record_error(&mut self, sensor: Sensor) {
+        let counters = &mut self.sensors[sensor as usize];
+        counters.error_count += 1;
+        counters.consecutive_errors = counters.consecutive_errors.saturating_add(1);
+    }
+
+    pub fn health(&self, sensor: Sensor) -> Health {
+        let index = sensor as usize;
+        let counters = &self.sensors[index];
+        let quantities = QUANTITIES
+            .iter()
+            .zip(&self.quantities)
+            .filter(|(quantity, _)| quantity.sensor() as usize == index)
+            .map(|(_, state)| state);
+        let (stuck, out_of_range) =
+            quantities.fold((false, false), |(stuck, out_of_range), state| {
+                (stuck || state.stuck, out_of_range || state.out_of_range)
+            });
+
+        let status = if counters.sample_count == 0 && counters.error_count == 0 {
+            SensorStatus::NoData
+        } else if counters.consecutive_errors > 0 {
+            SensorStatus::Failing
+        } else if out_of_range {
+            SensorStatus::OutOfRange
+        } else if stuck {
+            SensorStatus::Stuck
+        } else {
+            SensorStatus::Healthy
+        };
+
+        Health {
+            status,
+            sample_count: counters.sample_count,
+            error_count: counters.error_count,
+            invalid_count: counters.invalid_count,
+            consecutive_errors: counters.consecutive_errors,
+            last_good: counters.last_good,
+            stuck,
+            out_of_range,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn starts_without_data() {
+        let monitor = HealthMonitor::new();
+        let health = monitor.health(Sensor::Barometer);
+        assert!(matches!(health.status, SensorStatus::NoData));
+        assert_eq!(health.sample_count, 0);
+        assert!(health.last_good.is_none());
+    }
+
+    #[test]
+    fn counts_errors_until_a_sample() {
+        let mut monitor = HealthMonitor::new();
+        let now = Instant::now();
+        assert!(monitor.record_sample(Sensor::Barometer, &[(Quantity::Pressure, &[101.3])], now));
+        monitor.record_error(Sensor::Barometer);
+        monitor.record_error(Sensor::Barometer);
+
+        let health = monitor.health(Sensor::Barometer);
+        assert!(matches!(health.status, SensorStatus::Failing));
+        assert_eq!(health.error_count, 2);
+        assert_eq!(health.consecutive_errors, 2);
+        assert_eq!(health.last_good, Some(now));
+        assert!(matches!(
+            monitor.health(Sensor::Magnetometer).status,
+            SensorStatus::NoData
+        ));
+
+        monitor.record_sample(Sensor::Barometer, &[(Quantity::Pressure, &[101.4])], now);
+        let health = monitor.health(Sensor::Barometer);
+        assert!(matches!(health.status, SensorStatus::Healthy));
+        assert_eq!(health.error_count, 2);
+        assert_eq!(health.consecutive_errors, 0);
+        assert_eq!(health.sample_count, 2);
+    }
+
+    #[test]
+    fn detects_out_of_range_values() {
+        let mut monitor = HealthMonitor::new();
+        let now = Instant::now();
+        for values in [
+            [0.0, 0.0, 9000.0],
+            [f32::NAN, 0.0, 0.0],
+            [f32::INFINITY, 0.0, 0.0],
+        ] {
+            let sample = [(Quantity::MagneticField, values.as_slice())];
+            assert!(!monitor.record_sample(Sensor::Magnetometer, &sample, now));
+        }
+        let health = monitor.health(Sensor::Magnetometer);
+        assert!(matches!(health.status, SensorStatus::OutOfRange));
+        assert!(health.out_of_range);
+        assert_eq!(health.invalid_count, 3);
+        assert!(health.last_good.is_none());
+
+        let sample = [(Quantity::MagneticField, [20.0, -5.0, 40.0].as_slice())];
+        assert!(monitor.record_sample(Sensor::Magnetometer, &sample, now));
+        assert!(matches!(
+            monitor.health(Sensor::Magnetometer).status,
+            SensorStatus::Healthy
+        ));
+    }
+
+    #[test]
+    fn detects_stuck_values() {
+        let mut monitor = HealthMonitor::new();
+        let start = Instant::now();
+        let sample = [(Quantity::Acceleration, [0.1, 0.2, 9.8].as_slice())];
+
+        // Repeated samples read faster than the output data rate are fine
+        for _ in 0..100 {
+            assert!(monitor.record_sample(Sensor::Imu, &sample, start));
+        }
+        // Until the value doesn't change for long enough
+        let later = start + Duration::from_secs(3);
+        assert!(!monitor.record_sample(Sensor::Imu, &sample, later));
+        let health = monitor.health(Sensor::Imu);
+        assert!(matches!(health.status, SensorStatus::Stuck));
+        assert_eq!(health.last_good, Some(start));
+
+        let sample = [(Quantity::Acceleration, [0.1, 0.3, 9.8].as_slice())];
+        assert!(monitor.record_sample(Sensor::Imu, &sample, later));
+        assert!(!monitor.health(Sensor::Imu).stuck);
+    }
+
+    #[test]
+    fn ignores_stable_voltages() {
+        let mut monitor = HealthMonitor::new();
+        let start = Instant::now();
+        let sample = [(Quantity::Voltage, [3.3, 0.0, 0.0, 0.0].as_slice())];
+        for seconds in 0..20 {
+            let now = start + Duration::from_secs(seconds);
+            assert!(monitor.record_sample(Sensor::Adc, &sample, now));
+        }
+        assert!(matches!(
+            monitor.health(Sensor::Adc).status,
+            SensorStatus::Healthy
+        ));
+    }
+
+    #[test]
+    fn combines_quantities_of_a_sensor() {
+        let mut monitor = HealthMonitor::new();
+        let now = Instant::now();
+        let samples = [
+            (Quantity::Pressure, [101.3].as_slice()),
+            (Quantity::Temperature, [150.0].as_slice()),
+        ];
+        assert!(!monitor.record_sample(Sensor::Barometer, &samples, now));
+        let health = monitor.health(Sensor::Barometer);
+        assert!(health.out_of_range);
+        assert_eq!(health.sample_count, 1);
+    }
+}
@@ -0,0 +1,267 @@
+//! Register access to the ICM20689 IMU, for what navigator-rs doesn't expose: the full-scale
+//! ranges, the digital low-pass filter, the sample rate and the FIFO.
+//!
+//! navigator-rs keeps reading the sensors through its own SPI handle, scaled for the ranges it
+//! sets up (±8 g and ±2000 dps), so its readings are rescaled by the ratio of the ranges written
+//! here to those.
+
+use std::io;
+
+use spidev::{SpiModeFlags, Spidev, SpidevOptions, SpidevTransfer};
+
+use crate::{AccelRange, GyroRange, ImuDlpf};
+
+/// SPI device of the IMU on the Navigator.
+const SPI_DEVICE: &str = "/dev/spidev1.2";
+const SPI_SPEED_HZ: u32 = 10_000_000;
+/// Register address bit selecting a read.
+const READ: u8 = 0x80;
+
+const SMPLRT_DIV: u8 = 0x19;
+const CONFIG: u8 = 0x1A;
+const GYRO_CONFIG: u8 = 0x1B;
+const ACCEL_CONFIG: u8 = 0x1C;
+const ACCEL_CONFIG2: u8 = 0x1D;
+const FIFO_EN: u8 = 0x23;
+const USER_CTRL: u8 = 0x6A;
+const FIFO_COUNT_H: u8 = 0x72;
+const FIFO_R_W: u8 = 0x74;
+
+/// Offset of the full-scale range in GYRO_CONFIG (FS_SEL) and ACCEL_CONFIG (ACCEL_FS_SEL).
+const FS_SEL_SHIFT: u8 = 3;
+
+/// FIFO_EN bits storing the gyroscope and accelerometer axes.
+const FIFO_EN_GYRO_ACCEL: u8 = 0x78;
+const USER_CTRL_FIFO_EN: u8 = 0x40;
+const USER_CTRL_FIFO_RESET: u8 = 0x04;
+/// Bytes of a FIFO sample: the accelerometer then the gyroscope axes, as big-endian `i16`.
+const FIFO_SAMPLE_SIZE: usize = 12;
+const FIFO_SIZE: usize = 4096;
+/// Largest FIFO read in a single transfer, within the 4096 bytes buffer of spidev.
+const FIFO_READ_SIZE: usize = 4095 / FIFO_SAMPLE_SIZE * FIFO_SAMPLE_SIZE;
+
+/// Internal sample rate divided by `SMPLRT_DIV + 1` when the low-pass filter is enabled [Hz].
+const INTERNAL_SAMPLE_RATE_HZ: f32 = 1000.0;
+
+/// Accelerometer range assumed by the navigator-rs readings.
+pub const DEFAULT_ACCEL_RANGE: AccelRange = AccelRange::G8;
+/// Gyroscope range assumed by the navigator-rs readings.
+pub const DEFAULT_GYRO_RANGE: GyroRange = GyroRange::Dps2000;
+
+/// Standard gravity used by the icm20689 crate to scale the accelerometer readings [m/s²].
+const GRAVITY: f32 = 9.807;
+
+impl AccelRange {
+    /// Largest measurement, in the unit of the navigator-rs readings [m/s²].
+    pub fn full_scale(&self) -> f32 {
+        let range_g = match self {
+            AccelRange::G2 => 2.0,
+            AccelRange::G4 => 4.0,
+            AccelRange::G8 => 8.0,
+            AccelRange::G16 => 16.0,
+        };
+        range_g * GRAVITY
+    }
+}
+
+impl GyroRange {
+    /// Largest measurement, in the unit of the navigator-rs readings [rad/s].
+    pub fn full_scale(&self) -> f32 {
+        let range_dps: f32 = match self {
+            GyroRange::Dps250 => 250.0,
+            GyroRange::Dps500 => 500.0,
+            GyroRange::Dps1000 => 1000.0,
+            GyroRange::Dps2000 => 2000.0,
+        };
+        range_dps.to_radians()
+    }
+}
+
+impl ImuDlpf {
+    /// DLPF_CFG value of CONFIG, A_DLPF_CFG of ACCEL_CONFIG2 has the closest bandwidth at the same
+    /// value.
+    fn config(&self) -> u8 {
+        match self {
+            ImuDlpf::Hz176 => 1,
+            ImuDlpf::Hz92 => 2,
+            ImuDlpf::Hz41 => 3,
+            ImuDlpf::Hz20 => 4,
+            ImuDlpf::Hz10 => 5,
+            ImuDlpf::Hz5 => 6,
+        }
+    }
+}
+
+/// Raw accelerometer then gyroscope axes of a FIFO sample.
+pub type RawSample = [i16; 6];
+
+pub struct Icm20689 {
+    spi: Spidev,
+    // Whether a DLPF_CFG applying SMPLRT_DIV was written, it's 0 after the reset by navigator-rs
+    dlpf_enabled: bool,
+    fifo_enabled: bool,
+}
+
+impl Icm20689 {
+    /// Opens the IMU, once set up by navigator-rs.
+    pub fn open() -> io::Result<Self> {
+        let mut spi = Spidev::open(SPI_DEVICE)?;
+        spi.configure(
+            &SpidevOptions::new()
+                .bits_per_word(8)
+                .max_speed_hz(SPI_SPEED_HZ)
+                .mode(SpiModeFlags::SPI_MODE_0)
+                .build(),
+        )?;
+        let mut imu = Self {
+            spi,
+            dlpf_enabled: false,
+            fifo_enabled: false,
+        };
+        // The icm20689 crate shifts the gyroscope range to the wrong bits, selecting ±500 dps while
+        // scaling the readings for ±2000 dps, which made them 4 times too small. Selecting the
+        // range it scales for fixes them, and moves their saturation from ±500 to ±2000 dps.
+        imu.set_gyro_range(DEFAULT_GYRO_RANGE)?;
+        Ok(imu)
+    }
+
+    pub fn set_accel_range(&mut self, range: AccelRange) -> io::Result<()> {
+        self.write_register(ACCEL_CONFIG, full_scale_select(range as u8))
+    }
+
+    pub fn set_gyro_range(&mut self, range: GyroRange) -> io::Result<()> {
+        self.write_register(GYRO_CONFIG, full_scale_select(range as u8))
+    }
+
+    /// Sets the bandwidth of both the gyroscope and the accelerometer filters.
+    pub fn set_dlpf(&mut self, dlpf: ImuDlpf) -> io::Result<()> {
+        self.write_register(CONFIG, dlpf.config())?;
+        self.write_register(ACCEL_CONFIG2, dlpf.config())?;
+        self.dlpf_enabled = true;
+        Ok(())
+    }
+
+    /// Sets the sample rate divider, enabling the low-pass filter it needs at its widest bandwidth
+    /// if it wasn't.
+    pub fn set_sample_rate_divider(&mut self, divider: u8) -> io::Result<()> {
+        if !self.dlpf_enabled {
+            self.set_dlpf(ImuDlpf::Hz176)?;
+        }
+        self.write_register(SMPLRT_DIV, divider)
+    }
+
+    /// Reads up to `max_samples` from the FIFO, oldest first. The first read starts the FIFO and
+    /// returns nothing, a full FIFO is reset since its samples are no longer aligned.
+    pub fn read_fifo(&mut self, max_samples: usize) -> io::Result<Vec<RawSample>> {
+        if !self.fifo_enabled {
+            self.write_register(FIFO_EN, FIFO_EN_GYRO_ACCEL)?;
+            self.reset_fifo()?;
+            self.fifo_enabled = true;
+            return Ok(Vec::new());
+        }
+        let count = self.read_registers(FIFO_COUNT_H, 2)?;
+        let count = u16::from_be_bytes([count[0] & 0x1F, count[1]]) as usize;
+        if count >= FIFO_SIZE {
+            self.reset_fifo()?;
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "IMU FIFO overflowed, its samples were dropped",
+            ));
+        }
+
+        let mut remaining = (count / FIFO_SAMPLE_SIZE).min(max_samples) * FIFO_SAMPLE_SIZE;
+        let mut bytes = Vec::with_capacity(remaining);
+        while remaining > 0 {
+            let size = remaining.min(FIFO_READ_SIZE);
+            bytes.extend(self.read_registers(FIFO_R_W, size)?);
+            remaining -= size;
+        }
+        Ok(parse_fifo(&bytes))
+    }
+
+    fn reset_fifo(&mut self) -> io::Result<()> {
+        self.write_register(USER_CTRL, USER_CTRL_FIFO_EN | USER_CTRL_FIFO_RESET)
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) -> io::Result<()> {
+        self.transfer(&[register, value]).map(drop)
+    }
+
+    fn read_registers(&mut self, register: u8, count: usize) -> io::Result<Vec<u8>> {
+        let mut request = vec![0; count + 1];
+        request[0] = register | READ;
+        Ok(self.transfer(&request)?.split_off(1))
+    }
+
+    fn transfer(&mut self, request: &[u8]) -> io::Result<Vec<u8>> {
+        let mut response = vec![0; request.len()];
+        self.spi
+            .transfer(&mut SpidevTransfer::read_write(request, &mut response))?;
+        Ok(response)
+    }
+}
+
+/// GYRO_CONFIG or ACCEL_CONFIG value selecting the full-scale range of index `range`.
+fn full_scale_select(range: u8) -> u8 {
+    range << FS_SEL_SHIFT
+}
+
+/// `SMPLRT_DIV` value giving the closest sample rate to `rate_hz`, fails if it's out of range.
+pub fn sample_rate_divider(rate_hz: f32) -> Result<u8, String> {
+    let min = INTERNAL_SAMPLE_RATE_HZ / 256.0;
+    if !(min..=INTERNAL_SAMPLE_RATE_HZ).contains(&rate_hz) {
+        return Err(format!(
+            "IMU sample rate must be between {min:.1} and {INTERNAL_SAMPLE_RATE_HZ:.0} Hz, found {rate_hz}."
+        ));
+    }
+    Ok((INTERNAL_SAMPLE_RATE_HZ / rate_hz - 1.0).round() as u8)
+}
+
+/// Splits the bytes read from the FIFO into samples, ignoring an incomplete one at the end.
+fn parse_fifo(bytes: &[u8]) -> Vec<RawSample> {
+    bytes
+        .chunks_exact(FIFO_SAMPLE_SIZE)
+        .map(|sample| {
+            let mut axes = [0; 6];
+            for (axis, bytes) in axes.iter_mut().zip(sample.chunks_exact(2)) {
+                *axis = i16::from_be_bytes([bytes[0], bytes[1]]);
+            }
+            axes
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fifo_samples_are_big_endian_axes() {
+        let mut bytes = vec![
+            0x00, 0x01, 0xFF, 0xFF, 0x80, 0x00, 0x7F, 0xFF, 0x12, 0x34, 0, 0,
+        ];
+        bytes.extend([0xAA; 5]);
+        assert_eq!(parse_fifo(&bytes), [[1, -1, i16::MIN, i16::MAX, 0x1234, 0]]);
+    }
+
+    #[test]
+    fn gyro_range_is_the_one_navigator_rs_scales_for() {
+        // The icm20689 crate writes ±2000 dps one bit too low, where it reads as ±500 dps
+        let crate_config = (GyroRange::Dps2000 as u8) << 2;
+        assert_eq!(crate_config >> FS_SEL_SHIFT, GyroRange::Dps500 as u8);
+
+        assert_eq!(full_scale_select(DEFAULT_GYRO_RANGE as u8), 0x18);
+        assert_eq!(DEFAULT_GYRO_RANGE.full_scale(), 2000f32.to_radians());
+    }
+
+    #[test]
+    fn sample_rates_are_rounded_to_a_divider() {
+        assert_eq!(sample_rate_divider(1000.0), Ok(0));
+        assert_eq!(sample_rate_divider(100.0), Ok(9));
+        assert_eq!(sample_rate_divider(3.90625), Ok(255));
+        assert_eq!(sample_rate_divider(190.0), Ok(4));
+        for rate in [0.0, 3.0, 1001.0, f32::NAN, -100.0] {
+            assert!(sample_rate_divider(rate).is_err(), "{rate}");
+        }
+    }
+}
@@ -1,53 +1,435 @@
-use cpy_binder::{cpy_enum, cpy_fn, cpy_fn_c, cpy_fn_py, cpy_module, cpy_struct};
+use cpy_binder::{cpy_enum, cpy_fn, cpy_fn_c, cpy_fn_py, cpy_struct};
 
 use lazy_static::lazy_static;
 use std::sync::Mutex;
 
-#[cpy_enum]
-#[comment = "Raspberry Pi version."]
-enum Raspberry {
-    Pi4,
-    Pi5,
+#[cfg(feature = "python")]
+mod aio;
+pub mod backend;
+mod channels;
+mod config;
+pub mod daemon;
+mod detect;
+mod filters;
+mod handle;
+mod health;
+mod icm20689;
+#[cfg(feature = "lua")]
+pub mod lua;
+#[cfg(feature = "node")]
+mod node;
+mod orientation;
+mod pca9685;
+mod ramp;
+mod vibration;
+
+use backend::NavigatorBackend;
+#[cfg(not(feature = "python"))]
+pub use handle::navigator_last_error;
+#[cfg(feature = "python")]
+use handle::NavigatorBuilder;
+use handle::NavigatorHandle;
+
+fn panic_message(error: Box<dyn std::any::Any + Send>) -> String {
+    error
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| error.downcast_ref::<&str>().map(|error| error.to_string()))
+        .unwrap_or_else(|| "Unknown failure".to_string())
+}
+
+/// Declares an enum of the bindings. With the `node` feature, it's also exported to Node.js by a
+/// `#[napi]` copy, which the addon functions convert from and to this enum.
+macro_rules! navigator_enum {
+    (#[comment = $comment:literal] enum $name:ident { $($variant:ident),+ $(,)? }) => {
+        #[cpy_enum]
+        #[comment = $comment]
+        enum $name {
+            $($variant),+
+        }
+
+        #[cfg(feature = "node")]
+        const _: () = {
+            use napi::bindgen_prelude::{
+                FromNapiValue, Result, ToNapiValue, TypeName, ValidateNapiValue,
+            };
+            use napi::sys::{napi_env, napi_value};
+
+            #[doc = $comment]
+            #[napi_derive::napi]
+            #[allow(clippy::enum_variant_names)]
+            pub enum $name {
+                $($variant),+
+            }
+
+            impl TypeName for crate::$name {
+                fn type_name() -> &'static str {
+                    <$name as TypeName>::type_name()
+                }
+
+                fn value_type() -> napi::ValueType {
+                    <$name as TypeName>::value_type()
+                }
+            }
+
+            impl ValidateNapiValue for crate::$name {
+                unsafe fn validate(env: napi_env, napi_val: napi_value) -> Result<napi_value> {
+                    <$name as ValidateNapiValue>::validate(env, napi_val)
+                }
+            }
+
+            impl FromNapiValue for crate::$name {
+                unsafe fn from_napi_value(env: napi_env, napi_val: napi_value) -> Result<Self> {
+                    Ok(match <$name as FromNapiValue>::from_napi_value(env, napi_val)? {
+                        $($name::$variant => crate::$name::$variant),+
+                    })
+                }
+            }
+
+            impl ToNapiValue for crate::$name {
+                unsafe fn to_napi_value(env: napi_env, val: Self) -> Result<napi_value> {
+                    let val = match val {
+                        $(crate::$name::$variant => $name::$variant),+
+                    };
+                    <$name as ToNapiValue>::to_napi_value(env, val)
+                }
+            }
+        };
+    };
+}
+
+navigator_enum! {
+    #[comment = "Raspberry Pi version, `Auto` (the default) detects it from the device tree."]
+    enum Raspberry {
+        Pi4,
+        Pi5,
+        Auto,
+    }
+}
+
+impl Raspberry {
+    /// Replaces `Auto` with the `detected` version.
+    fn resolve(self, detected: &DetectedHardware) -> Self {
+        match self {
+            Raspberry::Auto => detected.raspberry_pi_version.clone(),
+            version => version,
+        }
+    }
 }
 
 impl From<Raspberry> for navigator_rs::PiVersion {
     fn from(item: Raspberry) -> Self {
-        match item {
+        match item.resolve(&DETECTED_HARDWARE) {
             Raspberry::Pi4 => navigator_rs::PiVersion::Pi4,
             Raspberry::Pi5 => navigator_rs::PiVersion::Pi5,
+            Raspberry::Auto => unreachable!("Detection always resolves a version"),
         }
     }
 }
-#[cpy_enum]
-#[comment = "Navigator version."]
-enum NavigatorVersion {
-    Version1,
-    Version2,
+navigator_enum! {
+    #[comment = "Navigator version, `Auto` (the default) detects it from the board's barometer."]
+    enum NavigatorVersion {
+        Version1,
+        Version2,
+        Auto,
+    }
+}
+
+impl NavigatorVersion {
+    /// Replaces `Auto` with the `detected` version.
+    fn resolve(self, detected: &DetectedHardware) -> Self {
+        match self {
+            NavigatorVersion::Auto => detected.navigator_version.clone(),
+            version => version,
+        }
+    }
 }
 
 impl From<NavigatorVersion> for navigator_rs::NavigatorVersion {
     fn from(item: NavigatorVersion) -> Self {
-        match item {
+        match item.resolve(&DETECTED_HARDWARE) {
             NavigatorVersion::Version1 => navigator_rs::NavigatorVersion::V1,
             NavigatorVersion::Version2 => navigator_rs::NavigatorVersion::V2,
+            NavigatorVersion::Auto => unreachable!("Detection always resolves a version"),
+        }
+    }
+}
+
+#[cpy_struct]
+#[comment = "Hardware found by the detection used for the `Auto` versions. When `raspberry_pi_detected` or \
+    `navigator_detected` is false, the version couldn't be identified and falls back to `Pi4` or `Version1`."]
+struct DetectedHardware {
+    raspberry_pi_version: Raspberry,
+    navigator_version: NavigatorVersion,
+    raspberry_pi_detected: bool,
+    navigator_detected: bool,
+}
+
+impl From<detect::Detection> for DetectedHardware {
+    fn from(detection: detect::Detection) -> Self {
+        Self {
+            raspberry_pi_detected: detection.raspberry_pi_version.is_some(),
+            navigator_detected: detection.navigator_version.is_some(),
+            raspberry_pi_version: detection.raspberry_pi_version.unwrap_or(Raspberry::Pi4),
+            navigator_version: detection
+                .navigator_version
+                .unwrap_or(NavigatorVersion::Version1),
         }
     }
 }
 
+lazy_static! {
+    // Detected once, on the first use of an `Auto` version by the process owning the hardware
+    static ref DETECTED_HARDWARE: DetectedHardware = detect::detect(&detect::root()).into();
+}
+
+navigator_enum! {
+    #[comment = "Board mounting rotations, following ArduPilot's list (AHRS_ORIENTATION)."]
+    enum Rotation {
+        NoRotation,
+        Yaw45,
+        Yaw90,
+        Yaw135,
+        Yaw180,
+        Yaw225,
+        Yaw270,
+        Yaw315,
+        Roll180,
+        Roll180Yaw45,
+        Roll180Yaw90,
+        Roll180Yaw135,
+        Pitch180,
+        Roll180Yaw225,
+        Roll180Yaw270,
+        Roll180Yaw315,
+        Roll90,
+        Roll90Yaw45,
+        Roll90Yaw90,
+        Roll90Yaw135,
+        Roll270,
+        Roll270Yaw45,
+        Roll270Yaw90,
+        Roll270Yaw135,
+        Pitch90,
+        Pitch270,
+        Pitch180Yaw90,
+        Pitch180Yaw270,
+        Roll90Pitch90,
+        Roll180Pitch90,
+        Roll270Pitch90,
+        Roll90Pitch180,
+        Roll270Pitch180,
+        Roll90Pitch270,
+        Roll180Pitch270,
+        Roll270Pitch270,
+        Roll90Pitch180Yaw90,
+        Roll90Yaw270,
+        Roll90Pitch68Yaw293,
+        Pitch315,
+        Roll90Pitch315,
+        Pitch7,
+        Roll45,
+        Roll315,
+    }
+}
+
+impl From<Rotation> for orientation::Matrix {
+    fn from(item: Rotation) -> Self {
+        // Roll, pitch and yaw angles in degrees
+        let (roll, pitch, yaw) = match item {
+            Rotation::NoRotation => (0.0, 0.0, 0.0),
+            Rotation::Yaw45 => (0.0, 0.0, 45.0),
+            Rotation::Yaw90 => (0.0, 0.0, 90.0),
+            Rotation::Yaw135 => (0.0, 0.0, 135.0),
+            Rotation::Yaw180 => (0.0, 0.0, 180.0),
+            Rotation::Yaw225 => (0.0, 0.0, 225.0),
+            Rotation::Yaw270 => (0.0, 0.0, 270.0),
+            Rotation::Yaw315 => (0.0, 0.0, 315.0),
+            Rotation::Roll180 => (180.0, 0.0, 0.0),
+            Rotation::Roll180Yaw45 => (180.0, 0.0, 45.0),
+            Rotation::Roll180Yaw90 => (180.0, 0.0, 90.0),
+            Rotation::Roll180Yaw135 => (180.0, 0.0, 135.0),
+            Rotation::Pitch180 => (0.0, 180.0, 0.0),
+            Rotation::Roll180Yaw225 => (180.0, 0.0, 225.0),
+            Rotation::Roll180Yaw270 => (180.0, 0.0, 270.0),
+            Rotation::Roll180Yaw315 => (180.0, 0.0, 315.0),
+            Rotation::Roll90 => (90.0, 0.0, 0.0),
+            Rotation::Roll90Yaw45 => (90.0, 0.0, 45.0),
+            Rotation::Roll90Yaw90 => (90.0, 0.0, 90.0),
+            Rotation::Roll90Yaw135 => (90.0, 0.0, 135.0),
+            Rotation::Roll270 => (270.0, 0.0, 0.0),
+            Rotation::Roll270Yaw45 => (270.0, 0.0, 45.0),
+            Rotation::Roll270Yaw90 => (270.0, 0.0, 90.0),
+            Rotation::Roll270Yaw135 => (270.0, 0.0, 135.0),
+            Rotation::Pitch90 => (0.0, 90.0, 0.0),
+            Rotation::Pitch270 => (0.0, 270.0, 0.0),
+            Rotation::Pitch180Yaw90 => (0.0, 180.0, 90.0),
+            Rotation::Pitch180Yaw270 => (0.0, 180.0, 270.0),
+            Rotation::Roll90Pitch90 => (90.0, 90.0, 0.0),
+            Rotation::Roll180Pitch90 => (180.0, 90.0, 0.0),
+            Rotation::Roll270Pitch90 => (270.0, 90.0, 0.0),
+            Rotation::Roll90Pitch180 => (90.0, 180.0, 0.0),
+            Rotation::Roll270Pitch180 => (270.0, 180.0, 0.0),
+            Rotation::Roll90Pitch270 => (90.0, 270.0, 0.0),
+            Rotation::Roll180Pitch270 => (180.0, 270.0, 0.0),
+            Rotation::Roll270Pitch270 => (270.0, 270.0, 0.0),
+            Rotation::Roll90Pitch180Yaw90 => (90.0, 180.0, 90.0),
+            Rotation::Roll90Yaw270 => (90.0, 0.0, 270.0),
+            Rotation::Roll90Pitch68Yaw293 => (90.0, 68.8, 293.3),
+            Rotation::Pitch315 => (0.0, 315.0, 0.0),
+            Rotation::Roll90Pitch315 => (90.0, 315.0, 0.0),
+            Rotation::Pitch7 => (0.0, 7.0, 0.0),
+            Rotation::Roll45 => (45.0, 0.0, 0.0),
+            Rotation::Roll315 => (315.0, 0.0, 0.0),
+        };
+        orientation::from_euler(roll, pitch, yaw)
+    }
+}
+
+navigator_enum! {
+    #[comment = "Hardware access backend."]
+    enum Backend {
+        Local,
+        Daemon,
+    }
+}
+
 #[derive(Clone)]
 struct NavigatorBuilderManager {
     rgb_led_strip_size: usize,
     raspberry_pi_version: Raspberry,
     navigator_version: NavigatorVersion,
+    backend: Backend,
+    daemon_socket_path: String,
+    orientation: orientation::Matrix,
+    pwm_clock_hz: f32,
+    pwm_frequency_hz: Option<f32>,
+    pwm_phase_stagger: bool,
+    pwm_limits: [config::PwmLimits; backend::PWM_CHANNELS],
+    pwm_slew_rates: [f32; backend::PWM_CHANNELS],
+    channel_names: channels::ChannelNames,
+    // Filter of each `FilterChannel`
+    filters: std::collections::HashMap<usize, filters::FilterSpec>,
+    // Error of the file named by `NAVIGATOR_CONFIG`, reported by `build` until a file is applied
+    config_error: Option<String>,
+}
+
+impl Default for NavigatorBuilderManager {
+    fn default() -> Self {
+        Self {
+            rgb_led_strip_size: 1,
+            raspberry_pi_version: Raspberry::Auto,
+            navigator_version: NavigatorVersion::Auto,
+            backend: Backend::Local,
+            daemon_socket_path: daemon::DEFAULT_SOCKET_PATH.to_string(),
+            orientation: orientation::IDENTITY,
+            pwm_clock_hz: backend::DEFAULT_PWM_CLOCK_HZ,
+            pwm_frequency_hz: None,
+            pwm_phase_stagger: false,
+            pwm_limits: Default::default(),
+            pwm_slew_rates: Default::default(),
+            channel_names: Default::default(),
+            filters: Default::default(),
+            config_error: None,
+        }
+    }
+}
+
+impl NavigatorBuilderManager {
+    /// Default configuration, with the file named by `NAVIGATOR_CONFIG` applied. An invalid file
+    /// makes `build` fail, rather than running without its PWM limits.
+    fn from_env() -> Self {
+        let mut configuration = Self::default();
+        match config::from_env() {
+            Some(Ok(config)) => configuration.apply_config(&config),
+            Some(Err(error)) => {
+                configuration.config_error = Some(format!("Invalid ${}: {error}", config::PATH_ENV))
+            }
+            None => {}
+        }
+        configuration
+    }
+
+    /// Overrides the options set by `config`, and replaces all PWM limits, channel names and
+    /// filters with its own. Clears the error of an invalid `NAVIGATOR_CONFIG` file.
+    fn apply_config(&mut self, config: &config::Config) {
+        self.config_error = None;
+        if let Some(version) = &config.navigator_version {
+            self.navigator_version = version.clone();
+        }
+        if let Some(version) = &config.raspberry_pi_version {
+            self.raspberry_pi_version = version.clone();
+        }
+        if let Some(size) = config.rgb_led_strip_size {
+            self.rgb_led_strip_size = size;
+        }
+        if let Some(orientation) = config.orientation {
+            self.orientation = orientation;
+        }
+        if let Some(clock) = config.pwm_clock_hz {
+            self.pwm_clock_hz = clock;
+        }
+        if let Some(freq) = config.pwm_frequency_hz {
+            self.pwm_frequency_hz = Some(freq);
+        }
+        if let Some(enabled) = config.pwm_phase_stagger {
+            self.pwm_phase_stagger = enabled;
+        }
+        self.pwm_limits = Default::default();
+        for output in config.pwm.values() {
+            self.pwm_limits[output.channel] = output.limits;
+        }
+        self.channel_names = channels::ChannelNames::from_config(config);
+        self.filters = config.filters.clone().into_iter().collect();
+    }
+
+    /// Creates the backend selected by this configuration, fails if the hardware or the daemon
+    /// can't be accessed, or if the `NAVIGATOR_CONFIG` file is invalid.
+    fn build(&self) -> Result<Box<dyn NavigatorBackend>, String> {
+        if let Some(error) = &self.config_error {
+            return Err(error.clone());
+        }
+        let build = || {
+            let mut navigator = self.build_backend()?;
+            // Configuring the PWM frequency makes this instance the one driving the outputs
+            if let Some(freq) = self.pwm_frequency_hz {
+                navigator.claim_pwm()?;
+                navigator.set_pwm_frequency(freq)?;
+            }
+            Ok(navigator)
+        };
+        // navigator-rs panics when the hardware can't be initialized
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(build))
+            .unwrap_or_else(|error| Err(panic_message(error)))
+    }
+
+    fn build_backend(&self) -> Result<Box<dyn NavigatorBackend>, String> {
+        Ok(match self.backend {
+            Backend::Local => Box::new(
+                backend::LocalNavigator::new(
+                    backend::HardwareLock::acquire()?,
+                    self.raspberry_pi_version.clone().into(),
+                    navigator_rs::Navigator::create()
+                        .with_rgb_led_strip_size(self.rgb_led_strip_size)
+                        .with_navigator(self.navigator_version.clone().into()),
+                )?
+                .with_pwm_clock_hz(self.pwm_clock_hz),
+            ),
+            Backend::Daemon => Box::new(
+                daemon::DaemonClient::connect(&self.daemon_socket_path).map_err(|error| {
+                    format!(
+                        "Failed to connect to navigator daemon at {}: {error}",
+                        self.daemon_socket_path
+                    )
+                })?,
+            ),
+        })
+    }
 }
 
 lazy_static! {
     static ref NAVIGATORBUILDER: Mutex<NavigatorBuilderManager> =
-        Mutex::new(NavigatorBuilderManager {
-            rgb_led_strip_size: 1,
-            raspberry_pi_version: Raspberry::Pi4,
-            navigator_version: NavigatorVersion::Version1,
-        });
+        Mutex::new(NavigatorBuilderManager::from_env());
 }
 
 macro_rules! with_navigator_builder {
@@ -56,6 +438,19 @@ macro_rules! with_navigator_builder {
     };
 }
 
+/// Changes the configuration of the default instance, applied right away if it's running.
+fn configure<T>(change: impl FnOnce(&mut NavigatorBuilderManager) -> T) -> T {
+    let (result, configuration) = {
+        let mut builder = with_navigator_builder!();
+        let result = change(&mut builder);
+        (result, builder.clone())
+    };
+    allow_threads(|| {
+        NAVIGATOR.with_instance(|instance| instance.reconfigure(&configuration));
+    });
+    result
+}
+
 #[cpy_fn]
 #[comment_c = "Sets the size of the navigator led strip (1 is the default), should be called before `init`."]
 #[comment_py = "Sets the size of the navigator led strip (1 is the default), should be called before `init`.\n
@@ -68,51 +463,331 @@ fn set_rgb_led_strip_size(size: usize) {
 }
 
 #[cpy_fn]
-#[comment = "Sets the navigator version."]
+#[comment = "Sets the navigator version, `Auto` (the default) detects it."]
 fn set_navigator_version(version: NavigatorVersion) {
     with_navigator_builder!().navigator_version = version;
 }
 
 #[cpy_fn]
-#[comment = "Sets the raspberry pi version."]
+#[comment = "Sets the raspberry pi version, `Auto` (the default) detects it."]
 fn set_raspberry_pi_version(version: Raspberry) {
     with_navigator_builder!().raspberry_pi_version = version;
 }
 
-struct NavigatorManager {
-    navigator: navigator_rs::Navigator,
+#[cpy_fn]
+#[comment_c = "Sets the frequency of the clock connected to the PWM chip (PCA9685) EXTCLK pin, should be called before `init`."]
+#[comment_py = "Sets the frequency of the clock connected to the PWM chip (PCA9685) EXTCLK pin, should be called before `init`.\n
+    The Navigator board uses a 24.576 MHz clock (the default), this is only needed for modified boards.\n
+    Args:\n
+        clock (float32): The external clock frequency [Hz].\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_pwm_clock_hz(25_000_000)\n
+        >>> navigator.init()"]
+fn set_pwm_clock_hz(clock: f32) {
+    with_navigator_builder!().pwm_clock_hz = clock;
 }
 
-lazy_static! {
-    static ref NAVIGATOR: Mutex<Option<NavigatorManager>> = Mutex::new(None);
+#[cpy_fn]
+#[comment_c = "Sets how the board is mounted in the vehicle, applied to the accelerometer, gyroscope and magnetometer readings."]
+#[comment_py = "Sets how the board is mounted in the vehicle, applied to the accelerometer, gyroscope and magnetometer readings.\n
+    The rotations follow ArduPilot's AHRS_ORIENTATION list, and take effect immediately.\n
+    Args:\n
+        rotation (:py:class:`Rotation`): The board rotation relative to the vehicle.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import Rotation\n
+        >>> navigator.set_board_orientation(Rotation.Roll180Yaw90)"]
+fn set_board_orientation(rotation: Rotation) {
+    configure(|builder| builder.orientation = rotation.into());
+}
+
+#[cpy_fn_c]
+#[comment = "Sets how the board is mounted in the vehicle with a custom rotation matrix, as 9 row-major values."]
+fn set_board_orientation_matrix_c(matrix: *const f32) {
+    let values = unsafe { slice_from_c(matrix, 9) };
+    let mut orientation = orientation::IDENTITY;
+    for (row, values) in orientation.iter_mut().zip(values.chunks(3)) {
+        row.copy_from_slice(values);
+    }
+    configure(|builder| builder.orientation = orientation);
+}
+
+#[cpy_fn_py]
+#[comment = "Sets how the board is mounted in the vehicle with a custom rotation matrix.\n
+    Same as :py:func:`set_board_orientation`, for mountings not covered by :py:class:`Rotation`.\n
+    Args:\n
+        matrix ([[float32, float32, float32], ...]): 3x3 matrix that rotates board axes into vehicle axes.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_board_orientation_matrix([[0, 1, 0], [1, 0, 0], [0, 0, -1]])"]
+fn set_board_orientation_matrix_py(matrix: [[f32; 3]; 3]) {
+    configure(|builder| builder.orientation = matrix);
+}
+
+#[cpy_fn_c]
+#[comment = "Selects how the hardware is accessed, should be called before `init`. With `Backend::Daemon`, \
+    every call is forwarded to `navigatord` through the Unix socket `socket_path` (`/run/navigator.sock` if null)."]
+fn set_backend_c(backend: Backend, socket_path: *const std::os::raw::c_char) {
+    let mut builder = with_navigator_builder!();
+    builder.backend = backend;
+    builder.daemon_socket_path = match socket_path.is_null() {
+        true => daemon::DEFAULT_SOCKET_PATH.to_string(),
+        false => unsafe { string_from_c(socket_path) },
+    };
+}
+
+#[cpy_fn_py]
+#[comment = "Selects how the hardware is accessed, should be called before `init`.\n
+    Only one process can access the Navigator hardware at a time. With `Backend.Daemon`, every call
+    is forwarded to the `navigatord` broker, allowing multiple processes to share the board.\n
+    Args:\n
+        backend (:py:class:`Backend`): `Backend.Local` for direct access, `Backend.Daemon` to use `navigatord`.\n
+        socket_path (str, optional): Daemon's Unix socket (`/run/navigator.sock` by default).\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import Backend\n
+        >>> navigator.set_backend(Backend.Daemon, \"/run/navigator.sock\")\n
+        >>> navigator.init()"]
+fn set_backend_py(backend: Backend, socket_path: Option<String>) {
+    let mut builder = with_navigator_builder!();
+    builder.backend = backend;
+    builder.daemon_socket_path =
+        socket_path.unwrap_or_else(|| daemon::DEFAULT_SOCKET_PATH.to_string());
+}
+
+/// Copies a C string argument, which must not be null.
+///
+/// # Safety
+///
+/// `value` must point to a NUL-terminated string.
+#[cfg(not(feature = "python"))]
+unsafe fn string_from_c(value: *const std::os::raw::c_char) -> String {
+    assert!(!value.is_null());
+    std::ffi::CStr::from_ptr(value)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Borrows the `length` values of a C array argument, which must not be null.
+///
+/// # Safety
+///
+/// `values` must point to `length` initialized values, left unchanged while the slice is used.
+#[cfg(not(feature = "python"))]
+unsafe fn slice_from_c<'a, T>(values: *const T, length: usize) -> &'a [T] {
+    assert!(!values.is_null());
+    std::slice::from_raw_parts(values, length)
+}
+
+/// Borrows the `length` values of a C array argument to be written, which must not be null.
+///
+/// # Safety
+///
+/// `values` must point to `length` initialized values, not accessed elsewhere while the slice
+/// is used.
+#[cfg(not(feature = "python"))]
+unsafe fn slice_from_c_mut<'a, T>(values: *mut T, length: usize) -> &'a mut [T] {
+    assert!(!values.is_null());
+    std::slice::from_raw_parts_mut(values, length)
+}
+
+/// Copies `value` to the `length` bytes of `buffer`, truncated and NUL-terminated like
+/// `snprintf`. Returns the length of `value`, without the NUL.
+///
+/// # Safety
+///
+/// `buffer` must be null or point to `length` writable bytes.
+#[cfg(not(feature = "python"))]
+unsafe fn copy_to_c(value: &str, buffer: *mut std::os::raw::c_char, length: usize) -> usize {
+    if !buffer.is_null() && length > 0 {
+        let copied = value.len().min(length - 1);
+        std::ptr::copy_nonoverlapping(value.as_ptr().cast(), buffer, copied);
+        *buffer.add(copied) = 0;
+    }
+    value.len()
 }
 
-impl NavigatorManager {
-    fn get_instance() -> &'static Mutex<Option<Self>> {
-        if NAVIGATOR.lock().unwrap().is_none() {
-            let configuration = with_navigator_builder!().clone();
-            let navigator = navigator_rs::Navigator::create()
-                .with_rgb_led_strip_size(configuration.rgb_led_strip_size)
-                .with_navigator(configuration.navigator_version.into())
-                .with_pi(configuration.raspberry_pi_version.into())
-                .build();
-            *NAVIGATOR.lock().unwrap() = Some(NavigatorManager { navigator });
+#[cpy_fn_c]
+#[comment = "Applies the TOML configuration file at `path` (board and Raspberry Pi versions, led strip size, \
+    orientation, PWM clock, frequency and limits, channel names, filters), should be called before `init`. Returns \
+    false if it can't be read or is invalid, see `navigator_last_error`, the configuration is unchanged then."]
+fn load_config_c(path: *const std::os::raw::c_char) -> bool {
+    let path = unsafe { string_from_c(path) };
+    match config::load(std::path::Path::new(&path)) {
+        Ok(config) => {
+            configure(|builder| builder.apply_config(&config));
+            true
+        }
+        Err(error) => {
+            handle::set_last_error(error.to_string());
+            false
         }
-        &NAVIGATOR
     }
 }
 
+#[cpy_fn_py]
+#[comment = "Applies a TOML configuration file, should be called before `init`.\n
+    The file sets the board and Raspberry Pi versions, the led strip size, the board orientation,
+    the PWM clock and frequency, names PWM and ADC channels, with duty cycle limits for the PWM
+    outputs, and attaches filters to sensor channels. The file named by the `NAVIGATOR_CONFIG` environment variable is loaded by default.\n
+    Args:\n
+        path (str): Path of the configuration file.\n
+    Raises:\n
+        OSError: The file can't be read.\n
+        ValueError: The file is invalid, the message lists every invalid option.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.load_config(\"/etc/navigator.toml\")\n
+        >>> navigator.init()"]
+fn load_config_py(path: std::path::PathBuf) -> pyo3::PyResult<()> {
+    let config = config::load(&path)?;
+    configure(|builder| builder.apply_config(&config));
+    Ok(())
+}
+
+lazy_static! {
+    // The default instance, created by the first call using it and closed by `deinit`
+    static ref NAVIGATOR: NavigatorHandle = NavigatorHandle::closed();
+}
+
 macro_rules! with_navigator {
     () => {
-        NavigatorManager::get_instance()
-            .lock()
-            .unwrap()
+        NAVIGATOR
+            .lock_or_open(|| with_navigator_builder!().clone())
             .as_mut()
             .unwrap()
-            .navigator
     };
 }
 
+/// Runs `f` on the default instance, for the bindings that can't use `with_navigator!`. Called
+/// from plain Rust, so a failed access unwinds to the caller instead of through an `extern "C"`
+/// function.
+#[cfg(any(feature = "python", feature = "node", feature = "lua"))]
+pub(crate) fn with_default_instance<T>(f: impl FnOnce(&mut handle::Instance) -> T) -> T {
+    f(with_navigator!())
+}
+
+#[cfg(feature = "python")]
+thread_local! {
+    // Set on the threads started by `spawn_worker`, which never hold the GIL
+    static WORKER_THREAD: std::cell::Cell<bool> = std::cell::Cell::new(false);
+}
+
+/// Starts a Rust thread calling the bindings, which access the hardware without taking the GIL.
+#[cfg(feature = "python")]
+fn spawn_worker<F>(f: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    std::thread::spawn(move || {
+        WORKER_THREAD.with(|worker| worker.set(true));
+        f()
+    });
+}
+
+/// Releases the GIL while accessing the hardware, so other Python threads can run meanwhile.
+/// Python threads already hold it, so taking it is free, worker threads run `f` directly.
+#[cfg(feature = "python")]
+fn allow_threads<T, F>(f: F) -> T
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    match WORKER_THREAD.with(std::cell::Cell::get) {
+        true => f(),
+        false => pyo3::Python::with_gil(|py| py.allow_threads(f)),
+    }
+}
+
+/// Without Python, runs the hardware access of a C function. A failed hardware or daemon access
+/// panics like navigator-rs, which must not unwind into C, so it returns a failure value instead,
+/// with the error kept for `navigator_last_error`.
+#[cfg(not(feature = "python"))]
+fn allow_threads<T, F>(f: F) -> T
+where
+    T: Failure,
+    F: FnOnce() -> T,
+{
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f))
+        .unwrap_or_else(|error| T::failure(panic_message(error)))
+}
+
+/// Value returned by a C function whose hardware or daemon access failed.
+#[cfg(not(feature = "python"))]
+trait Failure {
+    /// Returns the failure of `error`, reported by the value itself or by `navigator_last_error`.
+    fn failure(error: String) -> Self;
+}
+
+#[cfg(not(feature = "python"))]
+impl<T> Failure for Result<T, String> {
+    fn failure(error: String) -> Self {
+        Err(error)
+    }
+}
+
+/// Implements [`Failure`] for types without room for the error, which return `$value`.
+#[cfg(not(feature = "python"))]
+macro_rules! impl_failure {
+    ($($type:ty => $value:expr),* $(,)?) => {
+        $(
+            impl Failure for $type {
+                fn failure(error: String) -> Self {
+                    handle::set_last_error(error);
+                    $value
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(not(feature = "python"))]
+impl_failure!(
+    () => (),
+    bool => false,
+    u8 => 0,
+    f32 => f32::NAN,
+    // Values of the 4 ADC channels
+    Vec<f32> => vec![f32::NAN; 4],
+    AxisData => AxisData::NAN,
+    SensorData => SensorData {
+        accelerometer: AxisData::NAN,
+        gyro: AxisData::NAN,
+        magnetometer: AxisData::NAN,
+        pressure: f32::NAN,
+        temperature: f32::NAN,
+        adc: [f32::NAN; 4],
+        leak: false,
+        timestamp: timestamp_us(),
+    },
+    backend::PwmState => backend::PwmState {
+        enabled: false,
+        frequency: f32::NAN,
+        prescale: 0,
+        duty_cycles: [f32::NAN; backend::PWM_CHANNELS],
+    },
+    CheckedValue => CheckedValue::invalid(),
+    CheckedAxisData => CheckedAxisData::invalid(),
+    // Nothing detected
+    DetectedHardware => detect::Detection::default().into(),
+    VibrationData => VibrationData::new(&vibration::VibrationMonitor::new()),
+    Vec<SensorHealth> => vec![],
+);
+
+/// Builds a float32 NumPy array with `shape` from the values returned by `read`.
+#[cfg(feature = "numpy")]
+fn into_numpy_array<F>(py: pyo3::Python, shape: &[usize], read: F) -> pyo3::PyResult<pyo3::PyObject>
+where
+    F: FnOnce() -> Vec<f32> + Send,
+{
+    use pyo3::ToPyObject;
+    let values = allow_threads(read);
+    Ok(numpy::PyArray1::from_vec(py, values)
+        .reshape(shape)?
+        .to_object(py))
+}
+
 macro_rules! impl_from_enum {
     ($from:ty, $to:ty, $($variant:ident),+ $(,)?) => {
         impl From<$from> for $to {
@@ -141,21 +816,144 @@ impl From<navigator_rs::AxisData> for AxisData {
     }
 }
 
-#[cpy_enum]
-#[comment = "Available ADC channels to read from."]
-enum AdcChannel {
-    Ch0,
-    Ch1,
-    Ch2,
-    Ch3,
+impl AxisData {
+    /// Value of a failed or invalid reading.
+    const NAN: Self = Self {
+        x: f32::NAN,
+        y: f32::NAN,
+        z: f32::NAN,
+    };
+}
+
+navigator_enum! {
+    #[comment = "Available PWM channels, `All` selects the 16 channels at once. Functions taking a channel also \
+    accept its index (0 for `Ch1`, 16 for `All`), other values are rejected."]
+    enum PwmChannel {
+        Ch1,
+        Ch2,
+        Ch3,
+        Ch4,
+        Ch5,
+        Ch6,
+        Ch7,
+        Ch8,
+        Ch9,
+        Ch10,
+        Ch11,
+        Ch12,
+        Ch13,
+        Ch14,
+        Ch15,
+        Ch16,
+        All,
+    }
+}
+
+navigator_enum! {
+    #[comment = "Output driver of the PWM channels."]
+    enum PwmOutputMode {
+        TotemPole,
+        OpenDrain,
+    }
+}
+
+/// PWM channel argument, a :py:class:`PwmChannel` or its index (0 for `Ch1`, 16 for `All`).
+#[cfg(feature = "python")]
+#[repr(transparent)]
+pub struct PwmChannelArg(usize);
+
+#[cfg(feature = "python")]
+impl<'source> pyo3::FromPyObject<'source> for PwmChannelArg {
+    fn extract(object: &'source pyo3::PyAny) -> pyo3::PyResult<Self> {
+        let index = match object.extract::<PwmChannel>() {
+            Ok(channel) => channel as usize,
+            Err(_) => object.extract::<usize>()?,
+        };
+        if index > PwmChannel::All as usize {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid PWM channel: {index}, expected a PwmChannel or an index from 0 to 16"
+            )));
+        }
+        Ok(Self(index))
+    }
+}
+
+/// PWM channel argument, the index of a `PwmChannel` (0 for `Ch1`, 16 for `All`).
+#[cfg(not(feature = "python"))]
+pub type PwmChannelArg = usize;
+
+#[cfg(feature = "python")]
+fn pwm_channel_index(channel: PwmChannelArg) -> usize {
+    channel.0
+}
+
+#[cfg(not(feature = "python"))]
+fn pwm_channel_index(channel: PwmChannelArg) -> usize {
+    channel
+}
+
+/// Channel indexes selected by a `PwmChannel` index, with `All` expanded.
+fn pwm_channels(index: usize) -> Result<Vec<usize>, String> {
+    if index == PwmChannel::All as usize {
+        Ok((0..backend::PWM_CHANNELS).collect())
+    } else if index < backend::PWM_CHANNELS {
+        Ok(vec![index])
+    } else {
+        Err(format!(
+            "Invalid PWM channel: {index}, expected an index from 0 to 15, or 16 for all channels."
+        ))
+    }
+}
+
+navigator_enum! {
+    #[comment = "Available ADC channels to read from."]
+    enum AdcChannel {
+        Ch0,
+        Ch1,
+        Ch2,
+        Ch3,
+    }
+}
+
+navigator_enum! {
+    #[comment = "Onboard user-controllable LEDs."]
+    enum UserLed {
+        Led1,
+        Led2,
+        Led3,
+    }
+}
+
+navigator_enum! {
+    #[comment = "Full-scale ranges of the ICM20689 accelerometer, in g (±8g by default)."]
+    enum AccelRange {
+        G2,
+        G4,
+        G8,
+        G16,
+    }
+}
+
+navigator_enum! {
+    #[comment = "Full-scale ranges of the ICM20689 gyroscope, in degrees per second (±2000dps by default)."]
+    enum GyroRange {
+        Dps250,
+        Dps500,
+        Dps1000,
+        Dps2000,
+    }
 }
 
-#[cpy_enum]
-#[comment = "Onboard user-controllable LEDs."]
-enum UserLed {
-    Led1,
-    Led2,
-    Led3,
+navigator_enum! {
+    #[comment = "Bandwidths of the ICM20689 digital low-pass filter, applied to both the accelerometer and the gyroscope."]
+    enum ImuDlpf {
+        Hz176,
+        Hz92,
+        Hz41,
+        Hz20,
+        Hz10,
+        Hz5,
+    }
 }
 
 #[cpy_struct]
@@ -166,6 +964,123 @@ struct AxisData {
     z: f32,
 }
 
+#[cpy_struct]
+#[comment = "Accelerometer and gyroscope measurements of a sample taken from the IMU FIFO."]
+struct ImuSample {
+    accelerometer: AxisData,
+    gyro: AxisData,
+}
+
+#[cpy_struct]
+#[comment = "Measurements from all sensors, taken in a single pass. `timestamp` is the time of the sample in \
+    microseconds since the UNIX epoch."]
+struct SensorData {
+    accelerometer: AxisData,
+    gyro: AxisData,
+    magnetometer: AxisData,
+    pressure: f32,
+    temperature: f32,
+    adc: [f32; 4],
+    leak: bool,
+    timestamp: u64,
+}
+
+/// Time since the UNIX epoch [µs].
+fn timestamp_us() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+impl SensorData {
+    /// Converts a reading taken at `timestamp` [µs], with the axes rotated to the vehicle frame.
+    fn new(data: navigator_rs::SensorData, rotation: &orientation::Matrix, timestamp: u64) -> Self {
+        let mut adc = [0.0; 4];
+        adc.iter_mut()
+            .zip(data.adc)
+            .for_each(|(channel, value)| *channel = value);
+
+        Self {
+            accelerometer: orientation::rotate(rotation, data.accelerometer).into(),
+            gyro: orientation::rotate(rotation, data.gyro).into(),
+            magnetometer: orientation::rotate(rotation, data.magnetometer).into(),
+            pressure: data.pressure,
+            temperature: data.temperature,
+            adc,
+            leak: data.leak,
+            timestamp,
+        }
+    }
+}
+
+#[cpy_struct]
+#[comment = "ArduPilot-style vibration levels and accelerometer clipping count. `vibration` is the RMS of the \
+    high-passed acceleration of each axis in [m/s²], `clipping` the number of samples beyond the accelerometer range."]
+struct VibrationData {
+    vibration: AxisData,
+    clipping: u32,
+}
+
+impl VibrationData {
+    fn new(monitor: &vibration::VibrationMonitor) -> Self {
+        let [x, y, z] = monitor.vibration();
+        Self {
+            vibration: AxisData { x, y, z },
+            clipping: monitor.clipping(),
+        }
+    }
+}
+
+#[cpy_struct]
+#[comment = "State of the PWM outputs, as written to the PCA9685. `frequency` is the achieved frequency in [Hz] \
+    after the prescale rounding, and `duty_cycles` the achieved duty cycle (0.0 : 1.0) of each channel."]
+struct PwmState {
+    enabled: bool,
+    frequency: f32,
+    prescale: u8,
+    duty_cycles: [f32; 16],
+}
+
+impl From<backend::PwmState> for PwmState {
+    fn from(state: backend::PwmState) -> Self {
+        Self {
+            enabled: state.enabled,
+            frequency: state.frequency,
+            prescale: state.prescale,
+            duty_cycles: state.duty_cycles,
+        }
+    }
+}
+
+#[cfg(feature = "numpy")]
+#[pyo3::pymethods]
+impl AxisData {
+    /// NumPy array interface over the x, y and z values, so `numpy.asarray(navigator.read_accel())`
+    /// is a read-only view of this object instead of a copy.
+    ///
+    /// The buffer protocol (`__getbuffer__`) isn't available to the stable ABI before Python 3.11.
+    #[getter]
+    fn __array_interface__<'py>(
+        slf: pyo3::PyRef<'py, Self>,
+        py: pyo3::Python<'py>,
+    ) -> pyo3::PyResult<&'py pyo3::types::PyDict> {
+        let typestr = if cfg!(target_endian = "little") {
+            "<f4"
+        } else {
+            ">f4"
+        };
+        // The struct is `#[repr(C)]`, so x, y and z are contiguous, the array keeps `slf` alive
+        let data = &slf.x as *const f32 as usize;
+        let interface = pyo3::types::PyDict::new(py);
+        interface.set_item("version", 3)?;
+        interface.set_item("shape", (3,))?;
+        interface.set_item("typestr", typestr)?;
+        interface.set_item("data", (data, true))?;
+        Ok(interface)
+    }
+}
+
 #[cpy_fn]
 #[comment_c = "Initializes the Navigator module with default settings (not necessary)."]
 #[comment_py = "Initializes the Navigator module with default settings (not necessary).\n
@@ -176,6 +1091,19 @@ fn init() {
     // Keep to avoid API break
 }
 
+#[cpy_fn]
+#[comment_c = "Releases the default instance, used by the free functions. It's created again by the next call."]
+#[comment_py = "Releases the default instance, used by the free functions. It's created again by the next call.\n
+    This stops the PWM ramps and releases the hardware, or the daemon connection, so the configuration
+    functions take effect again.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.read_temp()\n
+        >>> navigator.deinit()"]
+fn deinit() {
+    allow_threads(|| NAVIGATOR.close())
+}
+
 #[cpy_fn]
 #[comment_c = "Runs some tests on available sensors, then returns the result (not necessary)."]
 #[comment_py = "Runs some tests on available sensors, then returns the result (not necessary).\n
@@ -189,18 +1117,191 @@ fn self_test() -> bool {
     true
 }
 
-#[cpy_fn]
-#[comment_c = "Sets the state of the selected onboard LED."]
-#[comment_py = "Sets the state of the selected onboard LED.\n
-    Args:\n
-        select (:py:class:`UserLed`):  A pin to be selected.\n
-        state (bool): The desired output state. `True` -> ON, `False` -> OFF.\n
+/// Version of the C ABI, incremented when an exported function or type changes incompatibly.
+/// Compare it with `navigator_abi_version` to check the loaded library matches the header.
+pub const NAVIGATOR_ABI_VERSION: u32 = 1;
+
+/// Capability bit, the `navigatord` daemon backend is available.
+pub const NAVIGATOR_CAPABILITY_DAEMON: u32 = 1 << 0;
+/// Capability bit, the `navigator_*` handle functions are available.
+pub const NAVIGATOR_CAPABILITY_HANDLE: u32 = 1 << 1;
+/// Capability bit, the sensor filter functions are available.
+pub const NAVIGATOR_CAPABILITY_FILTERS: u32 = 1 << 2;
+/// Capability bit, the PWM slew-rate limit and ramp functions are available.
+pub const NAVIGATOR_CAPABILITY_PWM_RAMPS: u32 = 1 << 3;
+/// Capability bit, built with the `python` feature.
+pub const NAVIGATOR_CAPABILITY_PYTHON: u32 = 1 << 4;
+/// Capability bit, built with the `numpy` feature.
+pub const NAVIGATOR_CAPABILITY_NUMPY: u32 = 1 << 5;
+/// Capability bit, built with the `node` feature.
+pub const NAVIGATOR_CAPABILITY_NODE: u32 = 1 << 6;
+/// Capability bit, built with the `lua` feature.
+pub const NAVIGATOR_CAPABILITY_LUA: u32 = 1 << 7;
+
+// Versions as C strings, resolved at build time
+const LIB_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+const NAVIGATOR_RS_VERSION: &str = concat!(env!("NAVIGATOR_RS_VERSION"), "\0");
+
+#[cfg(any(feature = "python", feature = "node"))]
+fn without_nul(version: &str) -> String {
+    version.trim_end_matches('\0').to_string()
+}
+
+#[cpy_fn_c]
+#[comment = "Returns the library version, as a static NUL-terminated string (e.g. `0.1.1`)."]
+fn navigator_lib_version_c() -> *const std::os::raw::c_char {
+    LIB_VERSION.as_ptr().cast()
+}
+
+#[cpy_fn_py]
+#[comment = "Returns the library version.\n
+    Returns:\n
+        str: The version of the bluerobotics_navigator package (e.g. `0.1.1`).\n
     Examples:\n
         >>> import bluerobotics_navigator as navigator\n
-        >>> from bluerobotics_navigator import UserLed\n
-        >>> navigator.set_led(UserLed.Led1, True)"]
-fn set_led(select: UserLed, state: bool) {
-    with_navigator!().set_led(select.into(), state)
+        >>> print(navigator.navigator_lib_version())"]
+fn navigator_lib_version_py() -> String {
+    without_nul(LIB_VERSION)
+}
+
+#[cpy_fn_c]
+#[comment = "Returns the version of the navigator-rs crate used to access the hardware, as a static \
+    NUL-terminated string (`unknown` if it couldn't be found at build time)."]
+fn navigator_rs_version_c() -> *const std::os::raw::c_char {
+    NAVIGATOR_RS_VERSION.as_ptr().cast()
+}
+
+#[cpy_fn_py]
+#[comment = "Returns the version of the navigator-rs crate used to access the hardware.\n
+    Returns:\n
+        str: The navigator-rs version, `unknown` if it couldn't be found at build time.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> print(navigator.navigator_rs_version())"]
+fn navigator_rs_version_py() -> String {
+    without_nul(NAVIGATOR_RS_VERSION)
+}
+
+#[cpy_fn]
+#[comment_c = "Returns the ABI version of the loaded library, to be compared with `NAVIGATOR_ABI_VERSION`."]
+#[comment_py = "Returns the ABI version of the C library, incremented on incompatible changes.\n
+    Returns:\n
+        int: The ABI version.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> print(navigator.navigator_abi_version())"]
+fn navigator_abi_version() -> u32 {
+    NAVIGATOR_ABI_VERSION
+}
+
+#[cpy_fn]
+#[comment_c = "Returns the capabilities of the loaded library, as a bitmask of the `NAVIGATOR_CAPABILITY_*` values."]
+#[comment_py = "Returns the capabilities of the library, as a bitmask.\n
+    Bits: 0 daemon backend, 1 handle functions, 2 filters, 3 PWM ramps, 4 python, 5 numpy, 6 node, 7 lua.\n
+    Returns:\n
+        int: The capability bitmask.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> has_numpy = navigator.navigator_capabilities() & (1 << 5) != 0"]
+fn navigator_capabilities() -> u32 {
+    capabilities()
+}
+
+/// Capabilities of this build, see `navigator_capabilities`.
+fn capabilities() -> u32 {
+    let mut capabilities = NAVIGATOR_CAPABILITY_DAEMON
+        | NAVIGATOR_CAPABILITY_HANDLE
+        | NAVIGATOR_CAPABILITY_FILTERS
+        | NAVIGATOR_CAPABILITY_PWM_RAMPS;
+    if cfg!(feature = "python") {
+        capabilities |= NAVIGATOR_CAPABILITY_PYTHON;
+    }
+    if cfg!(feature = "numpy") {
+        capabilities |= NAVIGATOR_CAPABILITY_NUMPY;
+    }
+    if cfg!(feature = "node") {
+        capabilities |= NAVIGATOR_CAPABILITY_NODE;
+    }
+    if cfg!(feature = "lua") {
+        capabilities |= NAVIGATOR_CAPABILITY_LUA;
+    }
+    capabilities
+}
+
+#[cpy_struct]
+#[comment = "Board configuration of the default instance, as set by the configuration functions with `Auto` \
+    versions resolved. `daemon` is true when the hardware is accessed through `navigatord`."]
+struct BoardInfo {
+    navigator_version: NavigatorVersion,
+    raspberry_pi_version: Raspberry,
+    rgb_led_strip_size: usize,
+    daemon: bool,
+}
+
+#[cpy_fn]
+#[comment_c = "Returns the board configuration used by the default instance."]
+#[comment_py = "Returns the board configuration used by the default instance.\n
+    Returns:\n
+        :py:class:`BoardInfo`: Navigator and Raspberry Pi versions, LED strip size and backend.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> info = navigator.get_board_info()\n
+        >>> print(info.navigator_version, info.raspberry_pi_version)"]
+fn get_board_info() -> BoardInfo {
+    board_info(get_detected_hardware())
+}
+
+/// Board configuration of the default instance, with the `Auto` versions resolved from `detected`.
+fn board_info(detected: DetectedHardware) -> BoardInfo {
+    let builder = with_navigator_builder!().clone();
+    BoardInfo {
+        navigator_version: builder.navigator_version.resolve(&detected),
+        raspberry_pi_version: builder.raspberry_pi_version.resolve(&detected),
+        rgb_led_strip_size: builder.rgb_led_strip_size,
+        daemon: matches!(builder.backend, Backend::Daemon),
+    }
+}
+
+/// Versions found by the detection, of `navigatord` with `Backend::Daemon`.
+fn detected_hardware() -> DetectedHardware {
+    let daemon = matches!(with_navigator_builder!().backend, Backend::Daemon);
+    match daemon {
+        // Only the daemon accesses the hardware
+        true => with_navigator!().navigator.detected_hardware(),
+        false => DETECTED_HARDWARE.clone(),
+    }
+}
+
+#[cpy_fn]
+#[comment_c = "Returns the Raspberry Pi and Navigator versions found by the detection, used for the `Auto` versions. \
+    With `Backend::Daemon`, this is the detection of `navigatord`."]
+#[comment_py = "Returns the Raspberry Pi and Navigator versions found by the detection, used for the `Auto` versions.\n
+    The Raspberry Pi model is read from `/proc/device-tree/model`, and the Navigator version from the
+    barometer's chip ID. Set `NAVIGATOR_DETECTION_ROOT` to run it against another root directory.
+    With :py:attr:`Backend.Daemon`, the detection of `navigatord` is returned instead.\n
+    Returns:\n
+        :py:class:`DetectedHardware`: The detected versions, and whether each one was identified.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> hardware = navigator.get_detected_hardware()\n
+        >>> if not hardware.navigator_detected:\n
+        ...     navigator.set_navigator_version(navigator.NavigatorVersion.Version2)"]
+fn get_detected_hardware() -> DetectedHardware {
+    allow_threads(detected_hardware)
+}
+
+#[cpy_fn]
+#[comment_c = "Sets the state of the selected onboard LED."]
+#[comment_py = "Sets the state of the selected onboard LED.\n
+    Args:\n
+        select (:py:class:`UserLed`):  A pin to be selected.\n
+        state (bool): The desired output state. `True` -> ON, `False` -> OFF.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import UserLed\n
+        >>> navigator.set_led(UserLed.Led1, True)"]
+fn set_led(select: UserLed, state: bool) {
+    allow_threads(|| with_navigator!().navigator.set_led(select.into(), state))
 }
 
 #[cpy_fn]
@@ -215,7 +1316,7 @@ fn set_led(select: UserLed, state: bool) {
         >>> from bluerobotics_navigator import UserLed\n
         >>> led1_on = navigator.get_led(UserLed.Led1)"]
 fn get_led(select: UserLed) -> bool {
-    with_navigator!().get_led(select.into())
+    allow_threads(|| with_navigator!().navigator.get_led(select.into()))
 }
 
 #[cpy_fn]
@@ -228,7 +1329,7 @@ fn get_led(select: UserLed) -> bool {
         >>> from bluerobotics_navigator import UserLed\n
         >>> navigator.set_led_toggle(UserLed.Led1)"]
 fn set_led_toggle(select: UserLed) {
-    with_navigator!().set_led_toggle(select.into())
+    allow_threads(|| with_navigator!().navigator.set_led_toggle(select.into()))
 }
 
 #[cpy_fn]
@@ -240,9 +1341,11 @@ fn set_led_toggle(select: UserLed) {
         >>> import bluerobotics_navigator as navigator\n
         >>> navigator.set_led_all(True)"]
 fn set_led_all(state: bool) {
-    for led in [UserLed::Led1, UserLed::Led2, UserLed::Led3] {
-        with_navigator!().set_led(led.into(), state);
-    }
+    allow_threads(|| {
+        for led in [UserLed::Led1, UserLed::Led2, UserLed::Led3] {
+            with_navigator!().navigator.set_led(led.into(), state);
+        }
+    });
 }
 
 #[cpy_fn_c]
@@ -252,7 +1355,7 @@ fn set_neopixel_c(rgb_array: *const [u8; 3], length: usize) {
         assert!(!rgb_array.is_null());
         std::slice::from_raw_parts(rgb_array, length)
     };
-    with_navigator!().set_neopixel(array);
+    allow_threads(|| with_navigator!().navigator.set_neopixel(array));
 }
 
 #[cpy_fn_py]
@@ -264,7 +1367,7 @@ fn set_neopixel_c(rgb_array: *const [u8; 3], length: usize) {
         >>> import bluerobotics_navigator as navigator\n
         >>> navigator.set_neopixel([[100,0,0]])"]
 fn set_neopixel_py(rgb_array: Vec<[u8; 3]>) {
-    with_navigator!().set_neopixel(&rgb_array)
+    allow_threads(|| with_navigator!().navigator.set_neopixel(&rgb_array))
 }
 
 #[cpy_fn_c]
@@ -274,7 +1377,7 @@ fn set_neopixel_rgbw_c(rgb_array: *const [u8; 4], length: usize) {
         assert!(!rgb_array.is_null());
         std::slice::from_raw_parts(rgb_array, length)
     };
-    with_navigator!().set_neopixel_rgbw(array);
+    allow_threads(|| with_navigator!().navigator.set_neopixel_rgbw(array));
 }
 
 #[cpy_fn_py]
@@ -286,7 +1389,7 @@ fn set_neopixel_rgbw_c(rgb_array: *const [u8; 4], length: usize) {
         >>> import bluerobotics_navigator as navigator\n
         >>> navigator.set_neopixel([[100,0,0,128]])"]
 fn set_neopixel_rgbw_py(rgb_array: Vec<[u8; 4]>) {
-    with_navigator!().set_neopixel_rgbw(&rgb_array)
+    allow_threads(|| with_navigator!().navigator.set_neopixel_rgbw(&rgb_array))
 }
 
 #[cpy_fn_py]
@@ -298,21 +1401,30 @@ fn set_neopixel_rgbw_py(rgb_array: Vec<[u8; 4]>) {
         >>> import bluerobotics_navigator as navigator\n
         >>> adc_measurements = navigator.read_adc_all().channel"]
 fn read_adc_all_py() -> Vec<f32> {
-    with_navigator!().read_adc_all()
+    allow_threads(|| with_navigator!().read_adc_all())
 }
 
 #[cpy_fn_c]
 #[comment_c = "Reads the ADC channel values (from the ADS1115 chip)."]
 fn read_adc_all_c(adc_array: *mut f32, length: usize) {
-    let array = unsafe {
-        assert!(!adc_array.is_null());
-        std::slice::from_raw_parts_mut::<f32>(adc_array, length)
-    };
-
-    let values = with_navigator!().read_adc_all();
+    let array = unsafe { slice_from_c_mut(adc_array, length) };
+    let values = allow_threads(|| with_navigator!().read_adc_all());
     array[..length].copy_from_slice(&values[..length]);
 }
 
+#[cfg(feature = "numpy")]
+#[cpy_fn_py]
+#[comment = "Reads the ADC channel values (from the ADS1115 chip) into a NumPy array.\n
+    Same as :py:func:`read_adc_all`, requires the library to be built with the `numpy` feature.\n
+    Returns:\n
+        numpy.ndarray: float32 array with the measurements in [V].\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> adc_measurements = navigator.read_adc_all_np()"]
+fn read_adc_all_np_py(py: pyo3::Python) -> pyo3::PyResult<pyo3::PyObject> {
+    into_numpy_array(py, &[4], || with_navigator!().read_adc_all())
+}
+
 #[cpy_fn]
 #[comment_c = "Reads a specific ADC channel (from the ADS1115 chip)."]
 #[comment_py = "Reads a specific ADC channel (from the ADS1115 chip).\n\n
@@ -325,7 +1437,7 @@ fn read_adc_all_c(adc_array: *mut f32, length: usize) {
         >>> from bluerobotics_navigator import AdcChannel\n
         >>> adc1_measurement = navigator.read_adc(AdcChannel.Ch1)"]
 fn read_adc(channel: AdcChannel) -> f32 {
-    with_navigator!().read_adc(channel.into())
+    allow_threads(|| with_navigator!().read_adc(channel))
 }
 
 #[cpy_fn]
@@ -337,7 +1449,7 @@ fn read_adc(channel: AdcChannel) -> f32 {
         >>> import bluerobotics_navigator as navigator\n
         >>> air_pressure = navigator.read_pressure()"]
 fn read_pressure() -> f32 {
-    with_navigator!().read_pressure()
+    allow_threads(|| with_navigator!().read_pressure())
 }
 
 #[cpy_fn]
@@ -349,7 +1461,7 @@ fn read_pressure() -> f32 {
         >>> import bluerobotics_navigator as navigator\n
         >>> air_temperature = navigator.read_temperature()"]
 fn read_temp() -> f32 {
-    with_navigator!().read_temperature()
+    allow_threads(|| with_navigator!().read_temp())
 }
 
 #[cpy_fn]
@@ -361,7 +1473,7 @@ fn read_temp() -> f32 {
         >>> import bluerobotics_navigator as navigator\n
         >>> mag_field = navigator.read_mag()"]
 fn read_mag() -> AxisData {
-    with_navigator!().read_mag().into()
+    allow_threads(|| with_navigator!().read_mag())
 }
 
 #[cpy_fn]
@@ -374,7 +1486,7 @@ fn read_mag() -> AxisData {
         >>> acceleration = navigator.read_accel()\n
         >>> forward_acc = acceleration.x"]
 fn read_accel() -> AxisData {
-    with_navigator!().read_accel().into()
+    allow_threads(|| with_navigator!().read_accel())
 }
 
 #[cpy_fn]
@@ -389,260 +1501,2015 @@ fn read_accel() -> AxisData {
         >>> pitch_rate = angular_velocity.y\n
         >>> yaw_rate = angular_velocity.z"]
 fn read_gyro() -> AxisData {
-    with_navigator!().read_gyro().into()
+    allow_threads(|| with_navigator!().read_gyro())
 }
 
 #[cpy_fn]
-#[comment_c = "Reads the state of leak detector pin from Navigator."]
-#[comment_py = "Reads the state of leak detector pin from Navigator.\n\n
+#[comment_c = "Reads all sensors in a single pass, so the measurements are time-aligned."]
+#[comment_py = "Reads all sensors in a single pass, so the measurements are time-aligned.\n
+    The navigator is locked once for all readings, instead of once for each `read_*` call.\n
     Returns:\n
-        bool: The current state. `True` -> Leak detection, `False` -> No leak.\n
+        :py:class:`SensorData`: Accelerometer [m/s²], gyroscope [rad/s], magnetometer [µT],
+        pressure [kPa], temperature [˚C], ADC [V] and leak measurements, with the sample timestamp [µs].\n
     Examples:\n
         >>> import bluerobotics_navigator as navigator\n
-        >>> leak_detector = navigator.read_leak()"]
-fn read_leak() -> bool {
-    with_navigator!().read_leak()
+        >>> data = navigator.read_all()\n
+        >>> yaw_rate = data.gyro.z"]
+fn read_all() -> SensorData {
+    allow_threads(|| with_navigator!().read_all())
 }
 
-#[cpy_fn]
-#[comment_c = "Enables or disables the PWM chip (PCA9685), using the firmware and OE_pin."]
-#[comment_py = "Enables or disables the PWM chip (PCA9685), using the firmware and OE_pin.\n
+#[cfg(feature = "numpy")]
+#[cpy_fn_py]
+#[comment = "Reads multiple consecutive samples from the accelerometer, gyroscope and magnetometer.\n
+    The samples are taken in a single access to the hardware, requires the library to be built with
+    the `numpy` feature.\n
     Args:\n
-        state (bool): The desired PWM chip state. `True` -> ON, `False` -> OFF.\n
+        n (int): Number of samples to read.\n
+    Returns:\n
+        numpy.ndarray: float32 array with shape (n, 9), where each row contains the acceleration [m/s²],
+        angular velocity [rad/s] and magnetic field [µT] x, y and z values.\n
     Examples:\n
-        Please check :py:func:`set_pwm_channel_value`\n
-        >>> navigator.set_pwm_enable(True)"]
-fn set_pwm_enable(state: bool) {
-    with_navigator!().set_pwm_enable(state)
+        >>> import bluerobotics_navigator as navigator\n
+        >>> samples = navigator.read_imu_batch(100)\n
+        >>> gyro_z = samples[:, 5]"]
+fn read_imu_batch_py(py: pyo3::Python, n: usize) -> pyo3::PyResult<pyo3::PyObject> {
+    into_numpy_array(py, &[n, 9], || with_navigator!().read_imu_batch(n))
 }
 
 #[cpy_fn]
-#[comment_c = "Sets the PWM frequency of the PCA9685 chip. All channels use the same frequency."]
-#[comment_py = "Sets the PWM frequency of the PCA9685 chip. All channels use the same frequency.\n
-    This is a convenience wrapper around :py:func:`set_pwm_freq_prescale`, which chooses the closest
-    possible pre-scaler to achieve the desired frequency.\n
-    Notes:\n
-        Servo motors generally work best with PWM frequencies between 50-200 Hz.\n
-        LEDs flicker less in video streams when driven at a frequency multiple of the camera's
-        framerate (e.g. a 30fps camera stream should have LEDs at 30/60/90/120/... Hz).\n
+#[comment_c = "Sets the full-scale range of the accelerometer, the readings stay in [m/s²]."]
+#[comment_py = "Sets the full-scale range of the accelerometer, the readings stay in [m/s²].\n
+    A smaller range gives a finer resolution, a larger one avoids clipping under strong vibration
+    or shocks. The clipping count of :py:func:`read_vibration` follows the range.\n
     Args:\n
-        freq (float32) : The desired PWM frequency (24..1526) [Hz].\n
+        range (:py:class:`AccelRange`): The range, ±8g by default.\n
     Examples:\n
         >>> import bluerobotics_navigator as navigator\n
-        >>> navigator.set_pwm_freq_hz(60)\n
-        >>> navigator.set_pwm_channel_value(1, 2000)\n
-        >>> navigator.set_pwm_enable(True)"]
-fn set_pwm_freq_hz(freq: f32) {
-    with_navigator!().set_pwm_frequency(freq)
+        >>> from bluerobotics_navigator import AccelRange\n
+        >>> navigator.set_accel_range(AccelRange.G16)"]
+fn set_accel_range(range: AccelRange) {
+    allow_threads(|| with_navigator!().set_accel_range(range))
 }
 
 #[cpy_fn]
-#[comment_c = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel."]
-#[comment_py = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel.\n
-    This sets the PWM channel's OFF counter, with the ON counter hard-coded to 0.\n
-    The output turns ON at the start of each cycle, then turns OFF after the specified count
-    (value), where each full cycle (defined by :py:func:`set_pwm_freq_hz`) is split into 4096
-    segments.\n
-    Notes:\n
-        A duty cycle of 20% is achieved using a count of 819.\n
-        To achieve a specific pulse-duration you need to consider the cycle frequency:\n
-        `value = 4095 * pulse_duration / cycle_period`\n
-        As an example, if the frequency is set to 50 Hz (20 ms period) then a 1100 µs
-        pulse-duration can be achieved with a 5.5% duty cycle, which requires a count of 225.
-        Similarly, 1900 µs pulses would be achieved with a count of 389.
+#[comment_c = "Sets the full-scale range of the gyroscope, the readings stay in [rad/s]."]
+#[comment_py = "Sets the full-scale range of the gyroscope, the readings stay in [rad/s].\n
     Args:\n
-        channel (:py:class:`PwmChannel`): The channel to be selected for PWM.\n
-        value (u16) : Duty cycle count value (0..4095).\n
+        range (:py:class:`GyroRange`): The range, ±2000dps by default.\n
     Examples:\n
         >>> import bluerobotics_navigator as navigator\n
-        >>> from bluerobotics_navigator import PwmChannel\n
-        >>> navigator.init()\n
-        >>> navigator.set_pwm_freq_hz(1000)\n
-        >>> navigator.set_pwm_channel_value(PwmChannel.Ch1, 2000)\n
-        >>> navigator.set_pwm_enable(True)"]
-fn set_pwm_channel_value(channel: usize, value: f32) {
-    with_navigator!().set_pwm_duty_cycle(channel, value / 4096.0)
+        >>> from bluerobotics_navigator import GyroRange\n
+        >>> navigator.set_gyro_range(GyroRange.Dps500)"]
+fn set_gyro_range(range: GyroRange) {
+    allow_threads(|| with_navigator!().navigator.set_gyro_range(range))
 }
 
 #[cpy_fn]
-#[comment_c = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel."]
-#[comment_py = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel.\n
-    Similar to :py:func:`set_pwm_channel_value`, this function calculate the OFF counter
-    value to match desired PWM channel's duty_cyle.\n
-    Notes:\n
-        A duty cycle of 1.0 or 0.0 acts like a relay.\n
-        Details of counters on IC, check :py:func:`set_pwm_channel_value`.
+#[comment_c = "Sets the bandwidth of the IMU digital low-pass filter, for both the accelerometer and the gyroscope."]
+#[comment_py = "Sets the bandwidth of the IMU digital low-pass filter, for both the accelerometer and the gyroscope.\n
+    The filter runs in the ICM20689, before the samples are read, so it also applies to
+    :py:func:`read_imu_fifo`. A narrower bandwidth removes more noise and adds more delay.\n
     Args:\n
-        channel (:py:class:`PwmChannel`): The channel to be selected for PWM.\n
-        duty_cycle (f32) : Duty cycle count value (0.0 : 1.0).\n
+        dlpf (:py:class:`ImuDlpf`): The filter bandwidth.\n
     Examples:\n
         >>> import bluerobotics_navigator as navigator\n
-        >>> from bluerobotics_navigator import PwmChannel\n
-        >>> navigator.init()\n
-        >>> navigator.set_pwm_freq_hz(1000)\n
-        >>> navigator.set_pwm_channel_duty_cycle(PwmChannel.Ch1, 0.5)\n
-        >>> navigator.set_pwm_enable(True)"]
-fn set_pwm_channel_duty_cycle(channel: usize, duty_cycle: f32) {
-    with_navigator!().set_pwm_duty_cycle(channel, duty_cycle)
+        >>> from bluerobotics_navigator import ImuDlpf\n
+        >>> navigator.set_imu_dlpf(ImuDlpf.Hz41)"]
+fn set_imu_dlpf(dlpf: ImuDlpf) {
+    allow_threads(|| with_navigator!().navigator.set_imu_dlpf(dlpf))
 }
 
-#[cpy_fn_c]
-#[comment = "Sets the duty cycle (based on OFF counter from 0 to 1) for a list of multiple PWM channels."]
-fn set_pwm_channels_value_c(channels: *const usize, value: f32, length: usize) {
-    let array_channels = unsafe {
-        assert!(!channels.is_null());
-        std::slice::from_raw_parts(channels, length)
-    };
-    for channel in array_channels.iter().take(length) {
-        with_navigator!().set_pwm_duty_cycle(*channel, value);
-    }
+/// Sets the IMU sample rate of the default instance, fails if it's out of range.
+fn set_imu_rate(rate_hz: f32) -> Result<(), String> {
+    allow_threads(|| with_navigator!().navigator.set_imu_sample_rate(rate_hz))
 }
 
 #[cpy_fn_c]
-#[comment = "Sets the duty cycle (from 0.0 to 1.0) for a list of multiple PWM channels."]
-fn set_pwm_channels_duty_cycle_c(channels: *const usize, duty_cycle: f32, length: usize) {
-    let array_channels = unsafe {
-        assert!(!channels.is_null());
-        std::slice::from_raw_parts(channels, length)
-    };
-    for channel in array_channels.iter().take(length) {
-        with_navigator!().set_pwm_duty_cycle(*channel, duty_cycle);
-    }
+#[comment = "Sets the IMU sample rate, from 3.9 to 1000 Hz, rounded to the closest rate of the sample rate divider. \
+    Enables the low-pass filter at 176 Hz if `set_imu_dlpf` wasn't called. Returns false if the rate is out of range, \
+    see `navigator_last_error`."]
+fn set_imu_sample_rate_c(rate_hz: f32) -> bool {
+    set_imu_rate(rate_hz)
+        .map_err(handle::set_last_error)
+        .is_ok()
 }
 
 #[cpy_fn_py]
-#[comment = "Like :py:func:`set_pwm_channel_value`. This function sets the duty cycle for a list of multiple PWM channels.\n
+#[comment = "Sets the IMU sample rate, the rate at which the FIFO of :py:func:`read_imu_fifo` is filled.\n
+    The rate is 1000 Hz divided by an integer from 1 to 256, the closest one is used. It needs the
+    low-pass filter, enabled at 176 Hz if :py:func:`set_imu_dlpf` wasn't called.\n
     Args:\n
-        channels ([:py:class:`PwmChannel`]): A list of PWM channels to configure.\n
-        value (u16) : The desired duty cycle value (0..4095).\n
+        rate_hz (float): The sample rate, from 3.9 to 1000 Hz.\n
+    Raises:\n
+        ValueError: If the rate is out of range.\n
     Examples:\n
-        You can use this method like :py:func:`set_pwm_channel_value`.\n
-        >>> navigator.set_pwm_channels_value([PwmChannel.Ch1, PwmChannel.Ch16], 1000)"]
-fn set_pwm_channels_value_py(channels: Vec<usize>, value: u16) {
-    for i in 0..channels.len() {
-        with_navigator!().set_pwm_duty_cycle(channels[i], value as f32 / 4096.0);
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_imu_sample_rate(200)"]
+fn set_imu_sample_rate_py(rate_hz: f32) -> pyo3::PyResult<()> {
+    set_imu_rate(rate_hz).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Reads up to `max_samples` samples from the IMU FIFO of the default instance, fails if it overflowed.
+fn read_imu_samples(max_samples: usize) -> Result<Vec<ImuSample>, String> {
+    allow_threads(|| with_navigator!().read_imu_fifo(max_samples))
+}
+
+/// Copies the `read` samples to `samples`, up to `capacity`, and their number to `count`. Returns
+/// false with the error kept for `navigator_last_error` if the read failed.
+///
+/// # Safety
+///
+/// `samples` must point to `capacity` writable samples, `count` must be null or writable.
+#[cfg(not(feature = "python"))]
+unsafe fn copy_imu_samples(
+    read: Result<Vec<ImuSample>, String>,
+    samples: *mut ImuSample,
+    capacity: usize,
+    count: *mut usize,
+) -> bool {
+    let read = read.map_err(handle::set_last_error);
+    let copied = read.as_ref().map_or(0, Vec::len);
+    if let Ok(read) = read.as_ref() {
+        for (entry, sample) in slice_from_c_mut(samples, capacity).iter_mut().zip(read) {
+            *entry = sample.clone();
+        }
+    }
+    if !count.is_null() {
+        *count = copied;
     }
+    read.is_ok()
+}
+
+#[cpy_fn_c]
+#[comment = "Reads up to `capacity` accelerometer and gyroscope samples from the IMU FIFO, oldest first, to `samples`, \
+    with their number written to `count`. The FIFO is started by the first call, which reads no samples, and holds \
+    341 samples: it must be read faster than it fills at the rate of `set_imu_sample_rate`. Returns false if the \
+    FIFO overflowed, its samples are then dropped, see `navigator_last_error`."]
+fn read_imu_fifo_c(samples: *mut ImuSample, capacity: usize, count: *mut usize) -> bool {
+    unsafe { copy_imu_samples(read_imu_samples(capacity), samples, capacity, count) }
 }
 
 #[cpy_fn_py]
-#[comment = "Like :py:func:`set_pwm_channel_duty_cycle`. This function sets the duty cycle for a list of multiple PWM channels.\n
+#[comment = "Reads the accelerometer and gyroscope samples stored in the IMU FIFO, oldest first.\n
+    The IMU stores a sample at the rate of :py:func:`set_imu_sample_rate`, so no sample is missed
+    between calls. The FIFO is started by the first call, which returns no samples, and holds
+    341 samples: it must be read faster than it fills.\n
     Args:\n
-        channels ([:py:class:`PwmChannel`]): A list of PWM channels to configure.\n
-        duty_cycle (f32) : Duty cycle count value (0.0 : 1.0).\n
+        max_samples (int, optional): Largest number of samples to read, all of them by default.\n
+    Returns:\n
+        list[:py:class:`ImuSample`]: Acceleration [m/s²] and angular velocity [rad/s] of each sample.\n
+    Raises:\n
+        RuntimeError: If the FIFO overflowed, its samples are then dropped.\n
     Examples:\n
-        You can use this method like :py:func:`set_pwm_channel_duty_cycle`.\n
-        >>> navigator.set_pwm_channels_value([PwmChannel.Ch1, PwmChannel.Ch16], 0.5)"]
-fn set_pwm_channels_duty_cycle_py(channels: Vec<usize>, duty_cycle: f32) {
-    for channel in channels {
-        with_navigator!().set_pwm_duty_cycle(channel.into(), duty_cycle);
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_imu_sample_rate(500)\n
+        >>> navigator.read_imu_fifo()\n
+        >>> for sample in navigator.read_imu_fifo():\n
+        ...     print(sample.gyro.z)"]
+fn read_imu_fifo_py(max_samples: Option<usize>) -> pyo3::PyResult<Vec<ImuSample>> {
+    let max_samples = max_samples.unwrap_or(usize::MAX);
+    read_imu_samples(max_samples).map_err(pyo3::exceptions::PyRuntimeError::new_err)
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the vibration levels and clipping count, computed from the accelerometer readings of the other read functions."]
+#[comment_py = "Reads the vibration levels and clipping count, computed from the accelerometer readings of the other read functions.\n
+    No extra sampling is done, the metrics are updated by :py:func:`read_accel`, :py:func:`read_all` and
+    :py:func:`read_imu_batch`, so the accelerometer should be read regularly.\n
+    Returns:\n
+        :py:class:`VibrationData`: Vibration levels in [m/s²], as computed by ArduPilot, and the clipping count.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> vibration = navigator.read_vibration()\n
+        >>> print(vibration.vibration.z, vibration.clipping)"]
+fn read_vibration() -> VibrationData {
+    vibration_data()
+}
+
+/// Vibration metrics of the default instance, empty while it isn't running.
+fn vibration_data() -> VibrationData {
+    NAVIGATOR
+        .with_instance(|instance| instance.read_vibration())
+        .unwrap_or_else(|| VibrationData::new(&vibration::VibrationMonitor::new()))
+}
+
+#[cpy_fn]
+#[comment_c = "Resets the vibration levels and clipping count."]
+#[comment_py = "Resets the vibration levels and clipping count.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.reset_vibration()"]
+fn reset_vibration() {
+    NAVIGATOR.with_instance(handle::Instance::reset_vibration);
+}
+
+navigator_enum! {
+    #[comment = "Sensors whose health is monitored: the barometer (BMP280 or BMP390), the AK09915 magnetometer, the \
+    ICM20689 accelerometer and gyroscope, and the ADS1115 ADC."]
+    enum Sensor {
+        Barometer,
+        Magnetometer,
+        Imu,
+        Adc,
     }
 }
 
-#[cpy_fn_c]
-#[comment = "Sets the duty cycle (from 0 to 4096) for a list of multiple channels with multiple values."]
-fn set_pwm_channels_values_c(channels: *const usize, values: *const f32, length: usize) {
-    let array_channels = unsafe {
-        assert!(!channels.is_null());
-        std::slice::from_raw_parts(channels, length)
-    };
-    let array_values = unsafe {
-        assert!(!values.is_null());
-        std::slice::from_raw_parts(values, length)
-    };
-    for i in 0..length {
-        with_navigator!().set_pwm_duty_cycle(array_channels[i], array_values[i] / 4096.0);
+navigator_enum! {
+    #[comment = "Health of a sensor. `NoData` until it's read, `Failing` while its reads fail, `OutOfRange` when a value \
+    is beyond what it can measure and `Stuck` when a value stopped changing."]
+    enum SensorStatus {
+        NoData,
+        Healthy,
+        Failing,
+        Stuck,
+        OutOfRange,
     }
 }
 
-#[cpy_fn_c]
-#[comment = "Sets the duty cycle (from 0.0 to 1.0) for a list of multiple channels with multiple values."]
-fn set_pwm_channels_duty_cycle_values_c(
-    channels: *const usize,
-    duty_cycle: *const f32,
-    length: usize,
-) {
-    let array_channels = unsafe {
-        assert!(!channels.is_null());
-        std::slice::from_raw_parts(channels, length)
-    };
-    let array_values = unsafe {
-        assert!(!duty_cycle.is_null());
-        std::slice::from_raw_parts(duty_cycle, length)
-    };
-    for i in 0..length {
-        with_navigator!().set_pwm_duty_cycle(array_channels[i], array_values[i]);
+#[cpy_struct]
+#[comment = "Health of a sensor, from the reads of the default instance. `error_count` is the number of failed \
+    reads, `invalid_count` of samples that were stuck or out of range, and `consecutive_errors` of failed reads \
+    since the last successful one. `last_good_timestamp` is the time of the last valid sample in microseconds \
+    since the UNIX epoch, 0 if there is none."]
+struct SensorHealth {
+    sensor: Sensor,
+    status: SensorStatus,
+    sample_count: u64,
+    error_count: u64,
+    invalid_count: u64,
+    consecutive_errors: u32,
+    last_good_timestamp: u64,
+    stuck: bool,
+    out_of_range: bool,
+}
+
+#[cpy_struct]
+#[comment = "Measurement with its validity, `value` is NaN and `valid` false when the read failed, or the value is \
+    stuck or out of range."]
+struct CheckedValue {
+    value: f32,
+    valid: bool,
+}
+
+#[cpy_struct]
+#[comment = "Axis measurements with their validity, the axes are NaN and `valid` false when the read failed, or a \
+    value is stuck or out of range."]
+struct CheckedAxisData {
+    value: AxisData,
+    valid: bool,
+}
+
+impl CheckedValue {
+    fn new(reading: std::thread::Result<(f32, bool)>) -> Self {
+        match reading {
+            Ok((value, true)) => Self { value, valid: true },
+            _ => Self::invalid(),
+        }
+    }
+
+    fn invalid() -> Self {
+        Self {
+            value: f32::NAN,
+            valid: false,
+        }
     }
 }
 
-#[cpy_fn_py]
-#[comment = "Like :py:func:`set_pwm_channel_value`. This function sets the duty cycle for a list of
-    multiple channels with multiple values.\n
-    Args:\n
-        channels ([:py:class:`PwmChannel`]): A list of PWM channels to configure.\n
-        values ([u16]) : A corresponding list of duty cycle values.\n
-    Examples:\n
-        You can use this method like :py:func:`set_pwm_channel_value`.\n
-        >>> navigator.set_pwm_channels_values([PwmChannel.Ch1, PwmChannel.Ch5], [1000, 500])"]
-fn set_pwm_channels_values_py(channels: Vec<usize>, values: Vec<u16>) {
-    if channels.len() != values.len() {
-        println!("The number of values is different from the number of PWM channels.");
-        return;
+impl CheckedAxisData {
+    /// Converts a reading, with the axes rotated to the vehicle frame.
+    fn new(
+        reading: std::thread::Result<(navigator_rs::AxisData, bool)>,
+        rotation: &orientation::Matrix,
+    ) -> Self {
+        match reading {
+            Ok((value, true)) => Self {
+                value: orientation::rotate(rotation, value).into(),
+                valid: true,
+            },
+            _ => Self::invalid(),
+        }
+    }
+
+    fn invalid() -> Self {
+        Self {
+            value: AxisData::NAN,
+            valid: false,
+        }
     }
+}
+
+/// Runs a checked read of the default instance, failing to create it is a failed read as well.
+fn checked_read<T>(read: impl FnOnce() -> T, invalid: fn() -> T) -> T {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(read)).unwrap_or_else(|_| invalid())
+}
 
-    for i in 0..channels.len() {
-        with_navigator!().set_pwm_duty_cycle(channels[i].clone().into(), values[i] as f32 / 4096.0);
+impl SensorHealth {
+    /// Health of each sensor, in the order of `health::SENSORS`.
+    fn all(monitor: &health::HealthMonitor) -> Vec<Self> {
+        health::SENSORS
+            .into_iter()
+            .map(|sensor| {
+                let health = monitor.health(sensor.clone());
+                let last_good_timestamp = health.last_good.map_or(0, |last_good| {
+                    timestamp_us().saturating_sub(last_good.elapsed().as_micros() as u64)
+                });
+                SensorHealth {
+                    sensor,
+                    status: health.status,
+                    sample_count: health.sample_count,
+                    error_count: health.error_count,
+                    invalid_count: health.invalid_count,
+                    consecutive_errors: health.consecutive_errors,
+                    last_good_timestamp,
+                    stuck: health.stuck,
+                    out_of_range: health.out_of_range,
+                }
+            })
+            .collect()
     }
 }
 
+/// Health of each sensor of the default instance, `NoData` while it isn't running.
+fn sensor_health_all() -> Vec<SensorHealth> {
+    NAVIGATOR
+        .with_instance(|instance| instance.sensor_health())
+        .unwrap_or_else(|| SensorHealth::all(&health::HealthMonitor::new()))
+}
+
 #[cpy_fn_py]
-#[comment = "Like :py:func:`set_pwm_channel_duty_cycle`. This function sets the duty cycle for a list of
-    multiple channels with multiple values.\n
+#[comment_py = "Returns the health of each sensor, from the reads of the default instance.\n
+    Every read function is monitored: failed reads are counted, and the values are checked against the sensor
+    range and for a value that stopped changing.\n
+    Returns:\n
+        list[:py:class:`SensorHealth`]: Health of the barometer, magnetometer, IMU and ADC, in this order.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> for health in navigator.get_sensor_health():\n
+        ...     print(health.sensor, health.status, health.error_count)"]
+fn get_sensor_health_py() -> Vec<SensorHealth> {
+    sensor_health_all()
+}
+
+/// Copies `sensors` to the `length` entries of `health`, if not null. Returns the number of sensors.
+///
+/// # Safety
+///
+/// `health` must be null or point to `length` writable entries.
+#[cfg(not(feature = "python"))]
+unsafe fn copy_sensor_health(
+    sensors: Vec<SensorHealth>,
+    health: *mut SensorHealth,
+    length: usize,
+) -> usize {
+    let count = sensors.len();
+    if !health.is_null() {
+        for (entry, sensor) in slice_from_c_mut(health, length).iter_mut().zip(sensors) {
+            *entry = sensor;
+        }
+    }
+    count
+}
+
+#[cpy_fn_c]
+#[comment = "Writes the health of each sensor (barometer, magnetometer, IMU and ADC, in this order) to `health`, \
+    up to `length` entries. Returns the number of sensors."]
+fn get_sensor_health_c(health: *mut SensorHealth, length: usize) -> usize {
+    unsafe { copy_sensor_health(sensor_health_all(), health, length) }
+}
+
+#[cpy_fn]
+#[comment_c = "Resets the health of every sensor."]
+#[comment_py = "Resets the health of every sensor.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.reset_sensor_health()"]
+fn reset_sensor_health() {
+    NAVIGATOR.with_instance(handle::Instance::reset_sensor_health);
+}
+
+#[cpy_fn]
+#[comment_c = "Reads a specific ADC channel, with NaN and `valid` false instead of a failed, stuck or out of range value."]
+#[comment_py = "Reads a specific ADC channel, with NaN and `valid` false instead of a failed, stuck or out of range value.\n
+    Same as :py:func:`read_adc`, but a failed read doesn't raise.\n
     Args:\n
-        channels ([:py:class:`PwmChannel`]): A list of PWM channels to configure.\n
-        duty_cycle_values (f32) : Duty cycle count value (0.0 : 1.0).\n
+        select (:py:class:`AdcChannel`):  An ADC channel to read from.\n
+    Returns:\n
+        :py:class:`CheckedValue`: Measurement in [V].\n
     Examples:\n
-        You can use this method like :py:func:`set_pwm_channel_duty_cycle`.\n
-        >>> navigator.set_pwm_channels_duty_cycle_values([PwmChannel.Ch1, PwmChannel.Ch5], [0.25, 0.75])"]
-fn set_pwm_channels_duty_cycle_values_py(channels: Vec<usize>, duty_cycle_values: Vec<f32>) {
-    if channels.len() != duty_cycle_values.len() {
-        println!("The number of values is different from the number of PWM channels.");
-        return;
-    }
-
-    for i in 0..channels.len() {
-        with_navigator!().set_pwm_duty_cycle(channels[i].clone().into(), duty_cycle_values[i]);
-    }
-}
-cpy_module!(
-    name = bluerobotics_navigator,
-    types = [AdcChannel, UserLed, AxisData, Raspberry, NavigatorVersion],
-    functions = [
-        init,
-        set_rgb_led_strip_size,
-        set_navigator_version,
-        set_raspberry_pi_version,
-        self_test,
-        set_led,
-        get_led,
-        set_led_toggle,
-        set_led_all,
-        set_neopixel,
-        set_neopixel_rgbw,
-        read_adc_all,
-        read_adc,
-        read_pressure,
-        read_temp,
-        read_leak,
-        read_mag,
-        read_accel,
-        read_gyro,
-        set_pwm_enable,
-        set_pwm_freq_hz,
-        set_pwm_channel_value,
-        set_pwm_channel_duty_cycle,
-        set_pwm_channels_value,
-        set_pwm_channels_duty_cycle,
-        set_pwm_channels_values,
-        set_pwm_channels_duty_cycle_values
-    ]
-);
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import AdcChannel\n
+        >>> adc1 = navigator.read_adc_checked(AdcChannel.Ch1)\n
+        >>> if adc1.valid:\n
+        ...     print(adc1.value)"]
+fn read_adc_checked(channel: AdcChannel) -> CheckedValue {
+    allow_threads(|| {
+        checked_read(
+            || with_navigator!().read_adc_checked(channel),
+            CheckedValue::invalid,
+        )
+    })
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current pressure, with NaN and `valid` false instead of a failed, stuck or out of range value."]
+#[comment_py = "Reads the current pressure, with NaN and `valid` false instead of a failed, stuck or out of range value.\n
+    Same as :py:func:`read_pressure`, but a failed read doesn't raise.\n
+    Returns:\n
+        :py:class:`CheckedValue`: Measurement in [kPa]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> pressure = navigator.read_pressure_checked()\n
+        >>> if pressure.valid:\n
+        ...     print(pressure.value)"]
+fn read_pressure_checked() -> CheckedValue {
+    allow_threads(|| {
+        checked_read(
+            || with_navigator!().read_pressure_checked(),
+            CheckedValue::invalid,
+        )
+    })
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current temperature, with NaN and `valid` false instead of a failed or out of range value."]
+#[comment_py = "Reads the current temperature, with NaN and `valid` false instead of a failed or out of range value.\n
+    Same as :py:func:`read_temp`, but a failed read doesn't raise.\n
+    Returns:\n
+        :py:class:`CheckedValue`: Measurement in [˚C]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> temperature = navigator.read_temp_checked()"]
+fn read_temp_checked() -> CheckedValue {
+    allow_threads(|| {
+        checked_read(
+            || with_navigator!().read_temp_checked(),
+            CheckedValue::invalid,
+        )
+    })
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the local magnetic field strengths, with NaN axes and `valid` false instead of a failed, stuck or out of range value."]
+#[comment_py = "Reads the local magnetic field strengths, with NaN axes and `valid` false instead of a failed, stuck or out of range value.\n
+    Same as :py:func:`read_mag`, but a failed read doesn't raise.\n
+    Returns:\n
+        :py:class:`CheckedAxisData`: Measurements in [µT]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> mag = navigator.read_mag_checked()\n
+        >>> if mag.valid:\n
+        ...     print(mag.value.x)"]
+fn read_mag_checked() -> CheckedAxisData {
+    allow_threads(|| {
+        checked_read(
+            || with_navigator!().read_mag_checked(),
+            CheckedAxisData::invalid,
+        )
+    })
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current acceleration values, with NaN axes and `valid` false instead of a failed, stuck or out of range value."]
+#[comment_py = "Reads the current acceleration values, with NaN axes and `valid` false instead of a failed, stuck or out of range value.\n
+    Same as :py:func:`read_accel`, but a failed read doesn't raise. Valid readings update the vibration levels.\n
+    Returns:\n
+        :py:class:`CheckedAxisData`: Measurements in [m/s²]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> acceleration = navigator.read_accel_checked()"]
+fn read_accel_checked() -> CheckedAxisData {
+    allow_threads(|| {
+        checked_read(
+            || with_navigator!().read_accel_checked(),
+            CheckedAxisData::invalid,
+        )
+    })
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current angular velocity, with NaN axes and `valid` false instead of a failed, stuck or out of range value."]
+#[comment_py = "Reads the current angular velocity, with NaN axes and `valid` false instead of a failed, stuck or out of range value.\n
+    Same as :py:func:`read_gyro`, but a failed read doesn't raise.\n
+    Returns:\n
+        :py:class:`CheckedAxisData`: Measurements in [rad/s]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> angular_velocity = navigator.read_gyro_checked()"]
+fn read_gyro_checked() -> CheckedAxisData {
+    allow_threads(|| {
+        checked_read(
+            || with_navigator!().read_gyro_checked(),
+            CheckedAxisData::invalid,
+        )
+    })
+}
+
+navigator_enum! {
+    #[comment = "Sensor channels that can have a filter attached, axis and ADC values are filtered independently."]
+    enum FilterChannel {
+        Accel,
+        Gyro,
+        Mag,
+        Pressure,
+        Temperature,
+        Adc,
+    }
+}
+
+fn set_channel_filter(channel: FilterChannel, spec: filters::FilterSpec) -> Result<(), String> {
+    spec.validate()?;
+    configure(|builder| builder.filters.insert(channel as usize, spec));
+    Ok(())
+}
+
+#[cpy_fn_c]
+#[comment = "Attaches a Butterworth low-pass filter to a sensor channel, used by the `read_*_filtered` functions. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn set_low_pass_filter_c(channel: FilterChannel, sample_rate: f32, cutoff: f32) -> bool {
+    set_channel_filter(
+        channel,
+        filters::FilterSpec::LowPass {
+            sample_rate,
+            cutoff,
+        },
+    )
+    .map_err(handle::set_last_error)
+    .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Attaches a Butterworth low-pass filter to a sensor channel, used by the `read_*_filtered` functions.\n
+    Replaces any filter previously attached to the channel.\n
+    Args:\n
+        channel (:py:class:`FilterChannel`): The sensor channel to filter.\n
+        sample_rate (float32): The rate the channel is read at [Hz].\n
+        cutoff (float32): The cutoff frequency [Hz], below half the sample rate.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import FilterChannel\n
+        >>> navigator.set_low_pass_filter(FilterChannel.Gyro, 100, 20)\n
+        >>> angular_velocity = navigator.read_gyro_filtered()"]
+fn set_low_pass_filter_py(
+    channel: FilterChannel,
+    sample_rate: f32,
+    cutoff: f32,
+) -> pyo3::PyResult<()> {
+    set_channel_filter(
+        channel,
+        filters::FilterSpec::LowPass {
+            sample_rate,
+            cutoff,
+        },
+    )
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Attaches a notch filter to a sensor channel, used by the `read_*_filtered` functions. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn set_notch_filter_c(
+    channel: FilterChannel,
+    sample_rate: f32,
+    center: f32,
+    bandwidth: f32,
+) -> bool {
+    set_channel_filter(
+        channel,
+        filters::FilterSpec::Notch {
+            sample_rate,
+            center,
+            bandwidth,
+        },
+    )
+    .map_err(handle::set_last_error)
+    .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Attaches a notch filter to a sensor channel, used by the `read_*_filtered` functions.\n
+    Replaces any filter previously attached to the channel.\n
+    Args:\n
+        channel (:py:class:`FilterChannel`): The sensor channel to filter.\n
+        sample_rate (float32): The rate the channel is read at [Hz].\n
+        center (float32): The rejected frequency [Hz], below half the sample rate.\n
+        bandwidth (float32): The width of the rejected band [Hz].\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import FilterChannel\n
+        >>> navigator.set_notch_filter(FilterChannel.Gyro, 400, 50, 10)"]
+fn set_notch_filter_py(
+    channel: FilterChannel,
+    sample_rate: f32,
+    center: f32,
+    bandwidth: f32,
+) -> pyo3::PyResult<()> {
+    set_channel_filter(
+        channel,
+        filters::FilterSpec::Notch {
+            sample_rate,
+            center,
+            bandwidth,
+        },
+    )
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Attaches a 1-euro filter to a sensor channel, used by the `read_*_filtered` functions. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn set_one_euro_filter_c(channel: FilterChannel, min_cutoff: f32, beta: f32) -> bool {
+    set_channel_filter(channel, filters::FilterSpec::OneEuro { min_cutoff, beta })
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Attaches a 1-euro filter to a sensor channel, used by the `read_*_filtered` functions.\n
+    The 1-euro filter adapts its cutoff to the rate of change, smoothing slow signals while
+    following fast changes. The time between reads is measured, so any read rate works.\n
+    Args:\n
+        channel (:py:class:`FilterChannel`): The sensor channel to filter.\n
+        min_cutoff (float32): The cutoff frequency for slow changes [Hz].\n
+        beta (float32): How much the cutoff increases with the rate of change.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import FilterChannel\n
+        >>> navigator.set_one_euro_filter(FilterChannel.Pressure, 1.0, 0.01)"]
+fn set_one_euro_filter_py(
+    channel: FilterChannel,
+    min_cutoff: f32,
+    beta: f32,
+) -> pyo3::PyResult<()> {
+    set_channel_filter(channel, filters::FilterSpec::OneEuro { min_cutoff, beta })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Attaches a moving average filter to a sensor channel, used by the `read_*_filtered` functions. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn set_moving_average_filter_c(channel: FilterChannel, window: usize) -> bool {
+    set_channel_filter(channel, filters::FilterSpec::MovingAverage { window })
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Attaches a moving average filter to a sensor channel, used by the `read_*_filtered` functions.\n
+    Replaces any filter previously attached to the channel.\n
+    Args:\n
+        channel (:py:class:`FilterChannel`): The sensor channel to filter.\n
+        window (int): Number of samples to average.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import FilterChannel\n
+        >>> navigator.set_moving_average_filter(FilterChannel.Adc, 10)"]
+fn set_moving_average_filter_py(channel: FilterChannel, window: usize) -> pyo3::PyResult<()> {
+    set_channel_filter(channel, filters::FilterSpec::MovingAverage { window })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Attaches a moving median filter to a sensor channel, used by the `read_*_filtered` functions. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn set_median_filter_c(channel: FilterChannel, window: usize) -> bool {
+    set_channel_filter(channel, filters::FilterSpec::Median { window })
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Attaches a moving median filter to a sensor channel, used by the `read_*_filtered` functions.\n
+    Removes outliers while keeping edges sharp.\n
+    Args:\n
+        channel (:py:class:`FilterChannel`): The sensor channel to filter.\n
+        window (int): Number of samples to take the median of.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import FilterChannel\n
+        >>> navigator.set_median_filter(FilterChannel.Pressure, 5)"]
+fn set_median_filter_py(channel: FilterChannel, window: usize) -> pyo3::PyResult<()> {
+    set_channel_filter(channel, filters::FilterSpec::Median { window })
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn]
+#[comment_c = "Removes the filter attached to a sensor channel, the `read_*_filtered` functions return raw values."]
+#[comment_py = "Removes the filter attached to a sensor channel, the `read_*_filtered` functions return raw values.\n
+    Args:\n
+        channel (:py:class:`FilterChannel`): The sensor channel.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import FilterChannel\n
+        >>> navigator.clear_filter(FilterChannel.Gyro)"]
+fn clear_filter(channel: FilterChannel) {
+    configure(|builder| builder.filters.remove(&(channel as usize)));
+}
+
+#[cpy_fn_py]
+#[comment = "Reads the ADC channel values, filtered by the filter attached to :py:attr:`FilterChannel.Adc`.\n
+    Same as :py:func:`read_adc_all`.\n
+    Returns:\n
+        [float32]: Measurements in [V].\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> adc_measurements = navigator.read_adc_all_filtered()"]
+fn read_adc_all_filtered_py() -> Vec<f32> {
+    allow_threads(|| with_navigator!().read_adc_all_filtered())
+}
+
+#[cpy_fn_c]
+#[comment_c = "Reads the ADC channel values, filtered by the filter attached to the Adc channel."]
+fn read_adc_all_filtered_c(adc_array: *mut f32, length: usize) {
+    let array = unsafe { slice_from_c_mut(adc_array, length) };
+    let values = allow_threads(|| with_navigator!().read_adc_all_filtered());
+    array[..length].copy_from_slice(&values[..length]);
+}
+
+#[cpy_fn]
+#[comment_c = "Reads a specific ADC channel, filtered by the filter attached to the Adc channel."]
+#[comment_py = "Reads a specific ADC channel, filtered by the filter attached to :py:attr:`FilterChannel.Adc`.\n
+    Same as :py:func:`read_adc`.\n
+    Args:\n
+        select (:py:class:`AdcChannel`):  An ADC channel to read from.\n
+    Returns:\n
+        float32: Measurement in [V].\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import AdcChannel\n
+        >>> adc1_measurement = navigator.read_adc_filtered(AdcChannel.Ch1)"]
+fn read_adc_filtered(channel: AdcChannel) -> f32 {
+    allow_threads(|| with_navigator!().read_adc_filtered(channel))
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current pressure, filtered by the filter attached to the Pressure channel."]
+#[comment_py = "Reads the current pressure, filtered by the filter attached to :py:attr:`FilterChannel.Pressure`.\n
+    Returns:\n
+        float32: Measurement in [kPa]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> air_pressure = navigator.read_pressure_filtered()"]
+fn read_pressure_filtered() -> f32 {
+    allow_threads(|| with_navigator!().read_pressure_filtered())
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current temperature, filtered by the filter attached to the Temperature channel."]
+#[comment_py = "Reads the current temperature, filtered by the filter attached to :py:attr:`FilterChannel.Temperature`.\n
+    Returns:\n
+        float32: Measurement in [˚C]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> air_temperature = navigator.read_temp_filtered()"]
+fn read_temp_filtered() -> f32 {
+    allow_threads(|| with_navigator!().read_temp_filtered())
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the local magnetic field strengths, filtered by the filter attached to the Mag channel."]
+#[comment_py = "Reads the local magnetic field strengths, filtered by the filter attached to :py:attr:`FilterChannel.Mag`.\n
+    Returns:\n
+        :py:class:`AxisData`: Measurements in [µT]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> mag_field = navigator.read_mag_filtered()"]
+fn read_mag_filtered() -> AxisData {
+    allow_threads(|| with_navigator!().read_mag_filtered())
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current acceleration values, filtered by the filter attached to the Accel channel."]
+#[comment_py = "Reads the current acceleration values, filtered by the filter attached to :py:attr:`FilterChannel.Accel`.\n
+    Returns:\n
+        :py:class:`AxisData`: Measurements in [m/s²]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> acceleration = navigator.read_accel_filtered()"]
+fn read_accel_filtered() -> AxisData {
+    allow_threads(|| with_navigator!().read_accel_filtered())
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the current angular velocity, filtered by the filter attached to the Gyro channel."]
+#[comment_py = "Reads the current angular velocity, filtered by the filter attached to :py:attr:`FilterChannel.Gyro`.\n
+    Returns:\n
+        :py:class:`AxisData`: Measurements in [rad/s]\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> angular_velocity = navigator.read_gyro_filtered()"]
+fn read_gyro_filtered() -> AxisData {
+    allow_threads(|| with_navigator!().read_gyro_filtered())
+}
+
+#[cfg(not(feature = "python"))]
+fn filter_array_c(
+    values: *mut f32,
+    length: usize,
+    spec: filters::FilterSpec,
+    sample_rate: f32,
+) -> bool {
+    spec.apply_slice(unsafe { slice_from_c_mut(values, length) }, sample_rate)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cfg(feature = "python")]
+fn filter_array_py(
+    mut values: Vec<f32>,
+    spec: filters::FilterSpec,
+    sample_rate: f32,
+) -> pyo3::PyResult<Vec<f32>> {
+    spec.apply_slice(&mut values, sample_rate)
+        .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    Ok(values)
+}
+
+#[cpy_fn_c]
+#[comment_c = "Filters an array of evenly spaced samples in place with a Butterworth low-pass filter. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn filter_low_pass_c(values: *mut f32, length: usize, sample_rate: f32, cutoff: f32) -> bool {
+    let spec = filters::FilterSpec::LowPass {
+        sample_rate,
+        cutoff,
+    };
+    filter_array_c(values, length, spec, sample_rate)
+}
+
+#[cpy_fn_py]
+#[comment = "Filters an array of evenly spaced samples with a Butterworth low-pass filter.\n
+    Args:\n
+        values ([float32]): The samples.\n
+        sample_rate (float32): The sample rate [Hz].\n
+        cutoff (float32): The cutoff frequency [Hz], below half the sample rate.\n
+    Returns:\n
+        [float32]: The filtered samples.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> filtered = navigator.filter_low_pass([0.0, 1.0, 1.0, 1.0], 100, 20)"]
+fn filter_low_pass_py(values: Vec<f32>, sample_rate: f32, cutoff: f32) -> pyo3::PyResult<Vec<f32>> {
+    let spec = filters::FilterSpec::LowPass {
+        sample_rate,
+        cutoff,
+    };
+    filter_array_py(values, spec, sample_rate)
+}
+
+#[cpy_fn_c]
+#[comment_c = "Filters an array of evenly spaced samples in place with a notch filter. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn filter_notch_c(
+    values: *mut f32,
+    length: usize,
+    sample_rate: f32,
+    center: f32,
+    bandwidth: f32,
+) -> bool {
+    let spec = filters::FilterSpec::Notch {
+        sample_rate,
+        center,
+        bandwidth,
+    };
+    filter_array_c(values, length, spec, sample_rate)
+}
+
+#[cpy_fn_py]
+#[comment = "Filters an array of evenly spaced samples with a notch filter.\n
+    Args:\n
+        values ([float32]): The samples.\n
+        sample_rate (float32): The sample rate [Hz].\n
+        center (float32): The rejected frequency [Hz], below half the sample rate.\n
+        bandwidth (float32): The width of the rejected band [Hz].\n
+    Returns:\n
+        [float32]: The filtered samples.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> filtered = navigator.filter_notch(samples, 400, 50, 10)"]
+fn filter_notch_py(
+    values: Vec<f32>,
+    sample_rate: f32,
+    center: f32,
+    bandwidth: f32,
+) -> pyo3::PyResult<Vec<f32>> {
+    let spec = filters::FilterSpec::Notch {
+        sample_rate,
+        center,
+        bandwidth,
+    };
+    filter_array_py(values, spec, sample_rate)
+}
+
+#[cpy_fn_c]
+#[comment_c = "Filters an array of evenly spaced samples in place with a 1-euro filter. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn filter_one_euro_c(
+    values: *mut f32,
+    length: usize,
+    sample_rate: f32,
+    min_cutoff: f32,
+    beta: f32,
+) -> bool {
+    let spec = filters::FilterSpec::OneEuro { min_cutoff, beta };
+    filter_array_c(values, length, spec, sample_rate)
+}
+
+#[cpy_fn_py]
+#[comment = "Filters an array of evenly spaced samples with a 1-euro filter.\n
+    Args:\n
+        values ([float32]): The samples.\n
+        sample_rate (float32): The sample rate [Hz].\n
+        min_cutoff (float32): The cutoff frequency for slow changes [Hz].\n
+        beta (float32): How much the cutoff increases with the rate of change.\n
+    Returns:\n
+        [float32]: The filtered samples.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> filtered = navigator.filter_one_euro(samples, 100, 1.0, 0.01)"]
+fn filter_one_euro_py(
+    values: Vec<f32>,
+    sample_rate: f32,
+    min_cutoff: f32,
+    beta: f32,
+) -> pyo3::PyResult<Vec<f32>> {
+    let spec = filters::FilterSpec::OneEuro { min_cutoff, beta };
+    filter_array_py(values, spec, sample_rate)
+}
+
+#[cpy_fn_c]
+#[comment_c = "Filters an array of samples in place with a moving average filter. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn filter_moving_average_c(values: *mut f32, length: usize, window: usize) -> bool {
+    let spec = filters::FilterSpec::MovingAverage { window };
+    filter_array_c(values, length, spec, 1.0)
+}
+
+#[cpy_fn_py]
+#[comment = "Filters an array of samples with a moving average filter.\n
+    Args:\n
+        values ([float32]): The samples.\n
+        window (int): Number of samples to average.\n
+    Returns:\n
+        [float32]: The filtered samples.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> filtered = navigator.filter_moving_average([1.0, 2.0, 3.0, 4.0], 2)"]
+fn filter_moving_average_py(values: Vec<f32>, window: usize) -> pyo3::PyResult<Vec<f32>> {
+    let spec = filters::FilterSpec::MovingAverage { window };
+    filter_array_py(values, spec, 1.0)
+}
+
+#[cpy_fn_c]
+#[comment_c = "Filters an array of samples in place with a moving median filter. \
+    Returns false if the parameters are invalid, see `navigator_last_error`."]
+fn filter_median_c(values: *mut f32, length: usize, window: usize) -> bool {
+    let spec = filters::FilterSpec::Median { window };
+    filter_array_c(values, length, spec, 1.0)
+}
+
+#[cpy_fn_py]
+#[comment = "Filters an array of samples with a moving median filter.\n
+    Args:\n
+        values ([float32]): The samples.\n
+        window (int): Number of samples to take the median of.\n
+    Returns:\n
+        [float32]: The filtered samples.\n
+    Raises:\n
+        ValueError: The filter parameters are invalid.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> filtered = navigator.filter_median([1.0, 9.0, 1.0, 1.0], 3)"]
+fn filter_median_py(values: Vec<f32>, window: usize) -> pyo3::PyResult<Vec<f32>> {
+    let spec = filters::FilterSpec::Median { window };
+    filter_array_py(values, spec, 1.0)
+}
+
+#[cpy_fn]
+#[comment_c = "Reads the state of leak detector pin from Navigator."]
+#[comment_py = "Reads the state of leak detector pin from Navigator.\n\n
+    Returns:\n
+        bool: The current state. `True` -> Leak detection, `False` -> No leak.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> leak_detector = navigator.read_leak()"]
+fn read_leak() -> bool {
+    allow_threads(|| with_navigator!().navigator.read_leak())
+}
+
+#[cpy_fn]
+#[comment_c = "Enables or disables the PWM chip (PCA9685), using the firmware and OE_pin."]
+#[comment_py = "Enables or disables the PWM chip (PCA9685), using the firmware and OE_pin.\n
+    Args:\n
+        state (bool): The desired PWM chip state. `True` -> ON, `False` -> OFF.\n
+    Examples:\n
+        Please check :py:func:`set_pwm_channel_value`\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_pwm_enable(state: bool) {
+    allow_threads(|| with_navigator!().navigator.set_pwm_enable(state))
+}
+
+/// Claims the PWM outputs for the default instance, fails if another daemon client owns them.
+fn claim_pwm_outputs() -> Result<(), String> {
+    allow_threads(|| with_navigator!().navigator.claim_pwm())
+}
+
+#[cpy_fn_c]
+#[comment = "Makes this process the only client of `navigatord` driving the PWM outputs, until `release_pwm` is \
+    called or it disconnects. The PWM functions fail until then with the daemon backend, the local backend always \
+    owns them. Returns false if another client claimed them, see `navigator_last_error`."]
+fn claim_pwm_c() -> bool {
+    claim_pwm_outputs().map_err(handle::set_last_error).is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Makes this process the only client of `navigatord` driving the PWM outputs.\n
+    With the daemon backend, the PWM functions fail until the outputs are claimed, they stay owned
+    until :py:func:`release_pwm` is called or the process disconnects. The local backend always owns them.\n
+    Raises:\n
+        RuntimeError: Another client claimed the PWM outputs.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import Backend\n
+        >>> navigator.set_backend(Backend.Daemon)\n
+        >>> navigator.claim_pwm()\n
+        >>> navigator.set_pwm_enable(True)"]
+fn claim_pwm_py() -> pyo3::PyResult<()> {
+    claim_pwm_outputs().map_err(pyo3::exceptions::PyRuntimeError::new_err)
+}
+
+#[cpy_fn]
+#[comment_c = "Releases the PWM outputs claimed by `claim_pwm`, so another client of `navigatord` can claim them."]
+#[comment_py = "Releases the PWM outputs claimed by :py:func:`claim_pwm`, so another client of `navigatord` can claim them.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.release_pwm()"]
+fn release_pwm() {
+    allow_threads(|| with_navigator!().navigator.release_pwm())
+}
+
+/// Sets the PWM frequency of the default instance, fails if the clock can't produce it.
+fn set_pwm_frequency(freq: f32) -> Result<(), String> {
+    allow_threads(|| with_navigator!().navigator.set_pwm_frequency(freq))
+}
+
+/// Sets the PWM prescale of the default instance, fails if it's below 3.
+fn set_pwm_prescale(prescale: u8) -> Result<(), String> {
+    allow_threads(|| with_navigator!().navigator.set_pwm_prescale(prescale))
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the PWM frequency of the PCA9685 chip. All channels use the same frequency. \
+    Returns false if the frequency is out of range, see `navigator_last_error`."]
+fn set_pwm_freq_hz_c(freq: f32) -> bool {
+    set_pwm_frequency(freq)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets the PWM frequency of the PCA9685 chip. All channels use the same frequency.\n
+    This is a convenience wrapper around :py:func:`set_pwm_freq_prescale`, which chooses the closest
+    possible pre-scaler to achieve the desired frequency.\n
+    Notes:\n
+        Servo motors generally work best with PWM frequencies between 50-200 Hz.\n
+        LEDs flicker less in video streams when driven at a frequency multiple of the camera's
+        framerate (e.g. a 30fps camera stream should have LEDs at 30/60/90/120/... Hz).\n
+    Args:\n
+        freq (float32) : The desired PWM frequency (24..1500 with the default clock) [Hz].\n
+    Raises:\n
+        ValueError: If the frequency is out of the range of the pre-scaler.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_pwm_freq_hz(60)\n
+        >>> navigator.set_pwm_channel_value(1, 2000)\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_pwm_freq_hz_py(freq: f32) -> pyo3::PyResult<()> {
+    set_pwm_frequency(freq).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the PWM frequency of the PCA9685 chip by its pre-scaler value. All channels use the same frequency. \
+    Returns false if the value is below 3, see `navigator_last_error`."]
+fn set_pwm_freq_prescale_c(prescale: u8) -> bool {
+    set_pwm_prescale(prescale)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets the PWM frequency of the PCA9685 chip by its pre-scaler value. All channels use the same frequency.\n
+    The resulting frequency is `clock / (4096 * (prescale + 1))`, where the clock is set by
+    :py:func:`set_pwm_clock_hz`.\n
+    Args:\n
+        prescale (u8) : The pre-scaler value (3..255).\n
+    Raises:\n
+        ValueError: If the value is below 3.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_pwm_freq_prescale(119)\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_pwm_freq_prescale_py(prescale: u8) -> pyo3::PyResult<()> {
+    set_pwm_prescale(prescale).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn]
+#[comment_c = "Selects the output driver of the PWM channels, `TotemPole` (the default) or `OpenDrain`."]
+#[comment_py = "Selects the output driver of the PWM channels, `TotemPole` (the default) or `OpenDrain`.\n
+    Open-drain outputs only pull low, for drivers with their own pull-up. Written to the OUTDRV
+    bit of the PCA9685 MODE2 register.\n
+    Args:\n
+        mode (:py:class:`PwmOutputMode`): The output driver.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmOutputMode\n
+        >>> navigator.set_pwm_output_mode(PwmOutputMode.OpenDrain)\n
+        >>> navigator.set_pwm_invert(True)"]
+fn set_pwm_output_mode(mode: PwmOutputMode) {
+    allow_threads(|| with_navigator!().navigator.set_pwm_output_mode(mode))
+}
+
+#[cpy_fn]
+#[comment_c = "Inverts the logic state of the PWM outputs, for drivers active low."]
+#[comment_py = "Inverts the logic state of the PWM outputs, for drivers active low.\n
+    The duty cycles then set the proportion of low time. Written to the INVRT bit of the PCA9685
+    MODE2 register.\n
+    Args:\n
+        inverted (bool): Whether the outputs are inverted, `False` by default.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_pwm_invert(True)"]
+fn set_pwm_invert(inverted: bool) {
+    allow_threads(|| with_navigator!().navigator.set_pwm_invert(inverted))
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel. \
+    Returns false if the channel is invalid, see `navigator_last_error`."]
+fn set_pwm_channel_value_c(channel: PwmChannelArg, value: f32) -> bool {
+    set_pwm_duty_cycles(&[channel], &[value / 4096.0])
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel.\n
+    This sets the PWM channel's OFF counter, with the ON counter at 0, or at the channel's phase
+    offset when :py:func:`set_pwm_phase_stagger` is enabled. See :py:func:`set_pwm_channel_on_off`
+    to set both counters.\n
+    The output turns ON at the start of each cycle, then turns OFF after the specified count
+    (value), where each full cycle (defined by :py:func:`set_pwm_freq_hz`) is split into 4096
+    segments.\n
+    Notes:\n
+        A duty cycle of 20% is achieved using a count of 819.\n
+        To achieve a specific pulse-duration you need to consider the cycle frequency:\n
+        `value = 4095 * pulse_duration / cycle_period`\n
+        As an example, if the frequency is set to 50 Hz (20 ms period) then a 1100 µs
+        pulse-duration can be achieved with a 5.5% duty cycle, which requires a count of 225.
+        Similarly, 1900 µs pulses would be achieved with a count of 389.
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be selected for PWM, or its index.\n
+        value (u16) : Duty cycle count value (0..4095).\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.init()\n
+        >>> navigator.set_pwm_freq_hz(1000)\n
+        >>> navigator.set_pwm_channel_value(PwmChannel.Ch1, 2000)\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_pwm_channel_value_py(channel: PwmChannelArg, value: f32) -> pyo3::PyResult<()> {
+    set_pwm_duty_cycles(&[pwm_channel_index(channel)], &[value / 4096.0])
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel. \
+    Returns false if the channel is invalid, see `navigator_last_error`."]
+fn set_pwm_channel_duty_cycle_c(channel: PwmChannelArg, duty_cycle: f32) -> bool {
+    set_pwm_duty_cycles(&[channel], &[duty_cycle])
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets the duty cycle (the proportion of ON time) for the selected PWM channel.\n
+    Similar to :py:func:`set_pwm_channel_value`, this function calculate the OFF counter
+    value to match desired PWM channel's duty_cyle.\n
+    Notes:\n
+        A duty cycle of 1.0 or 0.0 acts like a relay.\n
+        Details of counters on IC, check :py:func:`set_pwm_channel_value`.
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be selected for PWM, or its index.\n
+        duty_cycle (f32) : Duty cycle count value (0.0 : 1.0).\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.init()\n
+        >>> navigator.set_pwm_freq_hz(1000)\n
+        >>> navigator.set_pwm_channel_duty_cycle(PwmChannel.Ch1, 0.5)\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_pwm_channel_duty_cycle_py(channel: PwmChannelArg, duty_cycle: f32) -> pyo3::PyResult<()> {
+    set_pwm_duty_cycles(&[pwm_channel_index(channel)], &[duty_cycle])
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the selected PWM channel fully ON, using the chip's full-ON bit. \
+    Returns false if the channel is invalid, see `navigator_last_error`."]
+fn set_pwm_channel_full_on_c(channel: PwmChannelArg) -> bool {
+    set_pwm_duty_cycles(&[channel], &[1.0])
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets the selected PWM channel fully ON, using the chip's full-ON bit.\n
+    The output stays high for the whole cycle, for relay-like use.\n
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be selected for PWM, or its index.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.set_pwm_channel_full_on(PwmChannel.Ch1)\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_pwm_channel_full_on_py(channel: PwmChannelArg) -> pyo3::PyResult<()> {
+    set_pwm_duty_cycles(&[pwm_channel_index(channel)], &[1.0])
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the selected PWM channel fully OFF, using the chip's full-OFF bit. \
+    Returns false if the channel is invalid, see `navigator_last_error`."]
+fn set_pwm_channel_full_off_c(channel: PwmChannelArg) -> bool {
+    set_pwm_duty_cycles(&[channel], &[0.0])
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets the selected PWM channel fully OFF, using the chip's full-OFF bit.\n
+    The output stays low for the whole cycle, for relay-like use.\n
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be selected for PWM, or its index.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.set_pwm_channel_full_off(PwmChannel.Ch1)"]
+fn set_pwm_channel_full_off_py(channel: PwmChannelArg) -> pyo3::PyResult<()> {
+    set_pwm_duty_cycles(&[pwm_channel_index(channel)], &[0.0])
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Sets both counters of a channel of the default instance, fails if a counter is above 4095.
+fn set_pwm_on_off(channel: usize, on: u16, off: u16) -> Result<(), String> {
+    allow_threads(|| with_navigator!().set_pwm_channel_on_off(channel, on, off))
+}
+
+#[cpy_fn_c]
+#[comment = "Sets both counters of the selected PWM channel: the output turns ON at the `on` count and OFF at the `off` \
+    count of each cycle. Returns false if a counter is above 4095, see `navigator_last_error`."]
+fn set_pwm_channel_on_off_c(channel: PwmChannelArg, on: u16, off: u16) -> bool {
+    set_pwm_on_off(pwm_channel_index(channel), on, off)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets both counters of the selected PWM channel.\n
+    Each cycle is split into 4096 segments, the output turns ON at the `on` count and OFF at the
+    `off` count. An `off` count lower than `on` wraps around the end of the cycle. The duty cycle
+    is still clamped to the channel limits, by moving the OFF count.\n
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be selected for PWM, or its index.\n
+        on (u16) : The count turning the output ON (0..4095).\n
+        off (u16) : The count turning the output OFF (0..4095).\n
+    Raises:\n
+        ValueError: If a count is above 4095.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.set_pwm_channel_on_off(PwmChannel.Ch1, 1024, 3072)\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_pwm_channel_on_off_py(channel: PwmChannelArg, on: u16, off: u16) -> pyo3::PyResult<()> {
+    set_pwm_on_off(pwm_channel_index(channel), on, off)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn]
+#[comment_c = "Enables or disables the phase staggering of the PWM channels, spreading their ON times over the cycle."]
+#[comment_py = "Enables or disables the phase staggering of the PWM channels, spreading their ON times over the cycle.\n
+    When enabled, the duty cycles set afterwards turn ON 1/16 of a cycle later on each channel,
+    instead of all at the start of the cycle, which reduces the current spikes of the switching
+    outputs. Can also be set by `pwm_phase_stagger` in the configuration file.\n
+    Args:\n
+        enabled (bool): Whether the channels are staggered, disabled by default.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_pwm_phase_stagger(True)\n
+        >>> navigator.set_pwm_channels_duty_cycle_values([0, 1, 2], [0.5, 0.5, 0.5])"]
+fn set_pwm_phase_stagger(enabled: bool) {
+    configure(|builder| builder.pwm_phase_stagger = enabled);
+}
+
+/// Sets multiple channels of the default instance, see `Instance::set_pwm_duty_cycles`.
+fn set_pwm_duty_cycles(channels: &[usize], duty_cycles: &[f32]) -> Result<(), String> {
+    allow_threads(|| with_navigator!().set_pwm_duty_cycles(channels, duty_cycles))
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the duty cycle (based on OFF counter from 0 to 1) for a list of multiple PWM channels. Returns false \
+    if a channel is invalid, nothing is set then, see `navigator_last_error`."]
+fn set_pwm_channels_value_c(channels: *const usize, value: f32, length: usize) -> bool {
+    let array_channels = unsafe {
+        assert!(!channels.is_null());
+        std::slice::from_raw_parts(channels, length)
+    };
+    set_pwm_duty_cycles(array_channels, &vec![value; length])
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the duty cycle (from 0.0 to 1.0) for a list of multiple PWM channels. Returns false \
+    if a channel is invalid, nothing is set then, see `navigator_last_error`."]
+fn set_pwm_channels_duty_cycle_c(channels: *const usize, duty_cycle: f32, length: usize) -> bool {
+    let array_channels = unsafe {
+        assert!(!channels.is_null());
+        std::slice::from_raw_parts(channels, length)
+    };
+    set_pwm_duty_cycles(array_channels, &vec![duty_cycle; length])
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Like :py:func:`set_pwm_channel_value`. This function sets the duty cycle for a list of multiple PWM channels.\n
+    Args:\n
+        channels ([:py:class:`PwmChannel` or int]): A list of PWM channels (or indexes) to configure.\n
+        value (u16) : The desired duty cycle value (0..4095).\n
+    Examples:\n
+        You can use this method like :py:func:`set_pwm_channel_value`.\n
+        >>> navigator.set_pwm_channels_value([PwmChannel.Ch1, PwmChannel.Ch16], 1000)"]
+fn set_pwm_channels_value_py(channels: Vec<PwmChannelArg>, value: u16) -> pyo3::PyResult<()> {
+    let channels: Vec<usize> = channels.into_iter().map(pwm_channel_index).collect();
+    set_pwm_duty_cycles(&channels, &vec![value as f32 / 4096.0; channels.len()])
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_py]
+#[comment = "Like :py:func:`set_pwm_channel_duty_cycle`. This function sets the duty cycle for a list of multiple PWM channels.\n
+    Args:\n
+        channels ([:py:class:`PwmChannel` or int]): A list of PWM channels (or indexes) to configure.\n
+        duty_cycle (f32) : Duty cycle count value (0.0 : 1.0).\n
+    Examples:\n
+        You can use this method like :py:func:`set_pwm_channel_duty_cycle`.\n
+        >>> navigator.set_pwm_channels_value([PwmChannel.Ch1, PwmChannel.Ch16], 0.5)"]
+fn set_pwm_channels_duty_cycle_py(
+    channels: Vec<PwmChannelArg>,
+    duty_cycle: f32,
+) -> pyo3::PyResult<()> {
+    let channels: Vec<usize> = channels.into_iter().map(pwm_channel_index).collect();
+    set_pwm_duty_cycles(&channels, &vec![duty_cycle; channels.len()])
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the duty cycle (from 0 to 4096) for a list of multiple channels with multiple values. Returns false \
+    if a channel is invalid, nothing is set then, see `navigator_last_error`."]
+fn set_pwm_channels_values_c(channels: *const usize, values: *const f32, length: usize) -> bool {
+    let array_channels = unsafe {
+        assert!(!channels.is_null());
+        std::slice::from_raw_parts(channels, length)
+    };
+    let array_values = unsafe {
+        assert!(!values.is_null());
+        std::slice::from_raw_parts(values, length)
+    };
+    let duty_cycles: Vec<f32> = array_values.iter().map(|value| value / 4096.0).collect();
+    set_pwm_duty_cycles(array_channels, &duty_cycles)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the duty cycle (from 0.0 to 1.0) for a list of multiple channels with multiple values. Returns false \
+    if a channel is invalid, nothing is set then, see `navigator_last_error`."]
+fn set_pwm_channels_duty_cycle_values_c(
+    channels: *const usize,
+    duty_cycle: *const f32,
+    length: usize,
+) -> bool {
+    let array_channels = unsafe {
+        assert!(!channels.is_null());
+        std::slice::from_raw_parts(channels, length)
+    };
+    let array_values = unsafe {
+        assert!(!duty_cycle.is_null());
+        std::slice::from_raw_parts(duty_cycle, length)
+    };
+    set_pwm_duty_cycles(array_channels, array_values)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Like :py:func:`set_pwm_channel_value`. This function sets the duty cycle for a list of
+    multiple channels with multiple values.\n
+    The channels are updated at the same time, by a single write to the PWM chip.\n
+    Args:\n
+        channels ([:py:class:`PwmChannel` or int]): A list of PWM channels (or indexes) to configure.\n
+        values ([u16]) : A corresponding list of duty cycle values.\n
+    Raises:\n
+        ValueError: If the lists have different lengths.\n
+    Examples:\n
+        You can use this method like :py:func:`set_pwm_channel_value`.\n
+        >>> navigator.set_pwm_channels_values([PwmChannel.Ch1, PwmChannel.Ch5], [1000, 500])"]
+fn set_pwm_channels_values_py(
+    channels: Vec<PwmChannelArg>,
+    values: Vec<u16>,
+) -> pyo3::PyResult<()> {
+    if channels.len() != values.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "The number of channels and values must match",
+        ));
+    }
+
+    let channels: Vec<usize> = channels.into_iter().map(pwm_channel_index).collect();
+    let duty_cycles: Vec<f32> = values.iter().map(|&value| value as f32 / 4096.0).collect();
+    set_pwm_duty_cycles(&channels, &duty_cycles).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_py]
+#[comment = "Like :py:func:`set_pwm_channel_duty_cycle`. This function sets the duty cycle for a list of
+    multiple channels with multiple values.\n
+    The channels are updated at the same time, by a single write to the PWM chip.\n
+    Args:\n
+        channels ([:py:class:`PwmChannel` or int]): A list of PWM channels (or indexes) to configure.\n
+        duty_cycle_values (f32) : Duty cycle count value (0.0 : 1.0).\n
+    Raises:\n
+        ValueError: If the lists have different lengths.\n
+    Examples:\n
+        You can use this method like :py:func:`set_pwm_channel_duty_cycle`.\n
+        >>> navigator.set_pwm_channels_duty_cycle_values([PwmChannel.Ch1, PwmChannel.Ch5], [0.25, 0.75])"]
+fn set_pwm_channels_duty_cycle_values_py(
+    channels: Vec<PwmChannelArg>,
+    duty_cycle_values: Vec<f32>,
+) -> pyo3::PyResult<()> {
+    if channels.len() != duty_cycle_values.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "The number of channels and duty cycle values must match",
+        ));
+    }
+
+    let channels: Vec<usize> = channels.into_iter().map(pwm_channel_index).collect();
+    set_pwm_duty_cycles(&channels, &duty_cycle_values)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn]
+#[comment_c = "Reads back the state of all PWM outputs."]
+#[comment_py = "Reads back the state of all PWM outputs.\n
+    The PCA9685 can't be read, so this is the state written by this library since the start (with
+    the chip's power-on defaults before that), or by `navigatord` when using the daemon backend.\n
+    Returns:\n
+        :py:class:`PwmState`: The output enable, achieved frequency, prescale and duty cycles.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> state = navigator.get_pwm_state()\n
+        >>> print(state.enabled, state.frequency, state.duty_cycles)"]
+fn get_pwm_state() -> PwmState {
+    allow_threads(|| with_navigator!().navigator.pwm_state()).into()
+}
+
+#[cpy_fn]
+#[comment_c = "Reads back whether the PWM outputs are enabled."]
+#[comment_py = "Reads back whether the PWM outputs are enabled.\n
+    Returns:\n
+        bool: The state set by :py:func:`set_pwm_enable`.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> enabled = navigator.get_pwm_enable()"]
+fn get_pwm_enable() -> bool {
+    allow_threads(|| with_navigator!().navigator.pwm_state().enabled)
+}
+
+#[cpy_fn]
+#[comment_c = "Reads back the achieved PWM frequency, after rounding to the closest prescale value."]
+#[comment_py = "Reads back the achieved PWM frequency, after rounding to the closest prescale value.\n
+    Returns:\n
+        float32: The frequency in [Hz].\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_pwm_freq_hz(60)\n
+        >>> frequency = navigator.get_pwm_freq_hz()"]
+fn get_pwm_freq_hz() -> f32 {
+    allow_threads(|| with_navigator!().navigator.pwm_state().frequency)
+}
+
+#[cpy_fn]
+#[comment_c = "Reads back the PWM prescale value, which sets the frequency."]
+#[comment_py = "Reads back the PWM prescale value, which sets the frequency.\n
+    Returns:\n
+        int: The prescale value (3..255).\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> prescale = navigator.get_pwm_prescale()"]
+fn get_pwm_prescale() -> u8 {
+    allow_threads(|| with_navigator!().navigator.pwm_state().prescale)
+}
+
+#[cpy_fn]
+#[comment_c = "Reads back the achieved duty cycle of the selected PWM channel."]
+#[comment_py = "Reads back the achieved duty cycle of the selected PWM channel.\n
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The PWM channel to read, or its index.\n
+    Returns:\n
+        float32: The duty cycle (0.0 : 1.0), after rounding to the counter resolution. NaN for `All`.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> duty_cycle = navigator.get_pwm_channel_duty_cycle(PwmChannel.Ch1)"]
+fn get_pwm_channel_duty_cycle(channel: PwmChannelArg) -> f32 {
+    let state = allow_threads(|| with_navigator!().navigator.pwm_state());
+    state
+        .duty_cycles
+        .get(pwm_channel_index(channel))
+        .copied()
+        .unwrap_or(f32::NAN)
+}
+
+/// Sets the slew-rate limit of the default instance, see `Instance::set_pwm_slew_rate`.
+fn set_slew_rate(channel: usize, rate: f32) -> Result<(), String> {
+    configure(|builder| {
+        for channel in pwm_channels(channel)? {
+            builder.pwm_slew_rates[channel] = rate.max(0.0);
+        }
+        Ok(())
+    })
+}
+
+#[cpy_fn_c]
+#[comment = "Limits how fast the duty cycle of the selected PWM channel changes, 0 removes the limit. \
+    Returns false if the channel is invalid, see `navigator_last_error`."]
+fn set_pwm_slew_rate_c(channel: PwmChannelArg, rate: f32) -> bool {
+    set_slew_rate(channel, rate)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Limits how fast the duty cycle of the selected PWM channel changes, 0 removes the limit.\n
+    New duty cycles set on the channel, including by the functions setting multiple channels, are
+    reached progressively by a background thread instead of in a single step.\n
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be limited, or its index.\n
+        rate (float32): Maximum duty cycle change per second, e.g. 0.5 takes 2 seconds from 0.0 to 1.0.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.set_pwm_slew_rate(PwmChannel.All, 0.5)"]
+fn set_pwm_slew_rate_py(channel: PwmChannelArg, rate: f32) -> pyo3::PyResult<()> {
+    set_slew_rate(pwm_channel_index(channel), rate).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Ramps a channel of the default instance, see `Instance::ramp_pwm_channel`.
+fn ramp_channel(channel: usize, target: f32, duration_ms: u32) -> Result<(), String> {
+    let duration = std::time::Duration::from_millis(duration_ms.into());
+    allow_threads(|| with_navigator!().ramp_pwm_channel(channel, target, duration))
+}
+
+#[cpy_fn_c]
+#[comment = "Moves the duty cycle of the selected PWM channel linearly to a target, over a duration in milliseconds. \
+    Returns false if the channel is invalid, see `navigator_last_error`."]
+fn ramp_pwm_channel_c(channel: PwmChannelArg, target: f32, duration_ms: u32) -> bool {
+    ramp_channel(channel, target, duration_ms)
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Moves the duty cycle of the selected PWM channel linearly to a target, over a duration.\n
+    The ramp runs in a background thread and this function returns right away. The channel's
+    slew-rate limit still applies, and setting a new duty cycle on the channel replaces the ramp.\n
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be ramped, or its index.\n
+        target (float32): The final duty cycle (0.0 : 1.0).\n
+        duration_ms (int): The ramp duration [ms].\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.ramp_pwm_channel(PwmChannel.Ch1, 0.1, 2000)"]
+fn ramp_pwm_channel_py(
+    channel: PwmChannelArg,
+    target: f32,
+    duration_ms: u32,
+) -> pyo3::PyResult<()> {
+    ramp_channel(pwm_channel_index(channel), target, duration_ms)
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// Stops a ramp of the default instance, see `Instance::stop_pwm_ramp`.
+fn stop_ramp(channel: usize) -> Result<(), String> {
+    NAVIGATOR
+        .with_instance(|instance| instance.stop_pwm_ramp(channel))
+        .unwrap_or_else(|| pwm_channels(channel).map(drop))
+}
+
+#[cpy_fn_c]
+#[comment = "Stops the ramp of the selected PWM channel, keeping its current duty cycle. \
+    Returns false if the channel is invalid, see `navigator_last_error`."]
+fn stop_pwm_ramp_c(channel: PwmChannelArg) -> bool {
+    stop_ramp(pwm_channel_index(channel))
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Stops the ramp of the selected PWM channel, keeping its current duty cycle.\n
+    Also stops a duty cycle change in progress due to the slew-rate limit.\n
+    Args:\n
+        channel (:py:class:`PwmChannel` or int): The channel to be stopped, or its index.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.stop_pwm_ramp(PwmChannel.All)"]
+fn stop_pwm_ramp_py(channel: PwmChannelArg) -> pyo3::PyResult<()> {
+    stop_ramp(pwm_channel_index(channel)).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+fn pwm_channel_by_name(name: &str) -> Result<usize, String> {
+    with_navigator_builder!().channel_names.pwm(name)
+}
+
+fn adc_channel_by_name(name: &str) -> Result<AdcChannel, String> {
+    with_navigator_builder!().channel_names.adc(name)
+}
+
+#[cpy_fn_c]
+#[comment = "Names a PWM channel, by its index (0 for `Ch1`), replacing its previous name. Returns false if \
+    the name is empty or the channel invalid, see `navigator_last_error`."]
+fn set_pwm_channel_name_c(name: *const std::os::raw::c_char, channel: usize) -> bool {
+    let name = unsafe { string_from_c(name) };
+    let result = configure(|builder| builder.channel_names.set_pwm(&name, channel));
+    result.map_err(handle::set_last_error).is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Names a PWM channel, to be used by :py:func:`set_output`.\n
+    Names can also be set by the `[pwm.<name>]` tables of the configuration file, see
+    :py:func:`load_config`. A channel has a single name, naming it again replaces the previous one.\n
+    Args:\n
+        name (str): The channel name, e.g. \"lights\".\n
+        channel (:py:class:`PwmChannel` or int): The channel to be named, or its index. `All` can't be named.\n
+    Raises:\n
+        ValueError: The name is empty or the channel is `All`.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import PwmChannel\n
+        >>> navigator.set_pwm_channel_name(\"lights\", PwmChannel.Ch9)\n
+        >>> navigator.set_output(\"lights\", 0.5)"]
+fn set_pwm_channel_name_py(name: String, channel: PwmChannelArg) -> pyo3::PyResult<()> {
+    configure(|builder| {
+        builder
+            .channel_names
+            .set_pwm(&name, pwm_channel_index(channel))
+    })
+    .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Names an ADC channel, replacing its previous name. Returns false if the name is empty, see \
+    `navigator_last_error`."]
+fn set_adc_channel_name_c(name: *const std::os::raw::c_char, channel: AdcChannel) -> bool {
+    let name = unsafe { string_from_c(name) };
+    let result = configure(|builder| builder.channel_names.set_adc(&name, channel));
+    result.map_err(handle::set_last_error).is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Names an ADC channel, to be used by :py:func:`read_analog`.\n
+    Names can also be set by the `[adc.<name>]` tables of the configuration file, see
+    :py:func:`load_config`. A channel has a single name, naming it again replaces the previous one.\n
+    Args:\n
+        name (str): The channel name, e.g. \"battery_voltage\".\n
+        channel (:py:class:`AdcChannel`): The channel to be named.\n
+    Raises:\n
+        ValueError: The name is empty.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> from bluerobotics_navigator import AdcChannel\n
+        >>> navigator.set_adc_channel_name(\"battery_voltage\", AdcChannel.Ch2)\n
+        >>> voltage = navigator.read_analog(\"battery_voltage\")"]
+fn set_adc_channel_name_py(name: String, channel: AdcChannel) -> pyo3::PyResult<()> {
+    configure(|builder| builder.channel_names.set_adc(&name, channel))
+        .map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Removes a PWM or ADC channel name, returns false if no channel had this name."]
+fn remove_channel_name_c(name: *const std::os::raw::c_char) -> bool {
+    let name = unsafe { string_from_c(name) };
+    configure(|builder| builder.channel_names.remove(&name))
+}
+
+#[cpy_fn_py]
+#[comment = "Removes a PWM or ADC channel name.\n
+    Args:\n
+        name (str): The channel name.\n
+    Returns:\n
+        bool: False if no channel had this name.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.remove_channel_name(\"lights\")"]
+fn remove_channel_name_py(name: String) -> bool {
+    configure(|builder| builder.channel_names.remove(&name))
+}
+
+#[cpy_fn]
+#[comment_c = "Removes all PWM and ADC channel names."]
+#[comment_py = "Removes all PWM and ADC channel names.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.clear_channel_names()"]
+fn clear_channel_names() {
+    configure(|builder| builder.channel_names.clear());
+}
+
+#[cpy_fn_c]
+#[comment = "Copies the name of a PWM channel, by its index (0 for `Ch1`), to the `length` bytes of `name`, \
+    truncated and NUL-terminated like `snprintf`. Returns the length of the name, 0 if the channel has none."]
+fn get_pwm_channel_name_c(channel: usize, name: *mut std::os::raw::c_char, length: usize) -> usize {
+    let builder = with_navigator_builder!();
+    let value = builder.channel_names.pwm_name(channel).unwrap_or_default();
+    unsafe { copy_to_c(value, name, length) }
+}
+
+#[cpy_fn_c]
+#[comment = "Copies the name of an ADC channel to the `length` bytes of `name`, truncated and NUL-terminated \
+    like `snprintf`. Returns the length of the name, 0 if the channel has none."]
+fn get_adc_channel_name_c(
+    channel: AdcChannel,
+    name: *mut std::os::raw::c_char,
+    length: usize,
+) -> usize {
+    let builder = with_navigator_builder!();
+    let value = builder.channel_names.adc_name(channel).unwrap_or_default();
+    unsafe { copy_to_c(value, name, length) }
+}
+
+#[cpy_fn_py]
+#[comment = "Returns the named PWM channels.\n
+    Returns:\n
+        dict[str, int]: The channel index (0 for `Ch1`) of each name.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> print(navigator.get_pwm_channel_names())\n
+        {'lights': 8, 'thruster_port': 0}"]
+fn get_pwm_channel_names_py() -> std::collections::BTreeMap<String, usize> {
+    with_navigator_builder!().channel_names.pwm_names().clone()
+}
+
+#[cpy_fn_py]
+#[comment = "Returns the named ADC channels.\n
+    Returns:\n
+        dict[str, :py:class:`AdcChannel`]: The channel of each name.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> for name, channel in navigator.get_adc_channel_names().items():\n
+        ...     print(name, navigator.read_adc(channel))"]
+fn get_adc_channel_names_py() -> std::collections::BTreeMap<String, AdcChannel> {
+    with_navigator_builder!().channel_names.adc_names().clone()
+}
+
+#[cpy_fn_c]
+#[comment = "Sets the duty cycle (from 0.0 to 1.0) of a named PWM channel, like `set_pwm_channel_duty_cycle`. \
+    Returns false if no channel has this name, see `navigator_last_error`."]
+fn set_output_c(name: *const std::os::raw::c_char, duty_cycle: f32) -> bool {
+    let name = unsafe { string_from_c(name) };
+    pwm_channel_by_name(&name)
+        .and_then(|channel| set_pwm_duty_cycles(&[channel], &[duty_cycle]))
+        .map_err(handle::set_last_error)
+        .is_ok()
+}
+
+#[cpy_fn_py]
+#[comment = "Sets the duty cycle of a named PWM channel, see :py:func:`set_pwm_channel_name`.\n
+    Same as :py:func:`set_pwm_channel_duty_cycle`, including its limits and slew-rate.\n
+    Args:\n
+        name (str): The channel name.\n
+        duty_cycle (f32) : Duty cycle count value (0.0 : 1.0).\n
+    Raises:\n
+        KeyError: No PWM channel has this name.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> navigator.set_output(\"lights\", 0.5)\n
+        >>> navigator.set_pwm_enable(True)"]
+fn set_output_py(name: String, duty_cycle: f32) -> pyo3::PyResult<()> {
+    let channel = pwm_channel_by_name(&name).map_err(pyo3::exceptions::PyKeyError::new_err)?;
+    set_pwm_duty_cycles(&[channel], &[duty_cycle]).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+#[cpy_fn_c]
+#[comment = "Reads a named ADC channel, like `read_adc`. Returns NaN if no channel has this name, see \
+    `navigator_last_error`."]
+fn read_analog_c(name: *const std::os::raw::c_char) -> f32 {
+    let name = unsafe { string_from_c(name) };
+    match adc_channel_by_name(&name) {
+        Ok(channel) => read_adc(channel),
+        Err(error) => {
+            handle::set_last_error(error);
+            f32::NAN
+        }
+    }
+}
+
+#[cpy_fn_py]
+#[comment = "Reads a named ADC channel, see :py:func:`set_adc_channel_name`.\n
+    Same as :py:func:`read_adc`.\n
+    Args:\n
+        name (str): The channel name.\n
+    Returns:\n
+        float32: Measurement in [V].\n
+    Raises:\n
+        KeyError: No ADC channel has this name.\n
+    Examples:\n
+        >>> import bluerobotics_navigator as navigator\n
+        >>> voltage = navigator.read_analog(\"battery_voltage\")"]
+fn read_analog_py(name: String) -> pyo3::PyResult<f32> {
+    let channel = adc_channel_by_name(&name).map_err(pyo3::exceptions::PyKeyError::new_err)?;
+    Ok(read_adc(channel))
+}
+
+// Same module as `cpy_module!` generates, with the `aio` submodule added
+macro_rules! python_module {
+    (types = [$($type:ty),*], functions = [$($function:ident),*]) => {
+        #[cfg(feature = "python")]
+        #[pyo3::pymodule]
+        fn bluerobotics_navigator(
+            py: pyo3::Python,
+            m: &pyo3::types::PyModule,
+        ) -> pyo3::PyResult<()> {
+            $(m.add_class::<$type>()?;)*
+            $(m.add_function(pyo3::wrap_pyfunction!($function, m)?)?;)*
+            aio::add_submodule(py, m)
+        }
+    };
+}
+
+// The NumPy functions are only exported when built with the `numpy` feature
+macro_rules! navigator_module {
+    ($($numpy:ident),*) => {
+        python_module!(
+            types = [
+                AdcChannel,
+                UserLed,
+                PwmChannel,
+                PwmOutputMode,
+                AccelRange,
+                GyroRange,
+                ImuDlpf,
+                AxisData,
+                ImuSample,
+                Raspberry,
+                NavigatorVersion,
+                Backend,
+                Rotation,
+                FilterChannel,
+                SensorData,
+                VibrationData,
+                Sensor,
+                SensorStatus,
+                SensorHealth,
+                CheckedValue,
+                CheckedAxisData,
+                PwmState,
+                BoardInfo,
+                DetectedHardware,
+                NavigatorBuilder,
+                NavigatorHandle
+            ],
+            functions = [
+                init,
+                deinit,
+                set_rgb_led_strip_size,
+                set_navigator_version,
+                set_raspberry_pi_version,
+                set_backend,
+                load_config,
+                set_board_orientation,
+                set_board_orientation_matrix,
+                set_pwm_clock_hz,
+                self_test,
+                navigator_lib_version,
+                navigator_rs_version,
+                navigator_abi_version,
+                navigator_capabilities,
+                get_board_info,
+                get_detected_hardware,
+                set_led,
+                get_led,
+                set_led_toggle,
+                set_led_all,
+                set_neopixel,
+                set_neopixel_rgbw,
+                read_adc_all,
+                read_adc,
+                read_pressure,
+                read_temp,
+                read_leak,
+                read_mag,
+                read_accel,
+                read_gyro,
+                read_all,
+                $($numpy,)*
+                set_accel_range,
+                set_gyro_range,
+                set_imu_dlpf,
+                set_imu_sample_rate,
+                read_imu_fifo,
+                read_vibration,
+                reset_vibration,
+                get_sensor_health,
+                reset_sensor_health,
+                read_adc_checked,
+                read_pressure_checked,
+                read_temp_checked,
+                read_mag_checked,
+                read_accel_checked,
+                read_gyro_checked,
+                set_low_pass_filter,
+                set_notch_filter,
+                set_one_euro_filter,
+                set_moving_average_filter,
+                set_median_filter,
+                clear_filter,
+                read_adc_all_filtered,
+                read_adc_filtered,
+                read_pressure_filtered,
+                read_temp_filtered,
+                read_mag_filtered,
+                read_accel_filtered,
+                read_gyro_filtered,
+                filter_low_pass,
+                filter_notch,
+                filter_one_euro,
+                filter_moving_average,
+                filter_median,
+                claim_pwm,
+                release_pwm,
+                set_pwm_enable,
+                set_pwm_freq_hz,
+                set_pwm_freq_prescale,
+                set_pwm_output_mode,
+                set_pwm_invert,
+                set_pwm_channel_value,
+                set_pwm_channel_duty_cycle,
+                set_pwm_channel_full_on,
+                set_pwm_channel_full_off,
+                set_pwm_channel_on_off,
+                set_pwm_phase_stagger,
+                set_pwm_channel_name,
+                set_adc_channel_name,
+                remove_channel_name,
+                clear_channel_names,
+                get_pwm_channel_names,
+                get_adc_channel_names,
+                set_output,
+                read_analog,
+                set_pwm_channels_value,
+                set_pwm_channels_duty_cycle,
+                set_pwm_channels_values,
+                set_pwm_channels_duty_cycle_values,
+                get_pwm_state,
+                get_pwm_enable,
+                get_pwm_freq_hz,
+                get_pwm_prescale,
+                get_pwm_channel_duty_cycle,
+                set_pwm_slew_rate,
+                ramp_pwm_channel,
+                stop_pwm_ramp
+            ]
+        );
+    };
+}
+
+#[cfg(feature = "numpy")]
+navigator_module!(read_adc_all_np, read_imu_batch);
+#[cfg(not(feature = "numpy"))]
+navigator_module!();
@@ -0,0 +1,612 @@
+//! Embedded Lua scripting, for mission scripts without the Python runtime.
+//!
+//! [`new_state`] creates a sandboxed Lua 5.4 state with the navigator functions registered in a
+//! global `navigator` table, named as in the Python module. Scripts only get the `string`,
+//! `table`, `math`, `utf8` and `coroutine` libraries, without file, process or module access, and
+//! are stopped with an error once they exceed their [`Limits`], which the script can't catch.
+//! Since the instruction and time budgets are only checked between library calls, `string.find`
+//! is then restricted to plain searches, without the other pattern matching functions, and
+//! `string.rep` and `table.move` to bounded sizes.
+//!
+//! ```lua
+//! navigator.set_led(navigator.UserLed.Led1, true)
+//! while navigator.read_pressure() < 110 do
+//!     navigator.set_pwm_channel_duty_cycle(navigator.PwmChannel.Ch1, 0.4)
+//!     navigator.sleep(0.1)
+//! end
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::PoisonError;
+use std::time::{Duration, Instant};
+
+use mlua::{Function, HookTriggers, Lua, LuaOptions, StdLib, Table};
+
+use crate::handle::Instance;
+use crate::panic_message;
+
+#[cfg(feature = "python")]
+compile_error!("The `lua` and `python` features can't be enabled together");
+
+// VM instructions executed between two budget checks
+const HOOK_INSTRUCTIONS: u32 = 1000;
+
+// Base library functions giving access to files or loading bytecode
+const UNSAFE_GLOBALS: [&str; 3] = ["dofile", "loadfile", "load"];
+
+// Pattern matching functions, whose backtracking can run for hours within a single call
+const PATTERN_FUNCTIONS: [&str; 3] = ["match", "gmatch", "gsub"];
+
+// Largest string built by `string.rep` [bytes], which also takes that many iterations when empty
+const MAX_REP_LENGTH: u32 = 16 * 1024 * 1024;
+// Most elements copied by a `table.move` call
+const MAX_MOVE_COUNT: u32 = 1024 * 1024;
+
+const ADC_CHANNELS: [(&str, crate::AdcChannel); 4] = [
+    ("Ch0", crate::AdcChannel::Ch0),
+    ("Ch1", crate::AdcChannel::Ch1),
+    ("Ch2", crate::AdcChannel::Ch2),
+    ("Ch3", crate::AdcChannel::Ch3),
+];
+
+const USER_LEDS: [(&str, crate::UserLed); 3] = [
+    ("Led1", crate::UserLed::Led1),
+    ("Led2", crate::UserLed::Led2),
+    ("Led3", crate::UserLed::Led3),
+];
+
+const PWM_CHANNELS: [&str; 17] = [
+    "Ch1", "Ch2", "Ch3", "Ch4", "Ch5", "Ch6", "Ch7", "Ch8", "Ch9", "Ch10", "Ch11", "Ch12", "Ch13",
+    "Ch14", "Ch15", "Ch16", "All",
+];
+
+/// Resources a script may use before it's stopped, `None` disables a limit.
+#[derive(Clone, Debug)]
+pub struct Limits {
+    /// Lua VM instructions, checked every 1000 instructions.
+    pub instructions: Option<u64>,
+    /// Wall-clock time since the state was created, including `navigator.sleep` calls.
+    pub time: Option<Duration>,
+    /// Memory allocated by the Lua state, in bytes.
+    pub memory: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            instructions: Some(100_000_000),
+            time: Some(Duration::from_secs(60)),
+            memory: Some(16 * 1024 * 1024),
+        }
+    }
+}
+
+/// Creates a sandboxed Lua state with the `navigator` table, enforcing `limits`.
+pub fn new_state(limits: &Limits) -> mlua::Result<Lua> {
+    let lua = Lua::new_with(
+        StdLib::STRING | StdLib::TABLE | StdLib::MATH | StdLib::UTF8 | StdLib::COROUTINE,
+        LuaOptions::new(),
+    )?;
+    let globals = lua.globals();
+    for name in UNSAFE_GLOBALS {
+        globals.raw_remove(name)?;
+    }
+
+    if let Some(memory) = limits.memory {
+        lua.set_memory_limit(memory)?;
+    }
+
+    let start = Instant::now();
+    let budget = Rc::new(Budget {
+        max_instructions: limits.instructions,
+        executed: Cell::new(0),
+        deadline: limits.time.map(|time| start + time),
+        exhausted: RefCell::new(None),
+    });
+    if budget.max_instructions.is_some() || budget.deadline.is_some() {
+        let hook_budget = budget.clone();
+        lua.set_hook(
+            HookTriggers {
+                every_nth_instruction: Some(HOOK_INSTRUCTIONS),
+                ..Default::default()
+            },
+            move |_, _| {
+                let executed = &hook_budget.executed;
+                executed.set(executed.get() + u64::from(HOOK_INSTRUCTIONS));
+                hook_budget.check()
+            },
+        )?;
+        enforce_budget(&lua, budget.clone())?;
+        limit_library_calls(&lua)?;
+    }
+
+    globals.set("navigator", navigator_table(&lua, start, budget)?)?;
+    drop(globals);
+    Ok(lua)
+}
+
+/// Runs the script at `path` in a new state, see [`new_state`].
+pub fn run_file(path: &Path, limits: &Limits) -> mlua::Result<()> {
+    let source = std::fs::read_to_string(path).map_err(mlua::Error::external)?;
+    let lua = new_state(limits)?;
+    lua.load(&source)
+        .set_name(format!("@{}", path.display()))?
+        .exec()?;
+    Ok(())
+}
+
+/// Instruction and time budget of a state. Once exhausted, every check fails again, so a script
+/// catching the error can't keep running.
+struct Budget {
+    max_instructions: Option<u64>,
+    executed: Cell<u64>,
+    deadline: Option<Instant>,
+    exhausted: RefCell<Option<String>>,
+}
+
+impl Budget {
+    fn check(&self) -> mlua::Result<()> {
+        let mut exhausted = self.exhausted.borrow_mut();
+        if exhausted.is_none() {
+            *exhausted = match (self.max_instructions, self.deadline) {
+                (Some(max), _) if self.executed.get() > max => {
+                    Some(format!("Instruction budget of {max} exhausted"))
+                }
+                (_, Some(deadline)) if Instant::now() >= deadline => {
+                    Some("Time budget exhausted".to_string())
+                }
+                _ => None,
+            };
+        }
+        match &*exhausted {
+            Some(error) => Err(mlua::Error::RuntimeError(error.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Wraps the functions catching errors, `pcall`, `xpcall` and `coroutine.resume`, to raise the
+/// budget error again once they return. Message handlers and finalizers run without the hook, the
+/// handlers are skipped once the budget is exhausted and `__gc` metamethods aren't allowed.
+fn enforce_budget(lua: &Lua, budget: Rc<Budget>) -> mlua::Result<()> {
+    let check = lua.create_function(move |_, results: mlua::MultiValue| {
+        budget.check()?;
+        Ok(results)
+    })?;
+    let globals = lua.globals();
+    let coroutine: Table = globals.get("coroutine")?;
+    let (pcall, xpcall, resume, setmetatable): (Function, Function, Function, Function) = lua
+        .load(
+            r#"
+            local pcall, xpcall, resume, setmetatable, check = ...
+            return function(...) return check(pcall(...)) end,
+                function(f, handler, ...)
+                    return check(xpcall(f, function(...) check() return handler(...) end, ...))
+                end,
+                function(...) return check(resume(...)) end,
+                function(table, metatable)
+                    if type(metatable) == "table" and rawget(metatable, "__gc") ~= nil then
+                        error("__gc metamethods are not allowed", 2)
+                    end
+                    return setmetatable(table, metatable)
+                end
+            "#,
+        )
+        .set_name("=budget")?
+        .call((
+            globals.get::<_, Function>("pcall")?,
+            globals.get::<_, Function>("xpcall")?,
+            coroutine.get::<_, Function>("resume")?,
+            globals.get::<_, Function>("setmetatable")?,
+            check,
+        ))?;
+    globals.set("pcall", pcall)?;
+    globals.set("xpcall", xpcall)?;
+    globals.set("setmetatable", setmetatable)?;
+    coroutine.set("resume", resume)
+}
+
+/// Restricts the library functions that could run past the budget within a single call.
+fn limit_library_calls(lua: &Lua) -> mlua::Result<()> {
+    let globals = lua.globals();
+    let string: Table = globals.get("string")?;
+    let table: Table = globals.get("table")?;
+    let (find, rep, move_): (Function, Function, Function) = lua
+        .load(
+            r#"
+            local find, len, rep, move, max_length, max_count = ...
+            return function(s, pattern, init, plain)
+                    if not plain and find(pattern, "[%^%$%*%+%?%.%(%[%%%-]") then
+                        error("Only plain searches are allowed, use string.find(s, text, init, true)", 2)
+                    end
+                    return find(s, pattern, init, true)
+                end,
+                function(s, n, sep)
+                    local size = math.max(len(s) + (sep and len(sep) or 0), 1)
+                    if (tonumber(n) or 0) * size > max_length then
+                        error("string.rep results are limited to " .. max_length .. " bytes", 2)
+                    end
+                    return rep(s, n, sep)
+                end,
+                function(a1, f, e, t, a2)
+                    if (tonumber(e) or 0) - (tonumber(f) or 0) >= max_count then
+                        error("table.move is limited to " .. max_count .. " elements", 2)
+                    end
+                    return move(a1, f, e, t, a2)
+                end
+            "#,
+        )
+        .set_name("=limits")?
+        .call((
+            string.get::<_, Function>("find")?,
+            string.get::<_, Function>("len")?,
+            string.get::<_, Function>("rep")?,
+            table.get::<_, Function>("move")?,
+            MAX_REP_LENGTH,
+            MAX_MOVE_COUNT,
+        ))?;
+    for name in PATTERN_FUNCTIONS {
+        string.raw_remove(name)?;
+    }
+    string.set("find", find)?;
+    string.set("rep", rep)?;
+    table.set("move", move_)
+}
+
+/// Runs a navigator call, turning its panics into Lua errors the script can `pcall`. The calls
+/// don't go through the `extern "C"` functions, where a panic can't unwind.
+fn guarded<T>(call: impl FnOnce() -> T) -> mlua::Result<T> {
+    catch_unwind(AssertUnwindSafe(call))
+        .map_err(|error| mlua::Error::RuntimeError(panic_message(error)))
+}
+
+/// Runs `call` on the default instance, opened on first use, see [`guarded`].
+fn with_instance<T>(call: impl FnOnce(&mut Instance) -> T) -> mlua::Result<T> {
+    guarded(|| crate::with_default_instance(call))
+}
+
+/// Finds the enum value with the given index, as listed in its Lua table.
+fn variant<T: Clone>(kind: &str, variants: &[(&str, T)], index: usize) -> mlua::Result<T> {
+    variants
+        .get(index)
+        .map(|(_, value)| value.clone())
+        .ok_or_else(|| mlua::Error::RuntimeError(format!("Invalid {kind}: {index}")))
+}
+
+/// Validates a channel given as a `navigator.PwmChannel` value.
+fn pwm_channel(channel: usize) -> mlua::Result<usize> {
+    match channel < PWM_CHANNELS.len() {
+        true => Ok(channel),
+        false => Err(mlua::Error::RuntimeError(format!(
+            "Invalid PWM channel: {channel}"
+        ))),
+    }
+}
+
+/// Validates colors given as tables of `N` components.
+fn colors<const N: usize>(colors: Vec<Vec<u8>>) -> mlua::Result<Vec<[u8; N]>> {
+    colors
+        .into_iter()
+        .map(|color| {
+            color.try_into().map_err(|color: Vec<u8>| {
+                mlua::Error::RuntimeError(format!(
+                    "Expected {N} color components, got {}",
+                    color.len()
+                ))
+            })
+        })
+        .collect()
+}
+
+fn enum_table(lua: &Lua, names: impl Iterator<Item = &'static str>) -> mlua::Result<Table> {
+    lua.create_table_from(names.enumerate().map(|(index, name)| (name, index)))
+}
+
+fn axis_table(lua: &Lua, axis: crate::AxisData) -> mlua::Result<Table> {
+    lua.create_table_from([("x", axis.x), ("y", axis.y), ("z", axis.z)])
+}
+
+fn navigator_table(lua: &Lua, start: Instant, budget: Rc<Budget>) -> mlua::Result<Table> {
+    let table = lua.create_table()?;
+
+    table.set(
+        "AdcChannel",
+        enum_table(lua, ADC_CHANNELS.iter().map(|(name, _)| *name))?,
+    )?;
+    table.set(
+        "UserLed",
+        enum_table(lua, USER_LEDS.iter().map(|(name, _)| *name))?,
+    )?;
+    table.set("PwmChannel", enum_table(lua, PWM_CHANNELS.into_iter())?)?;
+
+    // Like in the Python module, the navigator is opened by the first call and always passes
+    table.set("init", lua.create_function(|_, ()| Ok(()))?)?;
+    table.set(
+        "deinit",
+        lua.create_function(|_, ()| guarded(|| crate::NAVIGATOR.close()))?,
+    )?;
+    table.set("self_test", lua.create_function(|_, ()| Ok(true))?)?;
+
+    // Time since the script started in [s], and a sleep that respects the time budget
+    table.set(
+        "clock",
+        lua.create_function(move |_, ()| Ok(start.elapsed().as_secs_f64()))?,
+    )?;
+    table.set(
+        "sleep",
+        lua.create_function(move |_, seconds: f64| {
+            let duration = Duration::try_from_secs_f64(seconds).map_err(|_| {
+                mlua::Error::RuntimeError(format!("Invalid sleep duration: {seconds}"))
+            })?;
+            let wake = Instant::now() + duration;
+            std::thread::sleep(match budget.deadline {
+                Some(deadline) if deadline < wake => {
+                    deadline.saturating_duration_since(Instant::now())
+                }
+                _ => duration,
+            });
+            budget.check()
+        })?,
+    )?;
+
+    table.set(
+        "get_detected_hardware",
+        lua.create_function(|lua, ()| {
+            let hardware = guarded(crate::detected_hardware)?;
+            let result = lua.create_table()?;
+            result.set(
+                "raspberry_pi_version",
+                format!("{:?}", hardware.raspberry_pi_version),
+            )?;
+            result.set(
+                "navigator_version",
+                format!("{:?}", hardware.navigator_version),
+            )?;
+            result.set("raspberry_pi_detected", hardware.raspberry_pi_detected)?;
+            result.set("navigator_detected", hardware.navigator_detected)?;
+            Ok(result)
+        })?,
+    )?;
+
+    table.set(
+        "set_led",
+        lua.create_function(|_, (select, state): (usize, bool)| {
+            let select = variant("LED", &USER_LEDS, select)?;
+            with_instance(|instance| instance.navigator.set_led(select.into(), state))
+        })?,
+    )?;
+    table.set(
+        "get_led",
+        lua.create_function(|_, select: usize| {
+            let select = variant("LED", &USER_LEDS, select)?;
+            with_instance(|instance| instance.navigator.get_led(select.into()))
+        })?,
+    )?;
+    table.set(
+        "set_led_toggle",
+        lua.create_function(|_, select: usize| {
+            let select = variant("LED", &USER_LEDS, select)?;
+            with_instance(|instance| instance.navigator.set_led_toggle(select.into()))
+        })?,
+    )?;
+    table.set(
+        "set_led_all",
+        lua.create_function(|_, state: bool| {
+            with_instance(|instance| {
+                for (_, led) in USER_LEDS {
+                    instance.navigator.set_led(led.into(), state);
+                }
+            })
+        })?,
+    )?;
+    table.set(
+        "set_neopixel",
+        lua.create_function(|_, rgb_array: Vec<Vec<u8>>| {
+            let colors = colors::<3>(rgb_array)?;
+            with_instance(|instance| instance.navigator.set_neopixel(&colors))
+        })?,
+    )?;
+    table.set(
+        "set_neopixel_rgbw",
+        lua.create_function(|_, rgbw_array: Vec<Vec<u8>>| {
+            let colors = colors::<4>(rgbw_array)?;
+            with_instance(|instance| instance.navigator.set_neopixel_rgbw(&colors))
+        })?,
+    )?;
+
+    table.set(
+        "read_adc_all",
+        lua.create_function(|_, ()| with_instance(Instance::read_adc_all))?,
+    )?;
+    table.set(
+        "read_adc",
+        lua.create_function(|_, channel: usize| {
+            let channel = variant("ADC channel", &ADC_CHANNELS, channel)?;
+            with_instance(|instance| instance.read_adc(channel))
+        })?,
+    )?;
+    table.set(
+        "read_pressure",
+        lua.create_function(|_, ()| with_instance(Instance::read_pressure))?,
+    )?;
+    table.set(
+        "read_temp",
+        lua.create_function(|_, ()| with_instance(Instance::read_temp))?,
+    )?;
+    table.set(
+        "read_leak",
+        lua.create_function(|_, ()| with_instance(|instance| instance.navigator.read_leak()))?,
+    )?;
+    table.set(
+        "read_mag",
+        lua.create_function(|lua, ()| axis_table(lua, with_instance(Instance::read_mag)?))?,
+    )?;
+    table.set(
+        "read_accel",
+        lua.create_function(|lua, ()| axis_table(lua, with_instance(Instance::read_accel)?))?,
+    )?;
+    table.set(
+        "read_gyro",
+        lua.create_function(|lua, ()| axis_table(lua, with_instance(Instance::read_gyro)?))?,
+    )?;
+    table.set(
+        "read_all",
+        lua.create_function(|lua, ()| {
+            let data = with_instance(Instance::read_all)?;
+            let result = lua.create_table()?;
+            result.set("accelerometer", axis_table(lua, data.accelerometer)?)?;
+            result.set("gyro", axis_table(lua, data.gyro)?)?;
+            result.set("magnetometer", axis_table(lua, data.magnetometer)?)?;
+            result.set("pressure", data.pressure)?;
+            result.set("temperature", data.temperature)?;
+            result.set("adc", data.adc.to_vec())?;
+            result.set("leak", data.leak)?;
+            result.set("timestamp", data.timestamp)?;
+            Ok(result)
+        })?,
+    )?;
+    table.set(
+        "get_sensor_health",
+        lua.create_function(|lua, ()| {
+            guarded(crate::sensor_health_all)?
+                .into_iter()
+                .map(|health| {
+                    let result = lua.create_table()?;
+                    result.set("sensor", format!("{:?}", health.sensor))?;
+                    result.set("status", format!("{:?}", health.status))?;
+                    result.set("sample_count", health.sample_count)?;
+                    result.set("error_count", health.error_count)?;
+                    result.set("invalid_count", health.invalid_count)?;
+                    result.set("consecutive_errors", health.consecutive_errors)?;
+                    result.set("last_good_timestamp", health.last_good_timestamp)?;
+                    result.set("stuck", health.stuck)?;
+                    result.set("out_of_range", health.out_of_range)?;
+                    Ok(result)
+                })
+                .collect::<mlua::Result<Vec<_>>>()
+        })?,
+    )?;
+    table.set(
+        "reset_sensor_health",
+        lua.create_function(|_, ()| {
+            guarded(|| {
+                crate::NAVIGATOR.with_instance(Instance::reset_sensor_health);
+            })
+        })?,
+    )?;
+
+    table.set(
+        "claim_pwm",
+        lua.create_function(|_, ()| {
+            with_instance(|instance| instance.navigator.claim_pwm())?
+                .map_err(mlua::Error::RuntimeError)
+        })?,
+    )?;
+    table.set(
+        "release_pwm",
+        lua.create_function(|_, ()| with_instance(|instance| instance.navigator.release_pwm()))?,
+    )?;
+    table.set(
+        "set_pwm_enable",
+        lua.create_function(|_, state: bool| {
+            with_instance(|instance| instance.navigator.set_pwm_enable(state))
+        })?,
+    )?;
+    table.set(
+        "set_pwm_freq_hz",
+        lua.create_function(|_, freq: f32| {
+            with_instance(|instance| instance.navigator.set_pwm_frequency(freq))?
+                .map_err(mlua::Error::RuntimeError)
+        })?,
+    )?;
+    table.set(
+        "set_pwm_freq_prescale",
+        lua.create_function(|_, prescale: u8| {
+            with_instance(|instance| instance.navigator.set_pwm_prescale(prescale))?
+                .map_err(mlua::Error::RuntimeError)
+        })?,
+    )?;
+    table.set(
+        "set_pwm_channel_duty_cycle",
+        lua.create_function(|_, (channel, duty_cycle): (usize, f32)| {
+            let channel = pwm_channel(channel)?;
+            with_instance(|instance| instance.set_pwm_duty_cycles(&[channel], &[duty_cycle]))?
+                .map_err(mlua::Error::RuntimeError)
+        })?,
+    )?;
+    table.set(
+        "set_pwm_channels_duty_cycle_values",
+        lua.create_function(|_, (channels, duty_cycle_values): (Vec<usize>, Vec<f32>)| {
+            if channels.len() != duty_cycle_values.len() {
+                return Err(mlua::Error::RuntimeError(
+                    "The number of channels and duty cycle values must match".to_string(),
+                ));
+            }
+            let channels = channels
+                .into_iter()
+                .map(pwm_channel)
+                .collect::<mlua::Result<Vec<_>>>()?;
+            with_instance(|instance| instance.set_pwm_duty_cycles(&channels, &duty_cycle_values))?
+                .map_err(mlua::Error::RuntimeError)
+        })?,
+    )?;
+    table.set(
+        "set_output",
+        lua.create_function(|_, (name, duty_cycle): (String, f32)| {
+            let channel = crate::pwm_channel_by_name(&name).map_err(mlua::Error::RuntimeError)?;
+            with_instance(|instance| instance.set_pwm_duty_cycles(&[channel], &[duty_cycle]))?
+                .map_err(mlua::Error::RuntimeError)
+        })?,
+    )?;
+    table.set(
+        "read_analog",
+        lua.create_function(|_, name: String| {
+            let channel = crate::adc_channel_by_name(&name).map_err(mlua::Error::RuntimeError)?;
+            with_instance(|instance| instance.read_adc(channel))
+        })?,
+    )?;
+    // Names map to the values of the `PwmChannel` and `AdcChannel` tables
+    table.set(
+        "get_pwm_channel_names",
+        lua.create_function(|_, ()| {
+            Ok(crate::NAVIGATORBUILDER
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .channel_names
+                .pwm_names()
+                .clone())
+        })?,
+    )?;
+    table.set(
+        "get_adc_channel_names",
+        lua.create_function(|_, ()| {
+            let names: BTreeMap<String, usize> = crate::NAVIGATORBUILDER
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .channel_names
+                .adc_names()
+                .iter()
+                .map(|(name, channel)| (name.clone(), channel.clone() as usize))
+                .collect();
+            Ok(names)
+        })?,
+    )?;
+    table.set(
+        "get_pwm_state",
+        lua.create_function(|lua, ()| {
+            let state = with_instance(|instance| instance.navigator.pwm_state())?;
+            let result = lua.create_table()?;
+            result.set("enabled", state.enabled)?;
+            result.set("frequency", state.frequency)?;
+            result.set("prescale", state.prescale)?;
+            result.set("duty_cycles", state.duty_cycles.to_vec())?;
+            Ok(result)
+        })?,
+    )?;
+
+    Ok(table)
+}
@@ -0,0 +1,1317 @@
+//! Node.js addon, exposing the same functions as the Python module.
+//!
+//! Functions are named in camelCase, enums map to objects of numbers and structures to plain
+//! objects. Sensor streams sample on a Rust thread and are consumed with `for await`, see
+//! `node/index.js`. The enums are the crate's, exported by `navigator_enum!`.
+
+// napi-derive doesn't register the exports in test builds
+#![cfg_attr(test, allow(dead_code))]
+
+use std::collections::{HashMap, VecDeque};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use napi::bindgen_prelude::*;
+use napi::{Env, JsObject, Task};
+use napi_derive::napi;
+
+use crate::handle::Instance;
+use crate::{
+    panic_message, AccelRange, AdcChannel, Backend, FilterChannel, GyroRange, ImuDlpf,
+    NavigatorBuilderManager, NavigatorVersion, PwmChannel, PwmOutputMode, Raspberry, Rotation,
+    Sensor, SensorStatus, UserLed,
+};
+
+#[cfg(feature = "python")]
+compile_error!("The `node` and `python` features can't be enabled together");
+
+// Samples kept by a stream while nobody is waiting, older ones are dropped
+const STREAM_BUFFER_SIZE: usize = 64;
+
+/// Board-oriented direction axes (x is forwards, y is right, z is down).
+#[napi(object)]
+pub struct AxisData {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl From<crate::AxisData> for AxisData {
+    fn from(data: crate::AxisData) -> Self {
+        Self {
+            x: data.x.into(),
+            y: data.y.into(),
+            z: data.z.into(),
+        }
+    }
+}
+
+/// Measurements from all sensors, taken in a single pass.
+#[napi(object)]
+pub struct SensorData {
+    pub accelerometer: AxisData,
+    pub gyro: AxisData,
+    pub magnetometer: AxisData,
+    pub pressure: f64,
+    pub temperature: f64,
+    pub adc: Vec<f64>,
+    pub leak: bool,
+    /// Time of the sample in microseconds since the UNIX epoch.
+    pub timestamp: i64,
+}
+
+impl From<crate::SensorData> for SensorData {
+    fn from(data: crate::SensorData) -> Self {
+        Self {
+            accelerometer: data.accelerometer.into(),
+            gyro: data.gyro.into(),
+            magnetometer: data.magnetometer.into(),
+            pressure: data.pressure.into(),
+            temperature: data.temperature.into(),
+            adc: data.adc.map(f64::from).to_vec(),
+            leak: data.leak,
+            timestamp: data.timestamp as i64,
+        }
+    }
+}
+
+/// Hardware found by the detection used for the `Auto` versions, a version falls back to `Pi4` or
+/// `Version1` when it wasn't detected.
+#[napi(object)]
+pub struct DetectedHardware {
+    pub raspberry_pi_version: Raspberry,
+    pub navigator_version: NavigatorVersion,
+    pub raspberry_pi_detected: bool,
+    pub navigator_detected: bool,
+}
+
+impl From<crate::DetectedHardware> for DetectedHardware {
+    fn from(hardware: crate::DetectedHardware) -> Self {
+        Self {
+            raspberry_pi_version: hardware.raspberry_pi_version,
+            navigator_version: hardware.navigator_version,
+            raspberry_pi_detected: hardware.raspberry_pi_detected,
+            navigator_detected: hardware.navigator_detected,
+        }
+    }
+}
+
+/// Board configuration of the default instance, with `Auto` versions resolved. `daemon` is true
+/// when the hardware is accessed through `navigatord`.
+#[napi(object)]
+pub struct BoardInfo {
+    pub navigator_version: NavigatorVersion,
+    pub raspberry_pi_version: Raspberry,
+    pub rgb_led_strip_size: u32,
+    pub daemon: bool,
+}
+
+impl From<crate::BoardInfo> for BoardInfo {
+    fn from(info: crate::BoardInfo) -> Self {
+        Self {
+            navigator_version: info.navigator_version,
+            raspberry_pi_version: info.raspberry_pi_version,
+            rgb_led_strip_size: info.rgb_led_strip_size as u32,
+            daemon: info.daemon,
+        }
+    }
+}
+
+/// Health of a sensor, from the reads of the default instance.
+#[napi(object)]
+pub struct SensorHealth {
+    pub sensor: Sensor,
+    pub status: SensorStatus,
+    pub sample_count: i64,
+    /// Failed reads.
+    pub error_count: i64,
+    /// Samples that were stuck or out of range.
+    pub invalid_count: i64,
+    /// Failed reads since the last successful one.
+    pub consecutive_errors: u32,
+    /// Time of the last valid sample in microseconds since the UNIX epoch, 0 if there is none.
+    pub last_good_timestamp: i64,
+    pub stuck: bool,
+    pub out_of_range: bool,
+}
+
+impl From<crate::SensorHealth> for SensorHealth {
+    fn from(health: crate::SensorHealth) -> Self {
+        Self {
+            sensor: health.sensor,
+            status: health.status,
+            sample_count: health.sample_count as i64,
+            error_count: health.error_count as i64,
+            invalid_count: health.invalid_count as i64,
+            consecutive_errors: health.consecutive_errors,
+            last_good_timestamp: health.last_good_timestamp as i64,
+            stuck: health.stuck,
+            out_of_range: health.out_of_range,
+        }
+    }
+}
+
+/// Accelerometer, gyroscope and magnetometer measurements.
+#[napi(object)]
+pub struct ImuData {
+    pub accelerometer: AxisData,
+    pub gyro: AxisData,
+    pub magnetometer: AxisData,
+}
+
+impl From<crate::SensorData> for ImuData {
+    fn from(data: crate::SensorData) -> Self {
+        Self {
+            accelerometer: data.accelerometer.into(),
+            gyro: data.gyro.into(),
+            magnetometer: data.magnetometer.into(),
+        }
+    }
+}
+
+/// Accelerometer and gyroscope measurements of a sample taken from the IMU FIFO.
+#[napi(object)]
+pub struct ImuSample {
+    pub accelerometer: AxisData,
+    pub gyro: AxisData,
+}
+
+impl From<crate::ImuSample> for ImuSample {
+    fn from(sample: crate::ImuSample) -> Self {
+        Self {
+            accelerometer: sample.accelerometer.into(),
+            gyro: sample.gyro.into(),
+        }
+    }
+}
+
+/// ArduPilot-style vibration levels in [m/s²] and accelerometer clipping count.
+#[napi(object)]
+pub struct VibrationData {
+    pub vibration: AxisData,
+    pub clipping: u32,
+}
+
+impl From<crate::VibrationData> for VibrationData {
+    fn from(data: crate::VibrationData) -> Self {
+        Self {
+            vibration: data.vibration.into(),
+            clipping: data.clipping,
+        }
+    }
+}
+
+/// Measurement with its validity, `value` is NaN when the read failed, or the value is stuck or
+/// out of range.
+#[napi(object)]
+pub struct CheckedValue {
+    pub value: f64,
+    pub valid: bool,
+}
+
+impl From<crate::CheckedValue> for CheckedValue {
+    fn from(checked: crate::CheckedValue) -> Self {
+        Self {
+            value: checked.value.into(),
+            valid: checked.valid,
+        }
+    }
+}
+
+/// Axis measurements with their validity, the axes are NaN when the read failed, or a value is
+/// stuck or out of range.
+#[napi(object)]
+pub struct CheckedAxisData {
+    pub value: AxisData,
+    pub valid: bool,
+}
+
+impl From<crate::CheckedAxisData> for CheckedAxisData {
+    fn from(checked: crate::CheckedAxisData) -> Self {
+        Self {
+            value: checked.value.into(),
+            valid: checked.valid,
+        }
+    }
+}
+
+/// State of the PWM outputs, as written to the PCA9685.
+#[napi(object)]
+pub struct PwmState {
+    pub enabled: bool,
+    pub frequency: f64,
+    pub prescale: u32,
+    pub duty_cycles: Vec<f64>,
+}
+
+impl From<crate::backend::PwmState> for PwmState {
+    fn from(state: crate::backend::PwmState) -> Self {
+        Self {
+            enabled: state.enabled,
+            frequency: state.frequency.into(),
+            prescale: state.prescale.into(),
+            duty_cycles: state.duty_cycles.map(f64::from).to_vec(),
+        }
+    }
+}
+
+/// Runs a navigator call, throwing a JS error if it panics. The calls use the Rust API, not the
+/// `extern "C"` functions, which can't report a failure through a panic.
+fn guarded<T>(call: impl FnOnce() -> T) -> Result<T> {
+    catch_unwind(AssertUnwindSafe(call)).map_err(|error| Error::from_reason(panic_message(error)))
+}
+
+/// Runs `call` on the default instance, opened on first use, see [`guarded`].
+fn with_instance<T>(call: impl FnOnce(&mut Instance) -> T) -> Result<T> {
+    guarded(|| crate::with_default_instance(call))
+}
+
+/// Reads the default instance with `read`, the `invalid` value instead of throwing if it fails.
+fn checked<T>(read: impl FnOnce(&mut Instance) -> T, invalid: fn() -> T) -> T {
+    crate::checked_read(|| crate::with_default_instance(read), invalid)
+}
+
+/// Configuration of the default instance, for the settings only read when it's opened.
+fn builder() -> MutexGuard<'static, NavigatorBuilderManager> {
+    crate::NAVIGATORBUILDER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Validates a channel given as a `PwmChannel` value.
+fn pwm_channel(channel: u32) -> Result<usize> {
+    match channel as usize {
+        channel if channel <= PwmChannel::All as usize => Ok(channel),
+        _ => Err(Error::from_reason(format!(
+            "Invalid PWM channel: {channel}"
+        ))),
+    }
+}
+
+/// Validates channels given as `PwmChannel` values.
+fn pwm_channel_list(channels: Vec<u32>) -> Result<Vec<usize>> {
+    channels.into_iter().map(pwm_channel).collect()
+}
+
+/// Sets the duty cycles of the default instance, throwing for invalid channels.
+fn set_duty_cycles(channels: &[usize], duty_cycles: &[f32]) -> Result<()> {
+    with_instance(|instance| instance.set_pwm_duty_cycles(channels, duty_cycles))?
+        .map_err(Error::from_reason)
+}
+
+/// Reads back the state of the PWM outputs of the default instance.
+fn pwm_state() -> Result<crate::backend::PwmState> {
+    with_instance(|instance| instance.navigator.pwm_state())
+}
+
+/// Attaches a filter to a sensor channel, throwing for invalid parameters.
+fn set_filter(channel: FilterChannel, spec: crate::filters::FilterSpec) -> Result<()> {
+    crate::set_channel_filter(channel, spec).map_err(Error::from_reason)
+}
+
+/// Filters an array of samples, throwing for invalid parameters.
+fn filter_array(
+    values: Vec<f64>,
+    spec: crate::filters::FilterSpec,
+    sample_rate: f32,
+) -> Result<Vec<f64>> {
+    let mut values: Vec<f32> = values.into_iter().map(|value| value as f32).collect();
+    spec.apply_slice(&mut values, sample_rate)
+        .map_err(Error::from_reason)?;
+    Ok(values.into_iter().map(f64::from).collect())
+}
+
+/// Validates colors given as arrays of `N` components.
+fn colors<const N: usize>(colors: Vec<Vec<u8>>) -> Result<Vec<[u8; N]>> {
+    colors
+        .into_iter()
+        .map(|color| {
+            color.try_into().map_err(|color: Vec<u8>| {
+                Error::from_reason(format!("Expected {N} color components, got {color:?}"))
+            })
+        })
+        .collect()
+}
+
+fn values(values: Vec<f32>) -> Vec<f64> {
+    values.into_iter().map(f64::from).collect()
+}
+
+/// Initializes the Navigator module with default settings (not necessary).
+#[napi]
+pub fn init() {
+    // Keep to avoid API break
+}
+
+/// Releases the default instance, it's created again by the next call.
+#[napi]
+pub fn deinit() -> Result<()> {
+    guarded(|| crate::NAVIGATOR.close())
+}
+
+/// Sets the size of the navigator led strip (1 is the default), should be called before `init`.
+#[napi]
+pub fn set_rgb_led_strip_size(size: u32) {
+    builder().rgb_led_strip_size = size as usize;
+}
+
+/// Sets the navigator version, `Auto` (the default) detects it.
+#[napi]
+pub fn set_navigator_version(version: NavigatorVersion) {
+    builder().navigator_version = version;
+}
+
+/// Sets the raspberry pi version, `Auto` (the default) detects it.
+#[napi]
+pub fn set_raspberry_pi_version(version: Raspberry) {
+    builder().raspberry_pi_version = version;
+}
+
+/// Runs some tests on available sensors, then returns the result (not necessary).
+#[napi]
+pub fn self_test() -> bool {
+    // Keep to avoid API break
+    true
+}
+
+/// Returns the library version (e.g. `0.1.1`).
+#[napi]
+pub fn navigator_lib_version() -> String {
+    crate::without_nul(crate::LIB_VERSION)
+}
+
+/// Returns the version of the navigator-rs crate used to access the hardware.
+#[napi]
+pub fn navigator_rs_version() -> String {
+    crate::without_nul(crate::NAVIGATOR_RS_VERSION)
+}
+
+/// Returns the ABI version of the C library, incremented on incompatible changes.
+#[napi]
+pub fn navigator_abi_version() -> u32 {
+    crate::NAVIGATOR_ABI_VERSION
+}
+
+/// Returns the capabilities of the library, as a bitmask (bit 6 is set for this addon).
+#[napi]
+pub fn navigator_capabilities() -> u32 {
+    crate::capabilities()
+}
+
+/// Returns the board configuration used by the default instance.
+#[napi]
+pub fn get_board_info() -> Result<BoardInfo> {
+    let detected = guarded(crate::detected_hardware)?;
+    Ok(crate::board_info(detected).into())
+}
+
+/// Returns the Raspberry Pi and Navigator versions found by the detection.
+#[napi]
+pub fn get_detected_hardware() -> Result<DetectedHardware> {
+    guarded(crate::detected_hardware).map(DetectedHardware::from)
+}
+
+/// Selects how the hardware is accessed, should be called before `init`. With `Backend.Daemon`,
+/// every call is forwarded to `navigatord` through `socketPath` (`/run/navigator.sock` by default).
+#[napi]
+pub fn set_backend(backend: Backend, socket_path: Option<String>) {
+    let mut builder = builder();
+    builder.backend = backend;
+    builder.daemon_socket_path =
+        socket_path.unwrap_or_else(|| crate::daemon::DEFAULT_SOCKET_PATH.to_string());
+}
+
+/// Applies a TOML configuration file, should be called before `init`. Throws if the file can't be
+/// read or is invalid, leaving the configuration unchanged.
+#[napi]
+pub fn load_config(path: String) -> Result<()> {
+    let config = crate::config::load(Path::new(&path))
+        .map_err(|error| Error::from_reason(error.to_string()))?;
+    crate::configure(|builder| builder.apply_config(&config));
+    Ok(())
+}
+
+/// Sets the frequency of the clock connected to the PWM chip (PCA9685) EXTCLK pin, should be called
+/// before `init`. The Navigator board uses a 24.576 MHz clock (the default).
+#[napi]
+pub fn set_pwm_clock_hz(clock: f64) {
+    builder().pwm_clock_hz = clock as f32;
+}
+
+/// Sets how the board is mounted in the vehicle, applied to the accelerometer, gyroscope and
+/// magnetometer readings.
+#[napi]
+pub fn set_board_orientation(rotation: Rotation) {
+    crate::configure(|builder| builder.orientation = rotation.into());
+}
+
+/// Sets how the board is mounted in the vehicle with a 3x3 matrix rotating board axes into vehicle
+/// axes, for mountings not covered by `Rotation`.
+#[napi]
+pub fn set_board_orientation_matrix(matrix: Vec<Vec<f64>>) -> Result<()> {
+    let mut orientation = [[0.0; 3]; 3];
+    if matrix.len() != 3 || matrix.iter().any(|row| row.len() != 3) {
+        return Err(Error::from_reason(format!(
+            "Expected a 3x3 matrix, got {matrix:?}"
+        )));
+    }
+    for (row, values) in orientation.iter_mut().zip(&matrix) {
+        for (value, &input) in row.iter_mut().zip(values) {
+            *value = input as f32;
+        }
+    }
+    crate::configure(|builder| builder.orientation = orientation);
+    Ok(())
+}
+
+/// Names a PWM channel (`PwmChannel.All` can't be named), replacing its previous name.
+#[napi]
+pub fn set_pwm_channel_name(name: String, channel: u32) -> Result<()> {
+    let channel = pwm_channel(channel)?;
+    crate::configure(|builder| builder.channel_names.set_pwm(&name, channel))
+        .map_err(Error::from_reason)
+}
+
+/// Names an ADC channel, replacing its previous name.
+#[napi]
+pub fn set_adc_channel_name(name: String, channel: AdcChannel) -> Result<()> {
+    crate::configure(|builder| builder.channel_names.set_adc(&name, channel))
+        .map_err(Error::from_reason)
+}
+
+/// Removes a PWM or ADC channel name, returns false if no channel had this name.
+#[napi]
+pub fn remove_channel_name(name: String) -> bool {
+    crate::configure(|builder| builder.channel_names.remove(&name))
+}
+
+/// Removes all PWM and ADC channel names.
+#[napi]
+pub fn clear_channel_names() {
+    crate::configure(|builder| builder.channel_names.clear());
+}
+
+/// Returns the `PwmChannel` of each named PWM channel.
+#[napi(ts_return_type = "Record<string, PwmChannel>")]
+pub fn get_pwm_channel_names() -> HashMap<String, u32> {
+    builder()
+        .channel_names
+        .pwm_names()
+        .iter()
+        .map(|(name, channel)| (name.clone(), *channel as u32))
+        .collect()
+}
+
+/// Returns the `AdcChannel` of each named ADC channel.
+#[napi(ts_return_type = "Record<string, AdcChannel>")]
+pub fn get_adc_channel_names() -> HashMap<String, u32> {
+    builder()
+        .channel_names
+        .adc_names()
+        .iter()
+        .map(|(name, channel)| (name.clone(), channel.clone() as u32))
+        .collect()
+}
+
+/// Sets the duty cycle of a named PWM channel, like `setPwmChannelDutyCycle`.
+#[napi]
+pub fn set_output(name: String, duty_cycle: f64) -> Result<()> {
+    with_instance(|instance| instance.set_output(&name, duty_cycle as f32))?
+        .map_err(Error::from_reason)
+}
+
+/// Reads a named ADC channel, like `readAdc`.
+#[napi]
+pub fn read_analog(name: String) -> Result<f64> {
+    let value = with_instance(|instance| instance.read_analog(&name))?;
+    value.map(f64::from).map_err(Error::from_reason)
+}
+
+/// Sets the state of the selected onboard LED.
+#[napi]
+pub fn set_led(select: UserLed, state: bool) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_led(select.into(), state))
+}
+
+/// Gets the selected onboard LED output state.
+#[napi]
+pub fn get_led(select: UserLed) -> Result<bool> {
+    with_instance(|instance| instance.navigator.get_led(select.into()))
+}
+
+/// Toggle the output of the selected LED.
+#[napi]
+pub fn set_led_toggle(select: UserLed) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_led_toggle(select.into()))
+}
+
+/// Sets all user LEDs to the desired state.
+#[napi]
+pub fn set_led_all(state: bool) -> Result<()> {
+    with_instance(|instance| {
+        for led in [UserLed::Led1, UserLed::Led2, UserLed::Led3] {
+            instance.navigator.set_led(led.into(), state);
+        }
+    })
+}
+
+/// Set the color brightnesses of a connected NeoPixel LED array, as `[[r, g, b], ...]`.
+#[napi]
+pub fn set_neopixel(rgb_array: Vec<Vec<u8>>) -> Result<()> {
+    let colors = colors::<3>(rgb_array)?;
+    with_instance(|instance| instance.navigator.set_neopixel(&colors))
+}
+
+/// Set the color brightnesses of a connected NeoPixel LED array, as `[[r, g, b, w], ...]`.
+#[napi]
+pub fn set_neopixel_rgbw(rgbw_array: Vec<Vec<u8>>) -> Result<()> {
+    let colors = colors::<4>(rgbw_array)?;
+    with_instance(|instance| instance.navigator.set_neopixel_rgbw(&colors))
+}
+
+/// Reads the ADC channel values (from the ADS1115 chip) in [V].
+#[napi]
+pub fn read_adc_all() -> Result<Vec<f64>> {
+    with_instance(Instance::read_adc_all).map(values)
+}
+
+/// Reads a specific ADC channel (from the ADS1115 chip) in [V].
+#[napi]
+pub fn read_adc(channel: AdcChannel) -> Result<f64> {
+    with_instance(|instance| instance.read_adc(channel)).map(f64::from)
+}
+
+/// Reads the current pressure (from the onboard BMP280 chip) in [kPa].
+#[napi]
+pub fn read_pressure() -> Result<f64> {
+    with_instance(Instance::read_pressure).map(f64::from)
+}
+
+/// Reads the current temperature (from the onboard BMP280 chip) in [˚C].
+#[napi]
+pub fn read_temp() -> Result<f64> {
+    with_instance(Instance::read_temp).map(f64::from)
+}
+
+/// Reads the state of leak detector pin from Navigator.
+#[napi]
+pub fn read_leak() -> Result<bool> {
+    with_instance(|instance| instance.navigator.read_leak())
+}
+
+/// Reads the local magnetic field strengths (from the onboard Ak09915 magnetometer) in [µT].
+#[napi]
+pub fn read_mag() -> Result<AxisData> {
+    with_instance(Instance::read_mag).map(AxisData::from)
+}
+
+/// Reads the current acceleration values (from the ICM20689 chip's accelerometer) in [m/s²].
+#[napi]
+pub fn read_accel() -> Result<AxisData> {
+    with_instance(Instance::read_accel).map(AxisData::from)
+}
+
+/// Reads the current angular velocity (from the ICM20689 chip's gyroscope) in [rad/s].
+#[napi]
+pub fn read_gyro() -> Result<AxisData> {
+    with_instance(Instance::read_gyro).map(AxisData::from)
+}
+
+/// Reads the accelerometer, gyroscope and magnetometer.
+#[napi]
+pub fn read_imu() -> Result<ImuData> {
+    with_instance(Instance::read_all).map(ImuData::from)
+}
+
+/// Reads all sensors in a single pass, so the measurements are time-aligned.
+#[napi]
+pub fn read_all() -> Result<SensorData> {
+    with_instance(Instance::read_all).map(SensorData::from)
+}
+
+/// Sets the full-scale range of the accelerometer, the readings stay in [m/s²].
+#[napi]
+pub fn set_accel_range(range: AccelRange) -> Result<()> {
+    with_instance(|instance| instance.set_accel_range(range))
+}
+
+/// Sets the full-scale range of the gyroscope, the readings stay in [rad/s].
+#[napi]
+pub fn set_gyro_range(range: GyroRange) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_gyro_range(range))
+}
+
+/// Sets the bandwidth of the IMU digital low-pass filter, for both the accelerometer and the gyroscope.
+#[napi]
+pub fn set_imu_dlpf(dlpf: ImuDlpf) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_imu_dlpf(dlpf))
+}
+
+/// Sets the IMU sample rate, the rate at which the FIFO of `readImuFifo` is filled, from 3.9 to
+/// 1000 Hz. Throws if the rate is out of range.
+#[napi]
+pub fn set_imu_sample_rate(rate_hz: f64) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_imu_sample_rate(rate_hz as f32))?
+        .map_err(Error::from_reason)
+}
+
+/// Reads up to `maxSamples` (all by default) samples stored in the IMU FIFO, oldest first. The
+/// FIFO is started by the first call, which returns no samples. Throws if the FIFO overflowed.
+#[napi]
+pub fn read_imu_fifo(max_samples: Option<u32>) -> Result<Vec<ImuSample>> {
+    let max_samples = max_samples.map_or(usize::MAX, |max_samples| max_samples as usize);
+    let samples = with_instance(|instance| instance.read_imu_fifo(max_samples))?
+        .map_err(Error::from_reason)?;
+    Ok(samples.into_iter().map(ImuSample::from).collect())
+}
+
+/// Reads the vibration levels and clipping count, computed from the accelerometer readings of the
+/// other read functions.
+#[napi]
+pub fn read_vibration() -> Result<VibrationData> {
+    guarded(crate::vibration_data).map(VibrationData::from)
+}
+
+/// Resets the vibration levels and clipping count.
+#[napi]
+pub fn reset_vibration() -> Result<()> {
+    guarded(|| {
+        crate::NAVIGATOR.with_instance(Instance::reset_vibration);
+    })
+}
+
+/// Reads a specific ADC channel, `valid` is false instead of throwing for a failed read.
+#[napi]
+pub fn read_adc_checked(channel: AdcChannel) -> CheckedValue {
+    checked(
+        |instance| instance.read_adc_checked(channel),
+        crate::CheckedValue::invalid,
+    )
+    .into()
+}
+
+/// Reads the current pressure, `valid` is false instead of throwing for a failed read.
+#[napi]
+pub fn read_pressure_checked() -> CheckedValue {
+    checked(
+        Instance::read_pressure_checked,
+        crate::CheckedValue::invalid,
+    )
+    .into()
+}
+
+/// Reads the current temperature, `valid` is false instead of throwing for a failed read.
+#[napi]
+pub fn read_temp_checked() -> CheckedValue {
+    checked(Instance::read_temp_checked, crate::CheckedValue::invalid).into()
+}
+
+/// Reads the local magnetic field strengths, `valid` is false instead of throwing for a failed read.
+#[napi]
+pub fn read_mag_checked() -> CheckedAxisData {
+    checked(Instance::read_mag_checked, crate::CheckedAxisData::invalid).into()
+}
+
+/// Reads the current acceleration values, `valid` is false instead of throwing for a failed read.
+#[napi]
+pub fn read_accel_checked() -> CheckedAxisData {
+    checked(
+        Instance::read_accel_checked,
+        crate::CheckedAxisData::invalid,
+    )
+    .into()
+}
+
+/// Reads the current angular velocity, `valid` is false instead of throwing for a failed read.
+#[napi]
+pub fn read_gyro_checked() -> CheckedAxisData {
+    checked(Instance::read_gyro_checked, crate::CheckedAxisData::invalid).into()
+}
+
+/// Attaches a Butterworth low-pass filter to a sensor channel, used by the `read*Filtered`
+/// functions. Throws if the parameters are invalid.
+#[napi]
+pub fn set_low_pass_filter(channel: FilterChannel, sample_rate: f64, cutoff: f64) -> Result<()> {
+    let spec = crate::filters::FilterSpec::LowPass {
+        sample_rate: sample_rate as f32,
+        cutoff: cutoff as f32,
+    };
+    set_filter(channel, spec)
+}
+
+/// Attaches a notch filter to a sensor channel, used by the `read*Filtered` functions. Throws if
+/// the parameters are invalid.
+#[napi]
+pub fn set_notch_filter(
+    channel: FilterChannel,
+    sample_rate: f64,
+    center: f64,
+    bandwidth: f64,
+) -> Result<()> {
+    let spec = crate::filters::FilterSpec::Notch {
+        sample_rate: sample_rate as f32,
+        center: center as f32,
+        bandwidth: bandwidth as f32,
+    };
+    set_filter(channel, spec)
+}
+
+/// Attaches a 1-euro filter to a sensor channel, used by the `read*Filtered` functions. Throws if
+/// the parameters are invalid.
+#[napi]
+pub fn set_one_euro_filter(channel: FilterChannel, min_cutoff: f64, beta: f64) -> Result<()> {
+    let spec = crate::filters::FilterSpec::OneEuro {
+        min_cutoff: min_cutoff as f32,
+        beta: beta as f32,
+    };
+    set_filter(channel, spec)
+}
+
+/// Attaches a moving average filter to a sensor channel, used by the `read*Filtered` functions.
+/// Throws if the window is empty.
+#[napi]
+pub fn set_moving_average_filter(channel: FilterChannel, window: u32) -> Result<()> {
+    let spec = crate::filters::FilterSpec::MovingAverage {
+        window: window as usize,
+    };
+    set_filter(channel, spec)
+}
+
+/// Attaches a moving median filter to a sensor channel, used by the `read*Filtered` functions.
+/// Throws if the window is empty.
+#[napi]
+pub fn set_median_filter(channel: FilterChannel, window: u32) -> Result<()> {
+    let spec = crate::filters::FilterSpec::Median {
+        window: window as usize,
+    };
+    set_filter(channel, spec)
+}
+
+/// Removes the filter of a sensor channel, its `read*Filtered` function then returns raw values.
+#[napi]
+pub fn clear_filter(channel: FilterChannel) {
+    crate::configure(|builder| builder.filters.remove(&(channel as usize)));
+}
+
+/// Reads the ADC channel values, filtered by the filter attached to `FilterChannel.Adc`.
+#[napi]
+pub fn read_adc_all_filtered() -> Result<Vec<f64>> {
+    with_instance(Instance::read_adc_all_filtered).map(values)
+}
+
+/// Reads a specific ADC channel, filtered by the filter attached to `FilterChannel.Adc`.
+#[napi]
+pub fn read_adc_filtered(channel: AdcChannel) -> Result<f64> {
+    with_instance(|instance| instance.read_adc_filtered(channel)).map(f64::from)
+}
+
+/// Reads the current pressure, filtered by the filter attached to `FilterChannel.Pressure`.
+#[napi]
+pub fn read_pressure_filtered() -> Result<f64> {
+    with_instance(Instance::read_pressure_filtered).map(f64::from)
+}
+
+/// Reads the current temperature, filtered by the filter attached to `FilterChannel.Temperature`.
+#[napi]
+pub fn read_temp_filtered() -> Result<f64> {
+    with_instance(Instance::read_temp_filtered).map(f64::from)
+}
+
+/// Reads the local magnetic field strengths, filtered by the filter attached to `FilterChannel.Mag`.
+#[napi]
+pub fn read_mag_filtered() -> Result<AxisData> {
+    with_instance(Instance::read_mag_filtered).map(AxisData::from)
+}
+
+/// Reads the current acceleration values, filtered by the filter attached to `FilterChannel.Accel`.
+#[napi]
+pub fn read_accel_filtered() -> Result<AxisData> {
+    with_instance(Instance::read_accel_filtered).map(AxisData::from)
+}
+
+/// Reads the current angular velocity, filtered by the filter attached to `FilterChannel.Gyro`.
+#[napi]
+pub fn read_gyro_filtered() -> Result<AxisData> {
+    with_instance(Instance::read_gyro_filtered).map(AxisData::from)
+}
+
+/// Filters an array of evenly spaced samples with a Butterworth low-pass filter.
+#[napi]
+pub fn filter_low_pass(values: Vec<f64>, sample_rate: f64, cutoff: f64) -> Result<Vec<f64>> {
+    let spec = crate::filters::FilterSpec::LowPass {
+        sample_rate: sample_rate as f32,
+        cutoff: cutoff as f32,
+    };
+    filter_array(values, spec, sample_rate as f32)
+}
+
+/// Filters an array of evenly spaced samples with a notch filter.
+#[napi]
+pub fn filter_notch(
+    values: Vec<f64>,
+    sample_rate: f64,
+    center: f64,
+    bandwidth: f64,
+) -> Result<Vec<f64>> {
+    let spec = crate::filters::FilterSpec::Notch {
+        sample_rate: sample_rate as f32,
+        center: center as f32,
+        bandwidth: bandwidth as f32,
+    };
+    filter_array(values, spec, sample_rate as f32)
+}
+
+/// Filters an array of evenly spaced samples with a 1-euro filter.
+#[napi]
+pub fn filter_one_euro(
+    values: Vec<f64>,
+    sample_rate: f64,
+    min_cutoff: f64,
+    beta: f64,
+) -> Result<Vec<f64>> {
+    let spec = crate::filters::FilterSpec::OneEuro {
+        min_cutoff: min_cutoff as f32,
+        beta: beta as f32,
+    };
+    filter_array(values, spec, sample_rate as f32)
+}
+
+/// Filters an array of samples with a moving average filter.
+#[napi]
+pub fn filter_moving_average(values: Vec<f64>, window: u32) -> Result<Vec<f64>> {
+    let spec = crate::filters::FilterSpec::MovingAverage {
+        window: window as usize,
+    };
+    filter_array(values, spec, 1.0)
+}
+
+/// Filters an array of samples with a moving median filter.
+#[napi]
+pub fn filter_median(values: Vec<f64>, window: u32) -> Result<Vec<f64>> {
+    let spec = crate::filters::FilterSpec::Median {
+        window: window as usize,
+    };
+    filter_array(values, spec, 1.0)
+}
+
+/// Returns the health of the barometer, magnetometer, IMU and ADC, in this order.
+#[napi]
+pub fn get_sensor_health() -> Result<Vec<SensorHealth>> {
+    let health = guarded(crate::sensor_health_all)?;
+    Ok(health.into_iter().map(SensorHealth::from).collect())
+}
+
+/// Resets the health of every sensor.
+#[napi]
+pub fn reset_sensor_health() -> Result<()> {
+    guarded(|| {
+        crate::NAVIGATOR.with_instance(Instance::reset_sensor_health);
+    })
+}
+
+/// Makes this process the only client of `navigatord` driving the PWM outputs, until `releasePwm`
+/// is called or it disconnects. The PWM functions throw until then with `Backend.Daemon`, the local
+/// backend always owns them. Throws if another client claimed them.
+#[napi]
+pub fn claim_pwm() -> Result<()> {
+    with_instance(|instance| instance.navigator.claim_pwm())?.map_err(Error::from_reason)
+}
+
+/// Releases the PWM outputs claimed by `claimPwm`, so another client of `navigatord` can claim them.
+#[napi]
+pub fn release_pwm() -> Result<()> {
+    with_instance(|instance| instance.navigator.release_pwm())
+}
+
+/// Enables or disables the PWM chip (PCA9685), using the firmware and OE_pin.
+#[napi]
+pub fn set_pwm_enable(state: bool) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_pwm_enable(state))
+}
+
+/// Sets the PWM frequency of the PCA9685 chip. All channels use the same frequency. Throws if the
+/// frequency is out of range.
+#[napi]
+pub fn set_pwm_freq_hz(freq: f64) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_pwm_frequency(freq as f32))?
+        .map_err(Error::from_reason)
+}
+
+/// Sets the PWM frequency of the PCA9685 chip from its prescale value (3..255).
+#[napi]
+pub fn set_pwm_freq_prescale(prescale: u8) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_pwm_prescale(prescale))?
+        .map_err(Error::from_reason)
+}
+
+/// Selects the output driver of the PWM channels, `TotemPole` (the default) or `OpenDrain`.
+#[napi]
+pub fn set_pwm_output_mode(mode: PwmOutputMode) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_pwm_output_mode(mode))
+}
+
+/// Inverts the logic state of the PWM outputs, for drivers active low.
+#[napi]
+pub fn set_pwm_invert(inverted: bool) -> Result<()> {
+    with_instance(|instance| instance.navigator.set_pwm_invert(inverted))
+}
+
+/// Sets the duty cycle of the selected PWM channel as a count of the 4096 segments of a cycle
+/// (0..4095).
+#[napi]
+pub fn set_pwm_channel_value(channel: u32, value: f64) -> Result<()> {
+    set_duty_cycles(&[pwm_channel(channel)?], &[value as f32 / 4096.0])
+}
+
+/// Sets the duty cycle (0.0 : 1.0) of the selected PWM channel.
+#[napi]
+pub fn set_pwm_channel_duty_cycle(channel: u32, duty_cycle: f64) -> Result<()> {
+    set_duty_cycles(&[pwm_channel(channel)?], &[duty_cycle as f32])
+}
+
+/// Sets the selected PWM channel fully ON, using the chip's full-ON bit.
+#[napi]
+pub fn set_pwm_channel_full_on(channel: u32) -> Result<()> {
+    set_duty_cycles(&[pwm_channel(channel)?], &[1.0])
+}
+
+/// Sets the selected PWM channel fully OFF, using the chip's full-OFF bit.
+#[napi]
+pub fn set_pwm_channel_full_off(channel: u32) -> Result<()> {
+    set_duty_cycles(&[pwm_channel(channel)?], &[0.0])
+}
+
+/// Sets both counters of the selected PWM channel: the output turns ON at the `on` count and OFF at
+/// the `off` count of each cycle. Throws if a count is above 4095.
+#[napi]
+pub fn set_pwm_channel_on_off(channel: u32, on: u32, off: u32) -> Result<()> {
+    let counter = |count: u32| {
+        u16::try_from(count).map_err(|_| {
+            Error::from_reason(format!(
+                "PWM counters must be between 0 and 4095, found {count}."
+            ))
+        })
+    };
+    let (channel, on, off) = (pwm_channel(channel)?, counter(on)?, counter(off)?);
+    with_instance(|instance| instance.set_pwm_channel_on_off(channel, on, off))?
+        .map_err(Error::from_reason)
+}
+
+/// Enables or disables the phase staggering of the PWM channels, spreading their ON times over the
+/// cycle.
+#[napi]
+pub fn set_pwm_phase_stagger(enabled: bool) {
+    crate::configure(|builder| builder.pwm_phase_stagger = enabled);
+}
+
+/// Sets the duty cycle of each selected PWM channel as a count (0..4095), like
+/// `setPwmChannelValue`.
+#[napi]
+pub fn set_pwm_channels_value(channels: Vec<u32>, value: f64) -> Result<()> {
+    let channels = pwm_channel_list(channels)?;
+    set_duty_cycles(&channels, &vec![value as f32 / 4096.0; channels.len()])
+}
+
+/// Sets the duty cycle (0.0 : 1.0) of each selected PWM channel, like `setPwmChannelDutyCycle`.
+#[napi]
+pub fn set_pwm_channels_duty_cycle(channels: Vec<u32>, duty_cycle: f64) -> Result<()> {
+    let channels = pwm_channel_list(channels)?;
+    set_duty_cycles(&channels, &vec![duty_cycle as f32; channels.len()])
+}
+
+/// Sets the duty cycle of each selected PWM channel as a count (0..4095), updated together.
+#[napi]
+pub fn set_pwm_channels_values(channels: Vec<u32>, values: Vec<f64>) -> Result<()> {
+    if channels.len() != values.len() {
+        return Err(Error::from_reason(
+            "The number of channels and values must match",
+        ));
+    }
+    let channels = pwm_channel_list(channels)?;
+    let duty_cycles: Vec<f32> = values.into_iter().map(|v| v as f32 / 4096.0).collect();
+    set_duty_cycles(&channels, &duty_cycles)
+}
+
+/// Sets the duty cycle (0.0 : 1.0) of each selected PWM channel, updated together.
+#[napi]
+pub fn set_pwm_channels_duty_cycle_values(
+    channels: Vec<u32>,
+    duty_cycle_values: Vec<f64>,
+) -> Result<()> {
+    if channels.len() != duty_cycle_values.len() {
+        return Err(Error::from_reason(
+            "The number of channels and duty cycle values must match",
+        ));
+    }
+    let channels = pwm_channel_list(channels)?;
+    let duty_cycles: Vec<f32> = duty_cycle_values.into_iter().map(|v| v as f32).collect();
+    set_duty_cycles(&channels, &duty_cycles)
+}
+
+/// Reads back the state of all PWM outputs.
+#[napi]
+pub fn get_pwm_state() -> Result<PwmState> {
+    pwm_state().map(PwmState::from)
+}
+
+/// Reads back whether the PWM outputs are enabled.
+#[napi]
+pub fn get_pwm_enable() -> Result<bool> {
+    Ok(pwm_state()?.enabled)
+}
+
+/// Reads back the achieved PWM frequency in [Hz], after rounding to the closest prescale value.
+#[napi]
+pub fn get_pwm_freq_hz() -> Result<f64> {
+    Ok(pwm_state()?.frequency.into())
+}
+
+/// Reads back the PWM prescale value (3..255), which sets the frequency.
+#[napi]
+pub fn get_pwm_prescale() -> Result<u32> {
+    Ok(pwm_state()?.prescale.into())
+}
+
+/// Reads back the achieved duty cycle (0.0 : 1.0) of the selected PWM channel, NaN for `All`.
+#[napi]
+pub fn get_pwm_channel_duty_cycle(channel: u32) -> Result<f64> {
+    let channel = pwm_channel(channel)?;
+    let duty_cycles = pwm_state()?.duty_cycles;
+    Ok(duty_cycles.get(channel).copied().unwrap_or(f32::NAN).into())
+}
+
+/// Limits how fast the duty cycle of the selected PWM channel changes, in duty cycle per second,
+/// 0 removes the limit.
+#[napi]
+pub fn set_pwm_slew_rate(channel: u32, rate: f64) -> Result<()> {
+    crate::set_slew_rate(pwm_channel(channel)?, rate as f32).map_err(Error::from_reason)
+}
+
+/// Moves the duty cycle of the selected PWM channel linearly to `target`, over `durationMs`. The
+/// ramp runs on a background thread, setting a new duty cycle on the channel replaces it.
+#[napi]
+pub fn ramp_pwm_channel(channel: u32, target: f64, duration_ms: u32) -> Result<()> {
+    let channel = pwm_channel(channel)?;
+    let duration = Duration::from_millis(duration_ms.into());
+    with_instance(|instance| instance.ramp_pwm_channel(channel, target as f32, duration))?
+        .map_err(Error::from_reason)
+}
+
+/// Stops the ramp of the selected PWM channel, keeping its current duty cycle.
+#[napi]
+pub fn stop_pwm_ramp(channel: u32) -> Result<()> {
+    crate::stop_ramp(pwm_channel(channel)?).map_err(Error::from_reason)
+}
+
+/// Measurement taken by a stream, converted to a JS value when resolved.
+pub enum Sample {
+    Value(f64),
+    Values(Vec<f64>),
+    Axis(AxisData),
+    Imu(ImuData),
+    All(SensorData),
+}
+
+struct StreamState {
+    samples: VecDeque<std::result::Result<Sample, String>>,
+    running: bool,
+}
+
+struct Stream {
+    state: Mutex<StreamState>,
+    updated: Condvar,
+}
+
+/// Asynchronous iterator over sensor samples, taken at a fixed rate by a Rust thread.
+#[napi]
+pub struct SensorStream {
+    stream: Arc<Stream>,
+    running: Arc<AtomicBool>,
+}
+
+impl SensorStream {
+    fn spawn<F>(rate: f64, read: F) -> Result<Self>
+    where
+        F: Fn() -> Sample + Send + 'static,
+    {
+        if rate.is_nan() || rate <= 0.0 {
+            return Err(Error::from_reason(format!("Invalid rate: {rate}")));
+        }
+        let period = Duration::from_secs_f64(1.0 / rate);
+        let stream = Arc::new(Stream {
+            state: Mutex::new(StreamState {
+                samples: VecDeque::new(),
+                running: true,
+            }),
+            updated: Condvar::new(),
+        });
+        let running = Arc::new(AtomicBool::new(true));
+
+        let (thread_stream, thread_running) = (stream.clone(), running.clone());
+        std::thread::spawn(move || {
+            let mut deadline = Instant::now();
+            while thread_running.load(Ordering::Relaxed) {
+                let result = catch_unwind(AssertUnwindSafe(&read)).map_err(panic_message);
+                let mut state = thread_stream
+                    .state
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if state.samples.len() == STREAM_BUFFER_SIZE {
+                    state.samples.pop_front();
+                }
+                state.samples.push_back(result);
+                drop(state);
+                thread_stream.updated.notify_all();
+
+                deadline += period;
+                let now = Instant::now();
+                if deadline > now {
+                    std::thread::sleep(deadline - now);
+                } else {
+                    // Too slow to keep up with the rate, don't try to catch up
+                    deadline = now;
+                }
+            }
+            thread_stream
+                .state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .running = false;
+            thread_stream.updated.notify_all();
+        });
+
+        Ok(Self { stream, running })
+    }
+}
+
+#[napi]
+impl SensorStream {
+    /// Waits for the next sample, as an iterator result (`{ done, value }`).
+    #[napi(ts_return_type = "Promise<IteratorResult<any>>")]
+    pub fn next(&self) -> AsyncTask<NextSample> {
+        AsyncTask::new(NextSample {
+            stream: self.stream.clone(),
+            running: self.running.clone(),
+        })
+    }
+
+    /// Stops sampling, ending the iteration.
+    #[napi]
+    pub fn close(&self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Drop for SensorStream {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Pending `SensorStream.next()` call, waiting on the libuv thread pool.
+pub struct NextSample {
+    stream: Arc<Stream>,
+    running: Arc<AtomicBool>,
+}
+
+impl Task for NextSample {
+    type Output = Option<Sample>;
+    type JsValue = JsObject;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        if !self.running.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let mut state = self
+            .stream
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        loop {
+            match state.samples.pop_front() {
+                Some(sample) => return sample.map(Some).map_err(Error::from_reason),
+                None if !state.running => return Ok(None),
+                None => {
+                    state = self
+                        .stream
+                        .updated
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner)
+                }
+            }
+        }
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        let mut result = env.create_object()?;
+        result.set_named_property("done", output.is_none())?;
+        match output {
+            Some(Sample::Value(value)) => result.set_named_property("value", value)?,
+            Some(Sample::Values(values)) => result.set_named_property("value", values)?,
+            Some(Sample::Axis(axis)) => result.set_named_property("value", axis)?,
+            Some(Sample::Imu(imu)) => result.set_named_property("value", imu)?,
+            Some(Sample::All(data)) => result.set_named_property("value", data)?,
+            None => result.set_named_property("value", env.get_undefined()?)?,
+        }
+        Ok(result)
+    }
+}
+
+/// Streams `readAdcAll` measurements at `rate` [Hz] (100 by default).
+#[napi]
+pub fn stream_adc_all(rate: Option<f64>) -> Result<SensorStream> {
+    SensorStream::spawn(rate.unwrap_or(100.0), || {
+        Sample::Values(values(crate::with_default_instance(Instance::read_adc_all)))
+    })
+}
+
+/// Streams `readPressure` measurements at `rate` [Hz] (100 by default).
+#[napi]
+pub fn stream_pressure(rate: Option<f64>) -> Result<SensorStream> {
+    SensorStream::spawn(rate.unwrap_or(100.0), || {
+        Sample::Value(crate::with_default_instance(Instance::read_pressure).into())
+    })
+}
+
+/// Streams `readMag` measurements at `rate` [Hz] (100 by default).
+#[napi]
+pub fn stream_mag(rate: Option<f64>) -> Result<SensorStream> {
+    SensorStream::spawn(rate.unwrap_or(100.0), || {
+        Sample::Axis(crate::with_default_instance(Instance::read_mag).into())
+    })
+}
+
+/// Streams `readAccel` measurements at `rate` [Hz] (100 by default).
+#[napi]
+pub fn stream_accel(rate: Option<f64>) -> Result<SensorStream> {
+    SensorStream::spawn(rate.unwrap_or(100.0), || {
+        Sample::Axis(crate::with_default_instance(Instance::read_accel).into())
+    })
+}
+
+/// Streams `readGyro` measurements at `rate` [Hz] (100 by default).
+#[napi]
+pub fn stream_gyro(rate: Option<f64>) -> Result<SensorStream> {
+    SensorStream::spawn(rate.unwrap_or(100.0), || {
+        Sample::Axis(crate::with_default_instance(Instance::read_gyro).into())
+    })
+}
+
+/// Streams `readImu` measurements at `rate` [Hz] (100 by default).
+#[napi]
+pub fn stream_imu(rate: Option<f64>) -> Result<SensorStream> {
+    SensorStream::spawn(rate.unwrap_or(100.0), || {
+        Sample::Imu(crate::with_default_instance(Instance::read_all).into())
+    })
+}
+
+/// Streams `readAll` measurements at `rate` [Hz] (100 by default).
+#[napi]
+pub fn stream_all(rate: Option<f64>) -> Result<SensorStream> {
+    SensorStream::spawn(rate.unwrap_or(100.0), || {
+        Sample::All(crate::with_default_instance(Instance::read_all).into())
+    })
+}
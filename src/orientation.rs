@@ -0,0 +1,195 @@
+//! Board mounting orientation, following ArduPilot's rotation conventions.
+
+pub type Matrix = [[f32; 3]; 3];
+
+pub const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Rotation matrix from euler angles in degrees, same as ArduPilot's `Matrix3::from_euler`.
+pub fn from_euler(roll: f32, pitch: f32, yaw: f32) -> Matrix {
+    let (sr, cr) = roll.to_radians().sin_cos();
+    let (sp, cp) = pitch.to_radians().sin_cos();
+    let (sy, cy) = yaw.to_radians().sin_cos();
+
+    let mut matrix = [
+        [cp * cy, sr * sp * cy - cr * sy, cr * sp * cy + sr * sy],
+        [cp * sy, sr * sp * sy + cr * cy, cr * sp * sy - sr * cy],
+        [-sp, sr * cp, cr * cp],
+    ];
+    // Remove rounding errors, so right angles give exact results
+    for value in matrix.iter_mut().flatten() {
+        if value.abs() < 1e-6 {
+            *value = 0.0;
+        }
+    }
+    matrix
+}
+
+pub fn rotate(matrix: &Matrix, axis: navigator_rs::AxisData) -> navigator_rs::AxisData {
+    let [x, y, z] = matrix.map(|row| row[0] * axis.x + row[1] * axis.y + row[2] * axis.z);
+    navigator_rs::AxisData { x, y, z }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rotation;
+
+    type Vector = [f32; 3];
+    type Elementary = fn(Vector) -> Vector;
+
+    const HALF_SQRT_2: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+    // Elementary rotations written as in ArduPilot's `Vector3::rotate`, which applies the roll,
+    // then the pitch, then the yaw of the composed rotations
+    fn roll_45([x, y, z]: Vector) -> Vector {
+        [x, HALF_SQRT_2 * (y - z), HALF_SQRT_2 * (y + z)]
+    }
+    fn roll_90([x, y, z]: Vector) -> Vector {
+        [x, -z, y]
+    }
+    fn roll_180([x, y, z]: Vector) -> Vector {
+        [x, -y, -z]
+    }
+    fn roll_270([x, y, z]: Vector) -> Vector {
+        [x, z, -y]
+    }
+    fn roll_315([x, y, z]: Vector) -> Vector {
+        [x, HALF_SQRT_2 * (y + z), HALF_SQRT_2 * (z - y)]
+    }
+    fn pitch_7([x, y, z]: Vector) -> Vector {
+        let (sin, cos) = (0.121_869_34, 0.992_546_15);
+        [cos * x + sin * z, y, -sin * x + cos * z]
+    }
+    fn pitch_90([x, y, z]: Vector) -> Vector {
+        [z, y, -x]
+    }
+    fn pitch_180([x, y, z]: Vector) -> Vector {
+        [-x, y, -z]
+    }
+    fn pitch_270([x, y, z]: Vector) -> Vector {
+        [-z, y, x]
+    }
+    fn pitch_315([x, y, z]: Vector) -> Vector {
+        [HALF_SQRT_2 * (x - z), y, HALF_SQRT_2 * (x + z)]
+    }
+    fn yaw_45([x, y, z]: Vector) -> Vector {
+        [HALF_SQRT_2 * (x - y), HALF_SQRT_2 * (x + y), z]
+    }
+    fn yaw_90([x, y, z]: Vector) -> Vector {
+        [-y, x, z]
+    }
+    fn yaw_135([x, y, z]: Vector) -> Vector {
+        [-HALF_SQRT_2 * (x + y), HALF_SQRT_2 * (x - y), z]
+    }
+    fn yaw_180([x, y, z]: Vector) -> Vector {
+        [-x, -y, z]
+    }
+    fn yaw_225([x, y, z]: Vector) -> Vector {
+        [HALF_SQRT_2 * (y - x), -HALF_SQRT_2 * (x + y), z]
+    }
+    fn yaw_270([x, y, z]: Vector) -> Vector {
+        [y, -x, z]
+    }
+    fn yaw_315([x, y, z]: Vector) -> Vector {
+        [HALF_SQRT_2 * (x + y), HALF_SQRT_2 * (y - x), z]
+    }
+    fn roll_90_pitch_68_yaw_293([x, y, z]: Vector) -> Vector {
+        [
+            0.143_038_97 * x + 0.368_776_5 * y - 0.918_446_4 * z,
+            -0.332_132_8 * x - 0.856_289_4 * y - 0.395_545_5 * z,
+            -0.932_323_8 * x + 0.361_624_57 * y,
+        ]
+    }
+
+    /// Every rotation, in the order of ArduPilot's list, with the elementary rotations it applies.
+    fn ardupilot_rotations() -> Vec<(Rotation, Vec<Elementary>)> {
+        vec![
+            (Rotation::NoRotation, vec![]),
+            (Rotation::Yaw45, vec![yaw_45]),
+            (Rotation::Yaw90, vec![yaw_90]),
+            (Rotation::Yaw135, vec![yaw_135]),
+            (Rotation::Yaw180, vec![yaw_180]),
+            (Rotation::Yaw225, vec![yaw_225]),
+            (Rotation::Yaw270, vec![yaw_270]),
+            (Rotation::Yaw315, vec![yaw_315]),
+            (Rotation::Roll180, vec![roll_180]),
+            (Rotation::Roll180Yaw45, vec![roll_180, yaw_45]),
+            (Rotation::Roll180Yaw90, vec![roll_180, yaw_90]),
+            (Rotation::Roll180Yaw135, vec![roll_180, yaw_135]),
+            (Rotation::Pitch180, vec![pitch_180]),
+            (Rotation::Roll180Yaw225, vec![roll_180, yaw_225]),
+            (Rotation::Roll180Yaw270, vec![roll_180, yaw_270]),
+            (Rotation::Roll180Yaw315, vec![roll_180, yaw_315]),
+            (Rotation::Roll90, vec![roll_90]),
+            (Rotation::Roll90Yaw45, vec![roll_90, yaw_45]),
+            (Rotation::Roll90Yaw90, vec![roll_90, yaw_90]),
+            (Rotation::Roll90Yaw135, vec![roll_90, yaw_135]),
+            (Rotation::Roll270, vec![roll_270]),
+            (Rotation::Roll270Yaw45, vec![roll_270, yaw_45]),
+            (Rotation::Roll270Yaw90, vec![roll_270, yaw_90]),
+            (Rotation::Roll270Yaw135, vec![roll_270, yaw_135]),
+            (Rotation::Pitch90, vec![pitch_90]),
+            (Rotation::Pitch270, vec![pitch_270]),
+            (Rotation::Pitch180Yaw90, vec![pitch_180, yaw_90]),
+            (Rotation::Pitch180Yaw270, vec![pitch_180, yaw_270]),
+            (Rotation::Roll90Pitch90, vec![roll_90, pitch_90]),
+            (Rotation::Roll180Pitch90, vec![roll_180, pitch_90]),
+            (Rotation::Roll270Pitch90, vec![roll_270, pitch_90]),
+            (Rotation::Roll90Pitch180, vec![roll_90, pitch_180]),
+            (Rotation::Roll270Pitch180, vec![roll_270, pitch_180]),
+            (Rotation::Roll90Pitch270, vec![roll_90, pitch_270]),
+            (Rotation::Roll180Pitch270, vec![roll_180, pitch_270]),
+            (Rotation::Roll270Pitch270, vec![roll_270, pitch_270]),
+            (
+                Rotation::Roll90Pitch180Yaw90,
+                vec![roll_90, pitch_180, yaw_90],
+            ),
+            (Rotation::Roll90Yaw270, vec![roll_90, yaw_270]),
+            (
+                Rotation::Roll90Pitch68Yaw293,
+                vec![roll_90_pitch_68_yaw_293],
+            ),
+            (Rotation::Pitch315, vec![pitch_315]),
+            (Rotation::Roll90Pitch315, vec![roll_90, pitch_315]),
+            (Rotation::Pitch7, vec![pitch_7]),
+            (Rotation::Roll45, vec![roll_45]),
+            (Rotation::Roll315, vec![roll_315]),
+        ]
+    }
+
+    #[test]
+    fn rotations_match_ardupilot() {
+        let vector = navigator_rs::AxisData {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        let rotations = ardupilot_rotations();
+        assert_eq!(rotations.len(), Rotation::Roll315 as usize + 1);
+        for (index, (rotation, elementary)) in rotations.into_iter().enumerate() {
+            assert_eq!(rotation.clone() as usize, index);
+            let expected = elementary
+                .into_iter()
+                .fold([vector.x, vector.y, vector.z], |vector, rotate| {
+                    rotate(vector)
+                });
+            let rotated = rotate(&rotation.into(), vector.clone());
+            let rotated = [rotated.x, rotated.y, rotated.z];
+            for (axis, (rotated, expected)) in rotated.iter().zip(expected).enumerate() {
+                assert!(
+                    (rotated - expected).abs() < 2e-3,
+                    "rotation {index}, axis {axis}: {rotated} != {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn right_angles_are_exact() {
+        let matrix = from_euler(90.0, 0.0, 180.0);
+        assert!(matrix
+            .iter()
+            .flatten()
+            .all(|value| [-1.0, 0.0, 1.0].contains(value)));
+    }
+}
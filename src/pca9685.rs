@@ -0,0 +1,229 @@
+//! Register access to the PCA9685 PWM controller, for what navigator-rs doesn't expose: both
+//! counters of a channel, the full-OFF bit and burst writes.
+//!
+//! navigator-rs still sets the chip up and drives its output enable pin and prescale, every
+//! channel is written here.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use navigator_rs::PiVersion;
+
+/// I2C address of the PCA9685 on the Navigator.
+const ADDRESS: u8 = 0x40;
+
+const MODE1: u8 = 0x00;
+const MODE2: u8 = 0x01;
+const LED0_ON_L: u8 = 0x06;
+/// Registers of a channel: ON_L, ON_H, OFF_L and OFF_H.
+const LED_REGISTERS: u8 = 4;
+
+/// MODE1 bit restarting the PWM channels, writing it as 0 has no effect.
+const MODE1_RESTART: u8 = 0x80;
+/// MODE1 bit incrementing the register address after each byte, used by the burst writes.
+const MODE1_AUTO_INCREMENT: u8 = 0x20;
+/// MODE2 bit inverting the logic state of the outputs.
+const MODE2_INVERT: u8 = 0x10;
+/// MODE2 bit selecting totem-pole outputs instead of open-drain ones.
+const MODE2_TOTEM_POLE: u8 = 0x04;
+
+/// Bit of the ON_H and OFF_H registers setting the output fully ON or OFF, full OFF wins.
+const FULL: u16 = 0x1000;
+/// Counter value at the end of a PWM cycle.
+pub(crate) const COUNTER_MAX: u16 = 4095;
+
+/// LEDn_ON and LEDn_OFF register values of a channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Counters {
+    on: u16,
+    off: u16,
+}
+
+impl Counters {
+    pub const FULL_ON: Self = Self { on: FULL, off: 0 };
+    pub const FULL_OFF: Self = Self { on: 0, off: FULL };
+
+    /// Output turning ON at the `on` count and OFF at the `off` count, both up to `COUNTER_MAX`.
+    pub fn new(on: u16, off: u16) -> Self {
+        Self {
+            on: on.min(COUNTER_MAX),
+            off: off.min(COUNTER_MAX),
+        }
+    }
+
+    /// `duty_cycle` turning ON at the `on` count, rounded down like navigator-rs. 0.0 and 1.0 use
+    /// the full-OFF and full-ON bits.
+    pub fn from_duty_cycle(duty_cycle: f32, on: u16) -> Self {
+        let duty_cycle = duty_cycle.clamp(0.0, 1.0);
+        if duty_cycle >= 1.0 {
+            return Self::FULL_ON;
+        }
+        match (duty_cycle * COUNTER_MAX as f32) as u16 {
+            0 => Self::FULL_OFF,
+            width => Self::new(on, (on + width) % (COUNTER_MAX + 1)),
+        }
+    }
+
+    /// Same ON count with the OFF count moved to give `duty_cycle`, without the full bits.
+    pub fn with_duty_cycle(self, duty_cycle: f32) -> Self {
+        let on = self.on & COUNTER_MAX;
+        let width = (duty_cycle.clamp(0.0, 1.0) * COUNTER_MAX as f32) as u16;
+        Self::new(on, (on + width) % (COUNTER_MAX + 1))
+    }
+
+    /// Proportion of the cycle the output is ON.
+    pub fn duty_cycle(&self) -> f32 {
+        if self.off & FULL != 0 {
+            0.0
+        } else if self.on & FULL != 0 {
+            1.0
+        } else {
+            let width = (self.off + COUNTER_MAX + 1 - self.on) % (COUNTER_MAX + 1);
+            width as f32 / COUNTER_MAX as f32
+        }
+    }
+
+    pub fn on(&self) -> u16 {
+        self.on
+    }
+
+    pub fn off(&self) -> u16 {
+        self.off
+    }
+
+    fn to_bytes(self) -> [u8; 4] {
+        let [on_l, on_h] = self.on.to_le_bytes();
+        let [off_l, off_h] = self.off.to_le_bytes();
+        [on_l, on_h, off_l, off_h]
+    }
+}
+
+pub struct Pca9685 {
+    device: File,
+    // Whether MODE1 is known to have the auto-increment bit
+    auto_increment: bool,
+}
+
+impl Pca9685 {
+    /// Opens the chip on the I2C bus of the Navigator overlay for `pi`.
+    pub fn open(pi: PiVersion) -> io::Result<Self> {
+        let bus = match pi {
+            PiVersion::Pi4 => "/dev/i2c-4",
+            PiVersion::Pi5 => "/dev/i2c-3",
+        };
+        Ok(Self {
+            device: crate::detect::open_i2c_device(Path::new(bus), ADDRESS)?,
+            auto_increment: false,
+        })
+    }
+
+    /// Writes the counters of consecutive channels from `first`, in a single transfer.
+    pub fn write_channels(&mut self, first: usize, counters: &[Counters]) -> io::Result<()> {
+        self.enable_auto_increment()?;
+        self.device.write_all(&burst(first, counters))
+    }
+
+    /// Selects totem-pole outputs, or open-drain ones.
+    pub fn set_totem_pole(&mut self, enabled: bool) -> io::Result<()> {
+        self.set_mode2_bit(MODE2_TOTEM_POLE, enabled)
+    }
+
+    /// Inverts the logic state of the outputs.
+    pub fn set_inverted(&mut self, inverted: bool) -> io::Result<()> {
+        self.set_mode2_bit(MODE2_INVERT, inverted)
+    }
+
+    fn set_mode2_bit(&mut self, bit: u8, enabled: bool) -> io::Result<()> {
+        let mode2 = match enabled {
+            true => self.read_register(MODE2)? | bit,
+            false => self.read_register(MODE2)? & !bit,
+        };
+        self.device.write_all(&[MODE2, mode2])
+    }
+
+    fn read_register(&mut self, register: u8) -> io::Result<u8> {
+        self.device.write_all(&[register])?;
+        let mut value = [0];
+        self.device.read_exact(&mut value)?;
+        Ok(value[0])
+    }
+
+    /// Should be called once MODE1 is written by navigator-rs, which clears the auto-increment
+    /// bit it doesn't know about.
+    pub fn mode1_changed(&mut self) {
+        self.auto_increment = false;
+    }
+
+    fn enable_auto_increment(&mut self) -> io::Result<()> {
+        if self.auto_increment {
+            return Ok(());
+        }
+        let mode1 = self.read_register(MODE1)?;
+        if mode1 & MODE1_AUTO_INCREMENT == 0 {
+            let mode1 = (mode1 & !MODE1_RESTART) | MODE1_AUTO_INCREMENT;
+            self.device.write_all(&[MODE1, mode1])?;
+        }
+        self.auto_increment = true;
+        Ok(())
+    }
+}
+
+/// Auto-increment write of the counters from the `first` channel: the address of its first
+/// register, then 4 bytes per channel.
+fn burst(first: usize, counters: &[Counters]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + counters.len() * LED_REGISTERS as usize);
+    data.push(LED0_ON_L + first as u8 * LED_REGISTERS);
+    for counters in counters {
+        data.extend(counters.to_bytes());
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duty_cycles_use_the_full_bits_at_their_ends() {
+        assert_eq!(Counters::from_duty_cycle(1.0, 256), Counters::FULL_ON);
+        assert_eq!(Counters::from_duty_cycle(0.0, 256), Counters::FULL_OFF);
+        assert_eq!(Counters::from_duty_cycle(f32::NAN, 0), Counters::FULL_OFF);
+        assert_eq!(Counters::FULL_ON.to_bytes(), [0x00, 0x10, 0x00, 0x00]);
+        assert_eq!(Counters::FULL_OFF.to_bytes(), [0x00, 0x00, 0x00, 0x10]);
+    }
+
+    #[test]
+    fn duty_cycles_start_at_their_phase() {
+        let counters = Counters::from_duty_cycle(0.5, 0);
+        assert_eq!(counters, Counters::new(0, 2047));
+        assert_eq!(counters.to_bytes(), [0x00, 0x00, 0xFF, 0x07]);
+
+        // The OFF count wraps around the end of the cycle
+        let counters = Counters::from_duty_cycle(0.5, 3840);
+        assert_eq!(counters, Counters::new(3840, 1791));
+        assert_eq!(counters.duty_cycle(), 2047.0 / 4095.0);
+    }
+
+    #[test]
+    fn bursts_start_at_the_first_channel() {
+        let counters = [Counters::new(256, 1024), Counters::FULL_OFF];
+        assert_eq!(
+            burst(2, &counters),
+            [0x0E, 0x00, 0x01, 0x00, 0x04, 0x00, 0x00, 0x00, 0x10]
+        );
+        assert_eq!(burst(15, &[Counters::FULL_ON])[0], 0x42);
+    }
+
+    #[test]
+    fn duty_cycle_follows_the_counters() {
+        assert_eq!(Counters::new(100, 100).duty_cycle(), 0.0);
+        assert_eq!(Counters::new(4000, 95).duty_cycle(), 191.0 / 4095.0);
+        assert_eq!(Counters::new(0, 5000), Counters::new(0, COUNTER_MAX));
+        assert_eq!(Counters::FULL_ON.duty_cycle(), 1.0);
+        assert_eq!(Counters::FULL_OFF.duty_cycle(), 0.0);
+
+        let counters = Counters::new(3000, 3500).with_duty_cycle(0.5);
+        assert_eq!(counters, Counters::new(3000, 951));
+    }
+}
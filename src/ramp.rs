@@ -0,0 +1,247 @@
+//! Smooth PWM transitions, slew-rate limits and timed ramps stepped by a background thread.
+
+use std::time::{Duration, Instant};
+
+use crate::backend::PWM_CHANNELS;
+
+/// Time between two steps of the moving channels.
+pub const STEP_PERIOD: Duration = Duration::from_millis(5);
+
+#[derive(Clone, Copy)]
+struct Ramp {
+    from: f32,
+    start: Instant,
+    duration: Duration,
+}
+
+#[derive(Clone, Copy)]
+struct Motion {
+    value: f32,
+    target: f32,
+    ramp: Option<Ramp>,
+}
+
+/// Channels moving towards a target duty cycle.
+#[derive(Default)]
+pub struct Ramps {
+    // Maximum duty cycle change per second, 0 when unlimited
+    slew_rates: [f32; PWM_CHANNELS],
+    motions: [Option<Motion>; PWM_CHANNELS],
+    last_step: Option<Instant>,
+}
+
+impl Ramps {
+    pub fn set_slew_rate(&mut self, channel: usize, rate: f32) {
+        self.slew_rates[channel] = rate.max(0.0);
+    }
+
+    pub fn is_slew_limited(&self, channel: usize) -> bool {
+        self.slew_rates[channel] > 0.0
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.motions.iter().all(Option::is_none)
+    }
+
+    /// Sets the channel target, returns whether it should be written right away since the
+    /// channel has no slew-rate limit. `current` is the duty cycle currently output.
+    pub fn set_target(&mut self, channel: usize, duty_cycle: f32, current: f32) -> bool {
+        if !self.is_slew_limited(channel) {
+            self.motions[channel] = None;
+            return true;
+        }
+        let value = self.motions[channel].map_or(current, |motion| motion.value);
+        self.motions[channel] = Some(Motion {
+            value,
+            target: duty_cycle,
+            ramp: None,
+        });
+        false
+    }
+
+    /// Moves the channel linearly to `target` over `duration` from `now`, still following its
+    /// slew-rate limit.
+    pub fn ramp(
+        &mut self,
+        channel: usize,
+        target: f32,
+        duration: Duration,
+        current: f32,
+        now: Instant,
+    ) {
+        let value = self.motions[channel].map_or(current, |motion| motion.value);
+        self.motions[channel] = Some(Motion {
+            value,
+            target,
+            ramp: Some(Ramp {
+                from: value,
+                start: now,
+                duration,
+            }),
+        });
+    }
+
+    /// Stops the channel, keeping the last duty cycle written.
+    pub fn stop(&mut self, channel: usize) {
+        self.motions[channel] = None;
+    }
+
+    /// Advances the moving channels, returning the channels and duty cycles to write.
+    pub fn step(&mut self, now: Instant) -> (Vec<usize>, Vec<f32>) {
+        let dt = self
+            .last_step
+            .map_or(STEP_PERIOD, |last_step| now - last_step)
+            .as_secs_f32();
+        self.last_step = Some(now);
+
+        let mut channels = Vec::new();
+        let mut duty_cycles = Vec::new();
+        for (channel, slot) in self.motions.iter_mut().enumerate() {
+            let Some(motion) = slot else {
+                continue;
+            };
+            let desired = match motion.ramp {
+                Some(ramp) if now - ramp.start < ramp.duration => {
+                    let progress = (now - ramp.start).as_secs_f32() / ramp.duration.as_secs_f32();
+                    ramp.from + (motion.target - ramp.from) * progress
+                }
+                _ => motion.target,
+            };
+            let rate = self.slew_rates[channel];
+            let max_step = rate * dt;
+            motion.value = if rate > 0.0 && (desired - motion.value).abs() > max_step {
+                motion.value + max_step.copysign(desired - motion.value)
+            } else {
+                desired
+            };
+
+            channels.push(channel);
+            duty_cycles.push(motion.value);
+            if motion.value == motion.target {
+                *slot = None;
+            }
+        }
+
+        if self.is_idle() {
+            self.last_step = None;
+        }
+        (channels, duty_cycles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_step(ramps: &mut Ramps, now: Instant, expected: &[(usize, f32)]) {
+        let (channels, duty_cycles) = ramps.step(now);
+        assert_eq!(channels.len(), expected.len());
+        for ((channel, duty_cycle), (expected_channel, expected_duty_cycle)) in
+            channels.into_iter().zip(duty_cycles).zip(expected)
+        {
+            assert_eq!(channel, *expected_channel);
+            assert!(
+                (duty_cycle - expected_duty_cycle).abs() < 1e-4,
+                "channel {channel}: {duty_cycle} != {expected_duty_cycle}"
+            );
+        }
+    }
+
+    #[test]
+    fn unlimited_channels_are_written_right_away() {
+        let mut ramps = Ramps::default();
+        assert!(ramps.set_target(0, 0.5, 0.0));
+        assert!(ramps.is_idle());
+        assert_step(&mut ramps, Instant::now(), &[]);
+    }
+
+    #[test]
+    fn slew_rate_limits_each_step() {
+        let mut ramps = Ramps::default();
+        ramps.set_slew_rate(2, 1.0);
+        assert!(!ramps.set_target(2, 0.3, 0.1));
+
+        // The first step moves by a step period
+        let start = Instant::now();
+        assert_step(&mut ramps, start, &[(2, 0.105)]);
+        assert_step(
+            &mut ramps,
+            start + Duration::from_millis(100),
+            &[(2, 0.205)],
+        );
+        assert_step(&mut ramps, start + Duration::from_millis(200), &[(2, 0.3)]);
+        assert!(ramps.is_idle());
+
+        // Going down, from the duty cycle currently output
+        assert!(!ramps.set_target(2, 0.0, 0.3));
+        assert_step(
+            &mut ramps,
+            start + Duration::from_millis(300),
+            &[(2, 0.295)],
+        );
+        assert_step(
+            &mut ramps,
+            start + Duration::from_millis(400),
+            &[(2, 0.195)],
+        );
+    }
+
+    #[test]
+    fn ramps_move_linearly() {
+        let mut ramps = Ramps::default();
+        let start = Instant::now();
+        ramps.ramp(0, 1.0, Duration::from_secs(1), 0.2, start);
+        ramps.ramp(5, 0.0, Duration::from_secs(2), 1.0, start);
+
+        assert_step(
+            &mut ramps,
+            start + Duration::from_millis(250),
+            &[(0, 0.4), (5, 0.875)],
+        );
+        assert_step(
+            &mut ramps,
+            start + Duration::from_secs(1),
+            &[(0, 1.0), (5, 0.5)],
+        );
+        assert_step(&mut ramps, start + Duration::from_secs(3), &[(5, 0.0)]);
+        assert!(ramps.is_idle());
+    }
+
+    #[test]
+    fn ramps_follow_the_slew_rate() {
+        let mut ramps = Ramps::default();
+        ramps.set_slew_rate(0, 0.1);
+        let start = Instant::now();
+        ramps.ramp(0, 1.0, Duration::from_millis(500), 0.0, start);
+
+        assert_step(&mut ramps, start, &[(0, 0.0)]);
+        assert_step(&mut ramps, start + Duration::from_secs(1), &[(0, 0.1)]);
+        assert!(!ramps.is_idle());
+    }
+
+    #[test]
+    fn stop_keeps_the_last_duty_cycle() {
+        let mut ramps = Ramps::default();
+        let start = Instant::now();
+        ramps.ramp(3, 1.0, Duration::from_secs(1), 0.0, start);
+        assert_step(&mut ramps, start + Duration::from_millis(500), &[(3, 0.5)]);
+
+        ramps.stop(3);
+        assert!(ramps.is_idle());
+        assert_step(&mut ramps, start + Duration::from_secs(1), &[]);
+
+        // A new ramp starts from the duty cycle currently output
+        ramps.ramp(
+            3,
+            0.0,
+            Duration::from_secs(1),
+            0.5,
+            start + Duration::from_secs(1),
+        );
+        assert_step(
+            &mut ramps,
+            start + Duration::from_millis(1500),
+            &[(3, 0.25)],
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! ArduPilot-style vibration and clipping metrics, computed from the accelerometer readings.
+
+use crate::filters::{Filter, FirstOrderLowPass};
+use std::time::Instant;
+
+/// Cutoff of the filter estimating the acceleration without vibration, same as ArduPilot.
+const FLOOR_CUTOFF_HZ: f32 = 5.0;
+/// Cutoff of the filter averaging the squared vibration, same as ArduPilot.
+const VIBRATION_CUTOFF_HZ: f32 = 2.0;
+/// Margin below the accelerometer range beyond which a sample is considered clipped [m/s²], half a
+/// g like ArduPilot.
+const CLIP_MARGIN: f32 = 0.5 * 9.80665;
+/// Acceleration considered clipped with the ±8g range configured by navigator-rs [m/s²].
+const CLIP_LIMIT: f32 = 8.0 * 9.80665 - CLIP_MARGIN;
+
+pub struct VibrationMonitor {
+    floor: [FirstOrderLowPass; 3],
+    vibration: [FirstOrderLowPass; 3],
+    clipping: u32,
+    clip_limit: f32,
+    last_sample: Option<Instant>,
+}
+
+impl VibrationMonitor {
+    pub fn new() -> Self {
+        Self {
+            floor: std::array::from_fn(|_| FirstOrderLowPass::new(FLOOR_CUTOFF_HZ)),
+            vibration: std::array::from_fn(|_| FirstOrderLowPass::new(VIBRATION_CUTOFF_HZ)),
+            clipping: 0,
+            clip_limit: CLIP_LIMIT,
+            last_sample: None,
+        }
+    }
+
+    /// Clears the metrics, keeping the accelerometer range.
+    pub fn reset(&mut self) {
+        *self = Self {
+            clip_limit: self.clip_limit,
+            ..Self::new()
+        };
+    }
+
+    /// Counts the clipping against an accelerometer range of ±`full_scale` [m/s²].
+    pub fn set_accel_range(&mut self, full_scale: f32) {
+        self.clip_limit = full_scale - CLIP_MARGIN;
+    }
+
+    /// Updates the metrics with a new accelerometer sample [m/s²], taken at `now`.
+    pub fn update(&mut self, accel: [f32; 3], now: Instant) {
+        let dt = self
+            .last_sample
+            .map_or(0.0, |last_sample| (now - last_sample).as_secs_f32());
+        self.last_sample = Some(now);
+
+        if accel.iter().any(|value| value.abs() > self.clip_limit) {
+            self.clipping += 1;
+        }
+        for ((value, floor), vibration) in accel
+            .into_iter()
+            .zip(&mut self.floor)
+            .zip(&mut self.vibration)
+        {
+            let difference = value - floor.apply(value, dt);
+            vibration.apply(difference * difference, dt);
+        }
+    }
+
+    /// Vibration level of each axis [m/s²], the RMS of the high-passed acceleration.
+    pub fn vibration(&self) -> [f32; 3] {
+        std::array::from_fn(|axis| self.vibration[axis].value().sqrt())
+    }
+
+    /// Number of samples with an axis beyond the accelerometer range.
+    pub fn clipping(&self) -> u32 {
+        self.clipping
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const GRAVITY: [f32; 3] = [0.0, 0.0, -9.80665];
+
+    /// Feeds `samples` at 1 kHz, `accel` giving the sample of each index.
+    fn feed(monitor: &mut VibrationMonitor, samples: u32, accel: impl Fn(u32) -> [f32; 3]) {
+        let start = Instant::now();
+        for index in 0..samples {
+            monitor.update(accel(index), start + Duration::from_millis(index.into()));
+        }
+    }
+
+    #[test]
+    fn steady_acceleration_has_no_vibration() {
+        let mut monitor = VibrationMonitor::new();
+        feed(&mut monitor, 2000, |_| GRAVITY);
+        assert!(monitor.vibration().iter().all(|axis| *axis < 1e-3));
+        assert_eq!(monitor.clipping(), 0);
+    }
+
+    #[test]
+    fn measures_vibration_of_each_axis() {
+        let mut monitor = VibrationMonitor::new();
+        // ±2 m/s² square wave at 50 Hz on x, well above the floor cutoff
+        feed(&mut monitor, 4000, |index| {
+            let x = if index / 10 % 2 == 0 { 2.0 } else { -2.0 };
+            [x, GRAVITY[1], GRAVITY[2]]
+        });
+        let [x, y, z] = monitor.vibration();
+        assert!((1.5..=2.5).contains(&x), "x vibration {x}");
+        assert!(y < 1e-3 && z < 1e-3);
+    }
+
+    #[test]
+    fn counts_clipped_samples() {
+        let mut monitor = VibrationMonitor::new();
+        feed(&mut monitor, 10, |index| match index % 3 {
+            0 => [CLIP_LIMIT + 1.0, 0.0, 0.0],
+            1 => [0.0, 0.0, -CLIP_LIMIT - 1.0],
+            _ => GRAVITY,
+        });
+        assert_eq!(monitor.clipping(), 7);
+    }
+
+    #[test]
+    fn clipping_follows_the_accelerometer_range() {
+        let mut monitor = VibrationMonitor::new();
+        monitor.set_accel_range(2.0 * 9.80665);
+        feed(&mut monitor, 4, |index| match index {
+            0 => [0.0, 0.0, -2.0 * 9.80665],
+            _ => GRAVITY,
+        });
+        assert_eq!(monitor.clipping(), 1);
+    }
+}
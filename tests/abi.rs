@@ -0,0 +1,55 @@
+//! Compares the generated C++ bindings with `tests/snapshots/bindings.h`, so changes of the C ABI
+//! are deliberate. After reviewing a change, run the test with `UPDATE_SNAPSHOTS=1` to accept it,
+//! and increment `NAVIGATOR_ABI_VERSION` if existing callers would break.
+
+const BINDINGS: &str = include_str!(concat!(env!("OUT_DIR"), "/bindings.h"));
+const SNAPSHOT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/bindings.h");
+
+#[test]
+fn bindings_match_snapshot() {
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(SNAPSHOT_PATH, BINDINGS).expect("Unable to write snapshot");
+        return;
+    }
+    let snapshot = std::fs::read_to_string(SNAPSHOT_PATH)
+        .expect("Missing snapshot, run with UPDATE_SNAPSHOTS=1 to create it");
+
+    if let Some((line, (expected, generated))) = snapshot
+        .lines()
+        .zip(BINDINGS.lines())
+        .enumerate()
+        .find(|(_, (expected, generated))| expected != generated)
+    {
+        panic!(
+            "bindings.h differs from the snapshot at line {}:\n- {expected}\n+ {generated}\n\
+            Run with UPDATE_SNAPSHOTS=1 to accept the change.",
+            line + 1
+        );
+    }
+    assert_eq!(
+        snapshot.lines().count(),
+        BINDINGS.lines().count(),
+        "bindings.h length differs from the snapshot, run with UPDATE_SNAPSHOTS=1 to accept the change"
+    );
+}
+
+#[test]
+fn abi_version_matches_header() {
+    assert_eq!(
+        bluerobotics_navigator::navigator_abi_version(),
+        bluerobotics_navigator::NAVIGATOR_ABI_VERSION
+    );
+    assert!(BINDINGS.contains(&format!(
+        "NAVIGATOR_ABI_VERSION = {}",
+        bluerobotics_navigator::NAVIGATOR_ABI_VERSION
+    )));
+}
+
+// The Python module returns a `str` instead
+#[cfg(not(feature = "python"))]
+#[test]
+fn lib_version_matches_package() {
+    let version =
+        unsafe { std::ffi::CStr::from_ptr(bluerobotics_navigator::navigator_lib_version()) };
+    assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+}
@@ -0,0 +1,163 @@
+//! Budget of the Lua scripts, which catching the errors must not escape.
+#![cfg(feature = "lua")]
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use bluerobotics_navigator::lua::{new_state, Limits};
+
+fn run(source: &str, limits: &Limits) -> mlua::Result<()> {
+    new_state(limits)?.load(source).exec()
+}
+
+fn instructions(count: u64) -> Limits {
+    Limits {
+        instructions: Some(count),
+        time: None,
+        memory: None,
+    }
+}
+
+#[test]
+fn stops_runaway_scripts() {
+    let error = run("while true do end", &instructions(100_000)).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Instruction budget of 100000 exhausted"));
+}
+
+#[test]
+fn pcall_does_not_escape_the_instruction_budget() {
+    let error = run(
+        "while true do pcall(function() while true do end end) end",
+        &instructions(100_000),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("Instruction budget"), "{error}");
+}
+
+#[test]
+fn xpcall_and_coroutines_do_not_escape_the_budget() {
+    for source in [
+        "while true do xpcall(function() while true do end end, function() while true do end end) end",
+        "while true do coroutine.resume(coroutine.create(function() while true do end end)) end",
+        "local ok = pcall(function() while true do end end) while true do local x = ok end",
+        "while true do xpcall(error, function() while true do end end) end",
+    ] {
+        let error = run(source, &instructions(100_000)).unwrap_err();
+        assert!(error.to_string().contains("Instruction budget"), "{error}");
+    }
+}
+
+#[test]
+fn pcall_does_not_escape_the_time_budget() {
+    let limits = Limits {
+        instructions: None,
+        time: Some(Duration::from_millis(100)),
+        memory: None,
+    };
+    let start = Instant::now();
+    let error = run(
+        "while true do pcall(function() while true do end end) end",
+        &limits,
+    )
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("Time budget exhausted"),
+        "{error}"
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn finalizers_are_not_allowed() {
+    let error = run(
+        "setmetatable({}, { __gc = function() while true do end end })",
+        &instructions(100_000),
+    )
+    .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("__gc metamethods are not allowed"));
+}
+
+#[test]
+fn caught_errors_keep_working_within_the_budget() {
+    run(
+        r##"
+        local ok, message = pcall(function() error("expected") end)
+        assert(not ok and message:find("expected"))
+        assert(select("#", pcall(function() return 1, 2, 3 end)) == 4)
+        local ok, handled = xpcall(error, function(message) return "handled " .. message end, "x")
+        assert(not ok and handled == "handled x")
+        assert(getmetatable(setmetatable({}, { __index = {} })))
+        "##,
+        &instructions(100_000),
+    )
+    .unwrap();
+}
+
+#[test]
+fn max_instructions_stops_caught_iterations() {
+    let script = std::env::temp_dir().join(format!("navigator-lua-{}.lua", std::process::id()));
+    std::fs::write(
+        &script,
+        "while true do pcall(function() while true do end end) end",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_navigator-lua"))
+        .args(["--max-instructions", "100000", "--timeout", "10"])
+        .arg(&script)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&script).unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Instruction budget of 100000 exhausted"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn library_calls_do_not_run_past_the_budget() {
+    let start = Instant::now();
+    for source in [
+        r#"string.find(string.rep("a", 40), ".-.-.-.-.-.-.-.-.-.-b")"#,
+        r#"("a"):rep(40):find("(.-)*b")"#,
+        r#"string.gsub(string.rep("a", 40), ".-.-.-.-.-.-.-.-.-.-b", "")"#,
+        r#"string.rep("", 1e15)"#,
+        r#"string.rep("x", 1e9, ",")"#,
+        r#"table.move({}, 1, 1e12, 2)"#,
+    ] {
+        assert!(run(source, &instructions(100_000)).is_err(), "{source}");
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn plain_searches_and_bounded_copies_are_allowed() {
+    run(
+        r#"
+        assert(("a.b"):find(".", 1, true) == 2)
+        assert(string.find("navigator", "gat") == 5)
+        assert(string.rep("ab", 3, ",") == "ab,ab,ab")
+        assert(table.move({ 1, 2, 3 }, 1, 3, 2)[4] == 3)
+        "#,
+        &instructions(100_000),
+    )
+    .unwrap();
+}
+
+#[test]
+fn failed_hardware_accesses_are_lua_errors() {
+    run(
+        r#"
+        local ok, message = pcall(navigator.read_pressure)
+        assert(not ok and tostring(message):find("Failed to create", 1, true))
+        "#,
+        &instructions(100_000),
+    )
+    .unwrap();
+}
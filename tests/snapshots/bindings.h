@@ -0,0 +1,919 @@
+#include <cstdarg>
+#include <cstdint>
+#include <cstdlib>
+#include <ostream>
+#include <new>
+
+/// Version of the C ABI, incremented when an exported function or type changes incompatibly.
+/// Compare it with `navigator_abi_version` to check the loaded library matches the header.
+constexpr static const uint32_t NAVIGATOR_ABI_VERSION = 1;
+
+/// Capability bit, the `navigatord` daemon backend is available.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_DAEMON = (1 << 0);
+
+/// Capability bit, the `navigator_*` handle functions are available.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_HANDLE = (1 << 1);
+
+/// Capability bit, the sensor filter functions are available.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_FILTERS = (1 << 2);
+
+/// Capability bit, the PWM slew-rate limit and ramp functions are available.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_PWM_RAMPS = (1 << 3);
+
+/// Capability bit, built with the `python` feature.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_PYTHON = (1 << 4);
+
+/// Capability bit, built with the `numpy` feature.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_NUMPY = (1 << 5);
+
+/// Capability bit, built with the `node` feature.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_NODE = (1 << 6);
+
+/// Capability bit, built with the `lua` feature.
+constexpr static const uint32_t NAVIGATOR_CAPABILITY_LUA = (1 << 7);
+
+///Full-scale ranges of the ICM20689 accelerometer, in g (±8g by default).
+enum class AccelRange {
+  G2,
+  G4,
+  G8,
+  G16,
+};
+
+///Available ADC channels to read from.
+enum class AdcChannel {
+  Ch0,
+  Ch1,
+  Ch2,
+  Ch3,
+};
+
+///Hardware access backend.
+enum class Backend {
+  Local,
+  Daemon,
+};
+
+///Sensor channels that can have a filter attached, axis and ADC values are filtered independently.
+enum class FilterChannel {
+  Accel,
+  Gyro,
+  Mag,
+  Pressure,
+  Temperature,
+  Adc,
+};
+
+///Full-scale ranges of the ICM20689 gyroscope, in degrees per second (±2000dps by default).
+enum class GyroRange {
+  Dps250,
+  Dps500,
+  Dps1000,
+  Dps2000,
+};
+
+///Bandwidths of the ICM20689 digital low-pass filter, applied to both the accelerometer and the gyroscope.
+enum class ImuDlpf {
+  Hz176,
+  Hz92,
+  Hz41,
+  Hz20,
+  Hz10,
+  Hz5,
+};
+
+///Navigator version, `Auto` (the default) detects it from the board's barometer.
+enum class NavigatorVersion {
+  Version1,
+  Version2,
+  Auto,
+};
+
+///Available PWM channels, `All` selects the 16 channels at once. Functions taking a channel also accept its index (0 for `Ch1`, 16 for `All`), other values are rejected.
+enum class PwmChannel {
+  Ch1,
+  Ch2,
+  Ch3,
+  Ch4,
+  Ch5,
+  Ch6,
+  Ch7,
+  Ch8,
+  Ch9,
+  Ch10,
+  Ch11,
+  Ch12,
+  Ch13,
+  Ch14,
+  Ch15,
+  Ch16,
+  All,
+};
+
+///Output driver of the PWM channels.
+enum class PwmOutputMode {
+  TotemPole,
+  OpenDrain,
+};
+
+///Raspberry Pi version, `Auto` (the default) detects it from the device tree.
+enum class Raspberry {
+  Pi4,
+  Pi5,
+  Auto,
+};
+
+///Board mounting rotations, following ArduPilot's list (AHRS_ORIENTATION).
+enum class Rotation {
+  NoRotation,
+  Yaw45,
+  Yaw90,
+  Yaw135,
+  Yaw180,
+  Yaw225,
+  Yaw270,
+  Yaw315,
+  Roll180,
+  Roll180Yaw45,
+  Roll180Yaw90,
+  Roll180Yaw135,
+  Pitch180,
+  Roll180Yaw225,
+  Roll180Yaw270,
+  Roll180Yaw315,
+  Roll90,
+  Roll90Yaw45,
+  Roll90Yaw90,
+  Roll90Yaw135,
+  Roll270,
+  Roll270Yaw45,
+  Roll270Yaw90,
+  Roll270Yaw135,
+  Pitch90,
+  Pitch270,
+  Pitch180Yaw90,
+  Pitch180Yaw270,
+  Roll90Pitch90,
+  Roll180Pitch90,
+  Roll270Pitch90,
+  Roll90Pitch180,
+  Roll270Pitch180,
+  Roll90Pitch270,
+  Roll180Pitch270,
+  Roll270Pitch270,
+  Roll90Pitch180Yaw90,
+  Roll90Yaw270,
+  Roll90Pitch68Yaw293,
+  Pitch315,
+  Roll90Pitch315,
+  Pitch7,
+  Roll45,
+  Roll315,
+};
+
+///Sensors whose health is monitored: the barometer (BMP280 or BMP390), the AK09915 magnetometer, the ICM20689 accelerometer and gyroscope, and the ADS1115 ADC.
+enum class Sensor {
+  Barometer,
+  Magnetometer,
+  Imu,
+  Adc,
+};
+
+///Health of a sensor. `NoData` until it's read, `Failing` while its reads fail, `OutOfRange` when a value is beyond what it can measure and `Stuck` when a value stopped changing.
+enum class SensorStatus {
+  NoData,
+  Healthy,
+  Failing,
+  Stuck,
+  OutOfRange,
+};
+
+///Onboard user-controllable LEDs.
+enum class UserLed {
+  Led1,
+  Led2,
+  Led3,
+};
+
+/// LEDn_ON and LEDn_OFF register values of a channel.
+struct Counters;
+
+/// Navigator configuration, with the same options as the `set_*` functions used before `init`.
+/// Starts from the file named by `NAVIGATOR_CONFIG`, if any.
+struct NavigatorBuilder;
+
+/// A Navigator instance, independent from the default one used by the free functions.
+///
+/// Created from a `NavigatorBuilder` (the default configuration if omitted), the hardware or daemon
+/// connection is released by `close`, or when leaving a `with` block.
+///
+/// Examples:
+///     >>> from bluerobotics_navigator import Backend, Navigator, NavigatorBuilder
+///     >>> builder = NavigatorBuilder().with_backend(Backend.Daemon)
+///     >>> with Navigator(builder) as nav:
+///     ...     print(nav.read_temp())
+struct NavigatorHandle;
+
+///Board configuration of the default instance, as set by the configuration functions with `Auto` versions resolved. `daemon` is true when the hardware is accessed through `navigatord`.
+struct BoardInfo {
+  NavigatorVersion navigator_version;
+  Raspberry raspberry_pi_version;
+  uintptr_t rgb_led_strip_size;
+  bool daemon;
+};
+
+///Hardware found by the detection used for the `Auto` versions. When `raspberry_pi_detected` or `navigator_detected` is false, the version couldn't be identified and falls back to `Pi4` or `Version1`.
+struct DetectedHardware {
+  Raspberry raspberry_pi_version;
+  NavigatorVersion navigator_version;
+  bool raspberry_pi_detected;
+  bool navigator_detected;
+};
+
+///Board-oriented direction axes (x is forwards, y is right, z is down).
+struct AxisData {
+  float x;
+  float y;
+  float z;
+};
+
+///Measurements from all sensors, taken in a single pass. `timestamp` is the time of the sample in microseconds since the UNIX epoch.
+struct SensorData {
+  AxisData accelerometer;
+  AxisData gyro;
+  AxisData magnetometer;
+  float pressure;
+  float temperature;
+  float adc[4];
+  bool leak;
+  uint64_t timestamp;
+};
+
+///Accelerometer and gyroscope measurements of a sample taken from the IMU FIFO.
+struct ImuSample {
+  AxisData accelerometer;
+  AxisData gyro;
+};
+
+///ArduPilot-style vibration levels and accelerometer clipping count. `vibration` is the RMS of the high-passed acceleration of each axis in [m/s²], `clipping` the number of samples beyond the accelerometer range.
+struct VibrationData {
+  AxisData vibration;
+  uint32_t clipping;
+};
+
+///Health of a sensor, from the reads of the default instance. `error_count` is the number of failed reads, `invalid_count` of samples that were stuck or out of range, and `consecutive_errors` of failed reads since the last successful one. `last_good_timestamp` is the time of the last valid sample in microseconds since the UNIX epoch, 0 if there is none.
+struct SensorHealth {
+  Sensor sensor;
+  SensorStatus status;
+  uint64_t sample_count;
+  uint64_t error_count;
+  uint64_t invalid_count;
+  uint32_t consecutive_errors;
+  uint64_t last_good_timestamp;
+  bool stuck;
+  bool out_of_range;
+};
+
+///Measurement with its validity, `value` is NaN and `valid` false when the read failed, or the value is stuck or out of range.
+struct CheckedValue {
+  float value;
+  bool valid;
+};
+
+///Axis measurements with their validity, the axes are NaN and `valid` false when the read failed, or a value is stuck or out of range.
+struct CheckedAxisData {
+  AxisData value;
+  bool valid;
+};
+
+/// PWM channel argument, the index of a `PwmChannel` (0 for `Ch1`, 16 for `All`).
+using PwmChannelArg = uintptr_t;
+
+///State of the PWM outputs, as written to the PCA9685. `frequency` is the achieved frequency in [Hz] after the prescale rounding, and `duty_cycles` the achieved duty cycle (0.0 : 1.0) of each channel.
+struct PwmState {
+  bool enabled;
+  float frequency;
+  uint8_t prescale;
+  float duty_cycles[16];
+};
+
+
+
+
+
+
+
+
+
+extern "C" {
+
+///Sets the size of the navigator led strip (1 is the default), should be called before `init`.
+void set_rgb_led_strip_size(uintptr_t size);
+
+///Sets the navigator version, `Auto` (the default) detects it.
+void set_navigator_version(NavigatorVersion version);
+
+///Sets the raspberry pi version, `Auto` (the default) detects it.
+void set_raspberry_pi_version(Raspberry version);
+
+///Sets the frequency of the clock connected to the PWM chip (PCA9685) EXTCLK pin, should be called before `init`.
+void set_pwm_clock_hz(float clock);
+
+///Sets how the board is mounted in the vehicle, applied to the accelerometer, gyroscope and magnetometer readings.
+void set_board_orientation(Rotation rotation);
+
+///Sets how the board is mounted in the vehicle with a custom rotation matrix, as 9 row-major values.
+void set_board_orientation_matrix(const float *matrix);
+
+///Selects how the hardware is accessed, should be called before `init`. With `Backend::Daemon`, every call is forwarded to `navigatord` through the Unix socket `socket_path` (`/run/navigator.sock` if null).
+void set_backend(Backend backend,
+                 const char *socket_path);
+
+///Applies the TOML configuration file at `path` (board and Raspberry Pi versions, led strip size, orientation, PWM clock, frequency and limits, channel names, filters), should be called before `init`. Returns false if it can't be read or is invalid, see `navigator_last_error`, the configuration is unchanged then.
+bool load_config(const char *path);
+
+///Initializes the Navigator module with default settings (not necessary).
+void init();
+
+///Releases the default instance, used by the free functions. It's created again by the next call.
+void deinit();
+
+///Runs some tests on available sensors, then returns the result (not necessary).
+bool self_test();
+
+///Returns the library version, as a static NUL-terminated string (e.g. `0.1.1`).
+const char *navigator_lib_version();
+
+///Returns the version of the navigator-rs crate used to access the hardware, as a static NUL-terminated string (`unknown` if it couldn't be found at build time).
+const char *navigator_rs_version();
+
+///Returns the ABI version of the loaded library, to be compared with `NAVIGATOR_ABI_VERSION`.
+uint32_t navigator_abi_version();
+
+///Returns the capabilities of the loaded library, as a bitmask of the `NAVIGATOR_CAPABILITY_*` values.
+uint32_t navigator_capabilities();
+
+///Returns the board configuration used by the default instance.
+BoardInfo get_board_info();
+
+///Returns the Raspberry Pi and Navigator versions found by the detection, used for the `Auto` versions. With `Backend::Daemon`, this is the detection of `navigatord`.
+DetectedHardware get_detected_hardware();
+
+///Sets the state of the selected onboard LED.
+void set_led(UserLed select, bool state);
+
+///Gets the selected onboard LED output state.
+bool get_led(UserLed select);
+
+///Toggle the output of the selected LED.
+void set_led_toggle(UserLed select);
+
+///Sets all user LEDs to the desired state ( Blue, Green, and Red ).
+void set_led_all(bool state);
+
+///Set the color brightnesses of a connected NeoPixel LED array.
+void set_neopixel(const uint8_t (*rgb_array)[3], uintptr_t length);
+
+///Set the color brightnesses of a connected NeoPixel LED array.
+void set_neopixel_rgbw(const uint8_t (*rgb_array)[4], uintptr_t length);
+
+///No documentation
+void read_adc_all(float *adc_array, uintptr_t length);
+
+///Reads a specific ADC channel (from the ADS1115 chip).
+float read_adc(AdcChannel channel);
+
+///Reads the current pressure (from the onboard BMP280 chip).
+float read_pressure();
+
+///Reads the current temperature (from the onboard BMP280 chip).
+float read_temp();
+
+///Reads the local magnetic field strengths (from the onboard Ak09915 magnetometer).
+AxisData read_mag();
+
+///Reads the current acceleration values (from the ICM20689 chip's accelerometer).
+AxisData read_accel();
+
+///Reads the current angular velocity (from the ICM20689 chip's gyroscope).
+AxisData read_gyro();
+
+///Reads all sensors in a single pass, so the measurements are time-aligned.
+SensorData read_all();
+
+///Sets the full-scale range of the accelerometer, the readings stay in [m/s²].
+void set_accel_range(AccelRange range);
+
+///Sets the full-scale range of the gyroscope, the readings stay in [rad/s].
+void set_gyro_range(GyroRange range);
+
+///Sets the bandwidth of the IMU digital low-pass filter, for both the accelerometer and the gyroscope.
+void set_imu_dlpf(ImuDlpf dlpf);
+
+///Sets the IMU sample rate, from 3.9 to 1000 Hz, rounded to the closest rate of the sample rate divider. Enables the low-pass filter at 176 Hz if `set_imu_dlpf` wasn't called. Returns false if the rate is out of range, see `navigator_last_error`.
+bool set_imu_sample_rate(float rate_hz);
+
+///Reads up to `capacity` accelerometer and gyroscope samples from the IMU FIFO, oldest first, to `samples`, with their number written to `count`. The FIFO is started by the first call, which reads no samples, and holds 341 samples: it must be read faster than it fills at the rate of `set_imu_sample_rate`. Returns false if the FIFO overflowed, its samples are then dropped, see `navigator_last_error`.
+bool read_imu_fifo(ImuSample *samples,
+                   uintptr_t capacity,
+                   uintptr_t *count);
+
+///Reads the vibration levels and clipping count, computed from the accelerometer readings of the other read functions.
+VibrationData read_vibration();
+
+///Resets the vibration levels and clipping count.
+void reset_vibration();
+
+///Writes the health of each sensor (barometer, magnetometer, IMU and ADC, in this order) to `health`, up to `length` entries. Returns the number of sensors.
+uintptr_t get_sensor_health(SensorHealth *health,
+                            uintptr_t length);
+
+///Resets the health of every sensor.
+void reset_sensor_health();
+
+///Reads a specific ADC channel, with NaN and `valid` false instead of a failed, stuck or out of range value.
+CheckedValue read_adc_checked(AdcChannel channel);
+
+///Reads the current pressure, with NaN and `valid` false instead of a failed, stuck or out of range value.
+CheckedValue read_pressure_checked();
+
+///Reads the current temperature, with NaN and `valid` false instead of a failed or out of range value.
+CheckedValue read_temp_checked();
+
+///Reads the local magnetic field strengths, with NaN axes and `valid` false instead of a failed, stuck or out of range value.
+CheckedAxisData read_mag_checked();
+
+///Reads the current acceleration values, with NaN axes and `valid` false instead of a failed, stuck or out of range value.
+CheckedAxisData read_accel_checked();
+
+///Reads the current angular velocity, with NaN axes and `valid` false instead of a failed, stuck or out of range value.
+CheckedAxisData read_gyro_checked();
+
+///Attaches a Butterworth low-pass filter to a sensor channel, used by the `read_*_filtered` functions. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool set_low_pass_filter(FilterChannel channel,
+                         float sample_rate,
+                         float cutoff);
+
+///Attaches a notch filter to a sensor channel, used by the `read_*_filtered` functions. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool set_notch_filter(FilterChannel channel,
+                      float sample_rate,
+                      float center,
+                      float bandwidth);
+
+///Attaches a 1-euro filter to a sensor channel, used by the `read_*_filtered` functions. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool set_one_euro_filter(FilterChannel channel,
+                         float min_cutoff,
+                         float beta);
+
+///Attaches a moving average filter to a sensor channel, used by the `read_*_filtered` functions. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool set_moving_average_filter(FilterChannel channel,
+                               uintptr_t window);
+
+///Attaches a moving median filter to a sensor channel, used by the `read_*_filtered` functions. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool set_median_filter(FilterChannel channel,
+                       uintptr_t window);
+
+///Removes the filter attached to a sensor channel, the `read_*_filtered` functions return raw values.
+void clear_filter(FilterChannel channel);
+
+///No documentation
+void read_adc_all_filtered(float *adc_array, uintptr_t length);
+
+///Reads a specific ADC channel, filtered by the filter attached to the Adc channel.
+float read_adc_filtered(AdcChannel channel);
+
+///Reads the current pressure, filtered by the filter attached to the Pressure channel.
+float read_pressure_filtered();
+
+///Reads the current temperature, filtered by the filter attached to the Temperature channel.
+float read_temp_filtered();
+
+///Reads the local magnetic field strengths, filtered by the filter attached to the Mag channel.
+AxisData read_mag_filtered();
+
+///Reads the current acceleration values, filtered by the filter attached to the Accel channel.
+AxisData read_accel_filtered();
+
+///Reads the current angular velocity, filtered by the filter attached to the Gyro channel.
+AxisData read_gyro_filtered();
+
+///No documentation
+bool filter_low_pass(float *values, uintptr_t length, float sample_rate, float cutoff);
+
+///No documentation
+bool filter_notch(float *values,
+                  uintptr_t length,
+                  float sample_rate,
+                  float center,
+                  float bandwidth);
+
+///No documentation
+bool filter_one_euro(float *values,
+                     uintptr_t length,
+                     float sample_rate,
+                     float min_cutoff,
+                     float beta);
+
+///No documentation
+bool filter_moving_average(float *values, uintptr_t length, uintptr_t window);
+
+///No documentation
+bool filter_median(float *values, uintptr_t length, uintptr_t window);
+
+///Reads the state of leak detector pin from Navigator.
+bool read_leak();
+
+///Enables or disables the PWM chip (PCA9685), using the firmware and OE_pin.
+void set_pwm_enable(bool state);
+
+///Makes this process the only client of `navigatord` driving the PWM outputs, until `release_pwm` is called or it disconnects. The PWM functions fail until then with the daemon backend, the local backend always owns them. Returns false if another client claimed them, see `navigator_last_error`.
+bool claim_pwm();
+
+///Releases the PWM outputs claimed by `claim_pwm`, so another client of `navigatord` can claim them.
+void release_pwm();
+
+///Sets the PWM frequency of the PCA9685 chip. All channels use the same frequency. Returns false if the frequency is out of range, see `navigator_last_error`.
+bool set_pwm_freq_hz(float freq);
+
+///Sets the PWM frequency of the PCA9685 chip by its pre-scaler value. All channels use the same frequency. Returns false if the value is below 3, see `navigator_last_error`.
+bool set_pwm_freq_prescale(uint8_t prescale);
+
+///Selects the output driver of the PWM channels, `TotemPole` (the default) or `OpenDrain`.
+void set_pwm_output_mode(PwmOutputMode mode);
+
+///Inverts the logic state of the PWM outputs, for drivers active low.
+void set_pwm_invert(bool inverted);
+
+///Sets the duty cycle (the proportion of ON time) for the selected PWM channel. Returns false if the channel is invalid, see `navigator_last_error`.
+bool set_pwm_channel_value(PwmChannelArg channel,
+                           float value);
+
+///Sets the duty cycle (the proportion of ON time) for the selected PWM channel. Returns false if the channel is invalid, see `navigator_last_error`.
+bool set_pwm_channel_duty_cycle(PwmChannelArg channel,
+                                float duty_cycle);
+
+///Sets the selected PWM channel fully ON, using the chip's full-ON bit. Returns false if the channel is invalid, see `navigator_last_error`.
+bool set_pwm_channel_full_on(PwmChannelArg channel);
+
+///Sets the selected PWM channel fully OFF, using the chip's full-OFF bit. Returns false if the channel is invalid, see `navigator_last_error`.
+bool set_pwm_channel_full_off(PwmChannelArg channel);
+
+///Sets both counters of the selected PWM channel: the output turns ON at the `on` count and OFF at the `off` count of each cycle. Returns false if a counter is above 4095, see `navigator_last_error`.
+bool set_pwm_channel_on_off(PwmChannelArg channel,
+                            uint16_t on,
+                            uint16_t off);
+
+///Enables or disables the phase staggering of the PWM channels, spreading their ON times over the cycle.
+void set_pwm_phase_stagger(bool enabled);
+
+///Sets the duty cycle (based on OFF counter from 0 to 1) for a list of multiple PWM channels. Returns false if a channel is invalid, nothing is set then, see `navigator_last_error`.
+bool set_pwm_channels_value(const uintptr_t *channels,
+                            float value,
+                            uintptr_t length);
+
+///Sets the duty cycle (from 0.0 to 1.0) for a list of multiple PWM channels. Returns false if a channel is invalid, nothing is set then, see `navigator_last_error`.
+bool set_pwm_channels_duty_cycle(const uintptr_t *channels,
+                                 float duty_cycle,
+                                 uintptr_t length);
+
+///Sets the duty cycle (from 0 to 4096) for a list of multiple channels with multiple values. Returns false if a channel is invalid, nothing is set then, see `navigator_last_error`.
+bool set_pwm_channels_values(const uintptr_t *channels,
+                             const float *values,
+                             uintptr_t length);
+
+///Sets the duty cycle (from 0.0 to 1.0) for a list of multiple channels with multiple values. Returns false if a channel is invalid, nothing is set then, see `navigator_last_error`.
+bool set_pwm_channels_duty_cycle_values(const uintptr_t *channels,
+                                        const float *duty_cycle,
+                                        uintptr_t length);
+
+///Reads back the state of all PWM outputs.
+PwmState get_pwm_state();
+
+///Reads back whether the PWM outputs are enabled.
+bool get_pwm_enable();
+
+///Reads back the achieved PWM frequency, after rounding to the closest prescale value.
+float get_pwm_freq_hz();
+
+///Reads back the PWM prescale value, which sets the frequency.
+uint8_t get_pwm_prescale();
+
+///Reads back the achieved duty cycle of the selected PWM channel.
+float get_pwm_channel_duty_cycle(PwmChannelArg channel);
+
+///Limits how fast the duty cycle of the selected PWM channel changes, 0 removes the limit. Returns false if the channel is invalid, see `navigator_last_error`.
+bool set_pwm_slew_rate(PwmChannelArg channel,
+                       float rate);
+
+///Moves the duty cycle of the selected PWM channel linearly to a target, over a duration in milliseconds. Returns false if the channel is invalid, see `navigator_last_error`.
+bool ramp_pwm_channel(PwmChannelArg channel,
+                      float target,
+                      uint32_t duration_ms);
+
+///Stops the ramp of the selected PWM channel, keeping its current duty cycle. Returns false if the channel is invalid, see `navigator_last_error`.
+bool stop_pwm_ramp(PwmChannelArg channel);
+
+///Names a PWM channel, by its index (0 for `Ch1`), replacing its previous name. Returns false if the name is empty or the channel invalid, see `navigator_last_error`.
+bool set_pwm_channel_name(const char *name,
+                          uintptr_t channel);
+
+///Names an ADC channel, replacing its previous name. Returns false if the name is empty, see `navigator_last_error`.
+bool set_adc_channel_name(const char *name,
+                          AdcChannel channel);
+
+///Removes a PWM or ADC channel name, returns false if no channel had this name.
+bool remove_channel_name(const char *name);
+
+///Removes all PWM and ADC channel names.
+void clear_channel_names();
+
+///Copies the name of a PWM channel, by its index (0 for `Ch1`), to the `length` bytes of `name`, truncated and NUL-terminated like `snprintf`. Returns the length of the name, 0 if the channel has none.
+uintptr_t get_pwm_channel_name(uintptr_t channel,
+                               char *name,
+                               uintptr_t length);
+
+///Copies the name of an ADC channel to the `length` bytes of `name`, truncated and NUL-terminated like `snprintf`. Returns the length of the name, 0 if the channel has none.
+uintptr_t get_adc_channel_name(AdcChannel channel,
+                               char *name,
+                               uintptr_t length);
+
+///Sets the duty cycle (from 0.0 to 1.0) of a named PWM channel, like `set_pwm_channel_duty_cycle`. Returns false if no channel has this name, see `navigator_last_error`.
+bool set_output(const char *name,
+                float duty_cycle);
+
+///Reads a named ADC channel, like `read_adc`. Returns NaN if no channel has this name, see `navigator_last_error`.
+float read_analog(const char *name);
+
+///Creates a navigator configuration with the default options, released by `navigator_builder_free`.
+NavigatorBuilder *navigator_builder_new();
+
+///Releases a configuration created by `navigator_builder_new`.
+void navigator_builder_free(NavigatorBuilder *builder);
+
+///Sets the size of the navigator led strip (1 is the default).
+void navigator_builder_set_rgb_led_strip_size(NavigatorBuilder *builder, uintptr_t size);
+
+///Sets the navigator version, `Auto` (the default) detects it.
+void navigator_builder_set_navigator_version(NavigatorBuilder *builder, NavigatorVersion version);
+
+///Sets the raspberry pi version, `Auto` (the default) detects it.
+void navigator_builder_set_raspberry_pi_version(NavigatorBuilder *builder, Raspberry version);
+
+///Selects how the hardware is accessed, see `set_backend`.
+void navigator_builder_set_backend(NavigatorBuilder *builder,
+                                   Backend backend,
+                                   const char *socket_path);
+
+///Sets how the board is mounted in the vehicle, see `set_board_orientation`.
+void navigator_builder_set_board_orientation(NavigatorBuilder *builder, Rotation rotation);
+
+///Sets the PWM chip external clock frequency [Hz], see `set_pwm_clock_hz`.
+void navigator_builder_set_pwm_clock_hz(NavigatorBuilder *builder, float clock);
+
+///Applies a configuration file, see `load_config`. Returns false if it can't be read or is invalid, see `navigator_last_error`.
+bool navigator_builder_load_config(NavigatorBuilder *builder,
+                                   const char *path);
+
+///Creates a navigator instance with this configuration, released by `navigator_free`. Returns null if the hardware or daemon can't be accessed, see `navigator_last_error`.
+NavigatorHandle *navigator_builder_build(const NavigatorBuilder *builder);
+
+///Describes the last failure of this thread: why a function returned false, null or NaN, or the failed hardware or daemon access of any function, which then returns NaN, false or 0 values. Valid until the next failure on this thread.
+const char *navigator_last_error();
+
+///Releases a navigator instance created by `navigator_builder_build`.
+void navigator_free(NavigatorHandle *navigator);
+
+///Same as `set_led`, for a navigator instance.
+void navigator_set_led(const NavigatorHandle *navigator, UserLed select, bool state);
+
+///Same as `get_led`, for a navigator instance.
+bool navigator_get_led(const NavigatorHandle *navigator, UserLed select);
+
+///Same as `set_led_toggle`, for a navigator instance.
+void navigator_set_led_toggle(const NavigatorHandle *navigator, UserLed select);
+
+///Same as `set_neopixel`, for a navigator instance.
+void navigator_set_neopixel(const NavigatorHandle *navigator,
+                            const uint8_t (*rgb_array)[3],
+                            uintptr_t length);
+
+///Same as `set_neopixel_rgbw`, for a navigator instance.
+void navigator_set_neopixel_rgbw(const NavigatorHandle *navigator,
+                                 const uint8_t (*rgb_array)[4],
+                                 uintptr_t length);
+
+///Same as `read_adc_all`, for a navigator instance.
+void navigator_read_adc_all(const NavigatorHandle *navigator, float *adc_array, uintptr_t length);
+
+///Same as `read_adc`, for a navigator instance.
+float navigator_read_adc(const NavigatorHandle *navigator, AdcChannel channel);
+
+///Same as `read_analog`, for a navigator instance, with the channel names of its configuration file.
+float navigator_read_analog(const NavigatorHandle *navigator,
+                            const char *name);
+
+///Same as `read_pressure`, for a navigator instance.
+float navigator_read_pressure(const NavigatorHandle *navigator);
+
+///Same as `read_temp`, for a navigator instance.
+float navigator_read_temp(const NavigatorHandle *navigator);
+
+///Same as `read_leak`, for a navigator instance.
+bool navigator_read_leak(const NavigatorHandle *navigator);
+
+///Same as `read_mag`, for a navigator instance.
+AxisData navigator_read_mag(const NavigatorHandle *navigator);
+
+///Same as `read_accel`, for a navigator instance.
+AxisData navigator_read_accel(const NavigatorHandle *navigator);
+
+///Same as `read_gyro`, for a navigator instance.
+AxisData navigator_read_gyro(const NavigatorHandle *navigator);
+
+///Same as `read_all`, for a navigator instance.
+SensorData navigator_read_all(const NavigatorHandle *navigator);
+
+///Same as `set_accel_range`, for a navigator instance.
+void navigator_set_accel_range(const NavigatorHandle *navigator, AccelRange range);
+
+///Same as `set_gyro_range`, for a navigator instance.
+void navigator_set_gyro_range(const NavigatorHandle *navigator, GyroRange range);
+
+///Same as `set_imu_dlpf`, for a navigator instance.
+void navigator_set_imu_dlpf(const NavigatorHandle *navigator, ImuDlpf dlpf);
+
+///Same as `set_imu_sample_rate`, for a navigator instance. Returns false if the rate is out of range, see `navigator_last_error`.
+bool navigator_set_imu_sample_rate(const NavigatorHandle *navigator,
+                                   float rate_hz);
+
+///Same as `read_imu_fifo`, for a navigator instance.
+bool navigator_read_imu_fifo(const NavigatorHandle *navigator,
+                             ImuSample *samples,
+                             uintptr_t capacity,
+                             uintptr_t *count);
+
+///Same as `read_vibration`, from the readings of a navigator instance.
+VibrationData navigator_read_vibration(const NavigatorHandle *navigator);
+
+///Same as `reset_vibration`, for a navigator instance.
+void navigator_reset_vibration(const NavigatorHandle *navigator);
+
+///Same as `get_sensor_health`, from the reads of a navigator instance.
+uintptr_t navigator_get_sensor_health(const NavigatorHandle *navigator,
+                                      SensorHealth *health,
+                                      uintptr_t length);
+
+///Same as `reset_sensor_health`, for a navigator instance.
+void navigator_reset_sensor_health(const NavigatorHandle *navigator);
+
+///Same as `read_adc_checked`, for a navigator instance.
+CheckedValue navigator_read_adc_checked(const NavigatorHandle *navigator, AdcChannel channel);
+
+///Same as `read_pressure_checked`, for a navigator instance.
+CheckedValue navigator_read_pressure_checked(const NavigatorHandle *navigator);
+
+///Same as `read_temp_checked`, for a navigator instance.
+CheckedValue navigator_read_temp_checked(const NavigatorHandle *navigator);
+
+///Same as `read_mag_checked`, for a navigator instance.
+CheckedAxisData navigator_read_mag_checked(const NavigatorHandle *navigator);
+
+///Same as `read_accel_checked`, for a navigator instance.
+CheckedAxisData navigator_read_accel_checked(const NavigatorHandle *navigator);
+
+///Same as `read_gyro_checked`, for a navigator instance.
+CheckedAxisData navigator_read_gyro_checked(const NavigatorHandle *navigator);
+
+///Same as `set_low_pass_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool navigator_set_low_pass_filter(const NavigatorHandle *navigator,
+                                   FilterChannel channel,
+                                   float sample_rate,
+                                   float cutoff);
+
+///Same as `set_notch_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool navigator_set_notch_filter(const NavigatorHandle *navigator,
+                                FilterChannel channel,
+                                float sample_rate,
+                                float center,
+                                float bandwidth);
+
+///Same as `set_one_euro_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool navigator_set_one_euro_filter(const NavigatorHandle *navigator,
+                                   FilterChannel channel,
+                                   float min_cutoff,
+                                   float beta);
+
+///Same as `set_moving_average_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool navigator_set_moving_average_filter(const NavigatorHandle *navigator,
+                                         FilterChannel channel,
+                                         uintptr_t window);
+
+///Same as `set_median_filter`, for the reads of a navigator instance. Returns false if the parameters are invalid, see `navigator_last_error`.
+bool navigator_set_median_filter(const NavigatorHandle *navigator,
+                                 FilterChannel channel,
+                                 uintptr_t window);
+
+///Same as `clear_filter`, for a navigator instance.
+void navigator_clear_filter(const NavigatorHandle *navigator, FilterChannel channel);
+
+///Same as `read_adc_all_filtered`, for a navigator instance.
+void navigator_read_adc_all_filtered(const NavigatorHandle *navigator,
+                                     float *adc_array,
+                                     uintptr_t length);
+
+///Same as `read_adc_filtered`, for a navigator instance.
+float navigator_read_adc_filtered(const NavigatorHandle *navigator, AdcChannel channel);
+
+///Same as `read_pressure_filtered`, for a navigator instance.
+float navigator_read_pressure_filtered(const NavigatorHandle *navigator);
+
+///Same as `read_temp_filtered`, for a navigator instance.
+float navigator_read_temp_filtered(const NavigatorHandle *navigator);
+
+///Same as `read_mag_filtered`, for a navigator instance.
+AxisData navigator_read_mag_filtered(const NavigatorHandle *navigator);
+
+///Same as `read_accel_filtered`, for a navigator instance.
+AxisData navigator_read_accel_filtered(const NavigatorHandle *navigator);
+
+///Same as `read_gyro_filtered`, for a navigator instance.
+AxisData navigator_read_gyro_filtered(const NavigatorHandle *navigator);
+
+///Same as `claim_pwm`, for a navigator instance. Returns false if another client claimed the PWM outputs, see `navigator_last_error`.
+bool navigator_claim_pwm(const NavigatorHandle *navigator);
+
+///Same as `release_pwm`, for a navigator instance.
+void navigator_release_pwm(const NavigatorHandle *navigator);
+
+///Same as `set_pwm_enable`, for a navigator instance.
+void navigator_set_pwm_enable(const NavigatorHandle *navigator, bool state);
+
+///Same as `set_pwm_freq_hz`, for a navigator instance. Returns false if the frequency is out of range, see `navigator_last_error`.
+bool navigator_set_pwm_freq_hz(const NavigatorHandle *navigator,
+                               float freq);
+
+///Same as `set_pwm_freq_prescale`, for a navigator instance. Returns false if the value is below 3, see `navigator_last_error`.
+bool navigator_set_pwm_freq_prescale(const NavigatorHandle *navigator,
+                                     uint8_t prescale);
+
+///Same as `set_pwm_output_mode`, for a navigator instance.
+void navigator_set_pwm_output_mode(const NavigatorHandle *navigator, PwmOutputMode mode);
+
+///Same as `set_pwm_invert`, for a navigator instance.
+void navigator_set_pwm_invert(const NavigatorHandle *navigator, bool inverted);
+
+///Same as `set_pwm_channel_value`, for a navigator instance. Returns false if the channel is invalid, see `navigator_last_error`.
+bool navigator_set_pwm_channel_value(const NavigatorHandle *navigator,
+                                     PwmChannelArg channel,
+                                     float value);
+
+///Same as `set_pwm_channel_duty_cycle`, for a navigator instance. Returns false if the channel is invalid, see `navigator_last_error`.
+bool navigator_set_pwm_channel_duty_cycle(const NavigatorHandle *navigator,
+                                          PwmChannelArg channel,
+                                          float duty_cycle);
+
+///Same as `set_pwm_channel_full_on`, for a navigator instance. Returns false if the channel is invalid, see `navigator_last_error`.
+bool navigator_set_pwm_channel_full_on(const NavigatorHandle *navigator,
+                                       PwmChannelArg channel);
+
+///Same as `set_pwm_channel_full_off`, for a navigator instance. Returns false if the channel is invalid, see `navigator_last_error`.
+bool navigator_set_pwm_channel_full_off(const NavigatorHandle *navigator,
+                                        PwmChannelArg channel);
+
+///Same as `set_pwm_channel_on_off`, for a navigator instance. Returns false if a counter is above 4095 or the channel is invalid, see `navigator_last_error`.
+bool navigator_set_pwm_channel_on_off(const NavigatorHandle *navigator,
+                                      PwmChannelArg channel,
+                                      uint16_t on,
+                                      uint16_t off);
+
+///Same as `set_pwm_phase_stagger`, for a navigator instance.
+void navigator_set_pwm_phase_stagger(const NavigatorHandle *navigator, bool enabled);
+
+///Same as `set_output`, for a navigator instance, with the channel names of its configuration file.
+bool navigator_set_output(const NavigatorHandle *navigator, const char *name, float duty_cycle);
+
+///Same as `set_pwm_channels_duty_cycle_values`, for a navigator instance. Returns false if a channel is invalid, see `navigator_last_error`.
+bool navigator_set_pwm_channels_duty_cycle_values(const NavigatorHandle *navigator,
+                                                  const uintptr_t *channels,
+                                                  const float *duty_cycle_values,
+                                                  uintptr_t length);
+
+///Same as `get_pwm_state`, for a navigator instance.
+PwmState navigator_get_pwm_state(const NavigatorHandle *navigator);
+
+///Same as `set_pwm_slew_rate`, for a navigator instance. Returns false if the channel is invalid, see `navigator_last_error`.
+bool navigator_set_pwm_slew_rate(const NavigatorHandle *navigator,
+                                 PwmChannelArg channel,
+                                 float rate);
+
+///Same as `ramp_pwm_channel`, for a navigator instance. Returns false if the channel is invalid, see `navigator_last_error`.
+bool navigator_ramp_pwm_channel(const NavigatorHandle *navigator,
+                                PwmChannelArg channel,
+                                float target,
+                                uint32_t duration_ms);
+
+///Same as `stop_pwm_ramp`, for a navigator instance. Returns false if the channel is invalid, see `navigator_last_error`.
+bool navigator_stop_pwm_ramp(const NavigatorHandle *navigator,
+                             PwmChannelArg channel);
+
+} // extern "C"